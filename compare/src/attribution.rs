@@ -0,0 +1,219 @@
+use std::collections::BTreeMap;
+
+use anyhow::Context;
+use wasmparser::{KnownCustom, Name, Parser, Payload, TypeRef};
+
+/// Where the bytes of a wasm module go, by function and by crate.
+///
+/// Functions are named by the `name` section, which is kept unless the
+/// build strips symbols; wasm-opt removes it, so this is for the wasm as
+/// built.
+#[derive(Debug, Clone, Default)]
+pub struct Attribution {
+    /// The size of the module.
+    pub total: u64,
+    /// The bodies of the functions, every byte of them is attributed to a
+    /// function.
+    pub code: u64,
+    /// The data segments: string literals, constants, vtables.
+    pub data: u64,
+    /// Whether there was a `name` section, without it every function is
+    /// `UNKNOWN_CRATE`.
+    pub has_names: bool,
+    /// Largest first.
+    pub functions: Vec<Function>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Function {
+    /// Demangled, without the hash.
+    pub name: String,
+    /// See `crate_of`.
+    pub crate_name: String,
+    /// Of the body in the code section.
+    pub size: u64,
+}
+
+/// The crate of functions without a name or with a name that is not a Rust
+/// path.
+pub const UNKNOWN_CRATE: &str = "?";
+
+/// What crates are shown as, e.g. `core`, `alloc` and `std` together as
+/// `std`. The crates that are in no group are in `OTHER_GROUP`.
+pub type CrateGroups = BTreeMap<String, Vec<String>>;
+
+/// The group of the crates that are in no other group: the framework, its
+/// dependencies and the implementation itself.
+pub const OTHER_GROUP: &str = "framework";
+
+/// Parse a wasm module and attribute the bytes of its functions.
+pub fn analyze(wasm: &[u8]) -> anyhow::Result<Attribution> {
+    let mut attribution = Attribution {
+        total: wasm.len() as u64,
+        ..Attribution::default()
+    };
+    let mut imported_functions = 0;
+    // By function index, imports first
+    let mut sizes = Vec::new();
+    let mut names = BTreeMap::new();
+    for payload in Parser::new(0).parse_all(wasm) {
+        match payload.context("invalid wasm")? {
+            Payload::ImportSection(imports) => {
+                for import in imports {
+                    if matches!(import?.ty, TypeRef::Func(_)) {
+                        imported_functions += 1;
+                    }
+                }
+            }
+            Payload::CodeSectionEntry(body) => {
+                let range = body.range();
+                sizes.push((range.end - range.start) as u64);
+            }
+            Payload::DataSection(data) => attribution.data += (data.range().end - data.range().start) as u64,
+            Payload::CustomSection(custom) => {
+                if let KnownCustom::Name(reader) = custom.as_known() {
+                    attribution.has_names = true;
+                    for name in reader {
+                        if let Name::Function(map) = name? {
+                            for naming in map {
+                                let naming = naming?;
+                                names.insert(naming.index, naming.name);
+                            }
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    attribution.code = sizes.iter().sum();
+    attribution.functions = sizes
+        .into_iter()
+        .enumerate()
+        .map(|(i, size)| {
+            let name = match names.get(&(imported_functions + i as u32)) {
+                Some(name) => demangle(name),
+                None => format!("function[{}]", imported_functions + i as u32),
+            };
+            Function {
+                crate_name: crate_of(&name).to_string(),
+                name,
+                size,
+            }
+        })
+        .collect();
+    attribution.functions.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+    Ok(attribution)
+}
+
+impl Attribution {
+    /// Bytes of code by crate, largest first.
+    pub fn by_crate(&self) -> Vec<(String, u64)> {
+        let mut crates = BTreeMap::new();
+        for function in &self.functions {
+            *crates.entry(function.crate_name.clone()).or_insert(0) += function.size;
+        }
+        largest_first(crates)
+    }
+
+    /// Bytes of code by group, in the order of `groups` with `OTHER_GROUP`
+    /// then `UNKNOWN_CRATE` last, including empty groups.
+    pub fn by_group(&self, groups: &CrateGroups) -> Vec<(String, u64)> {
+        let mut sizes: Vec<(String, u64)> = groups.keys().map(|group| (group.clone(), 0)).collect();
+        sizes.push((OTHER_GROUP.to_string(), 0));
+        sizes.push((UNKNOWN_CRATE.to_string(), 0));
+        for (crate_name, size) in self.by_crate() {
+            let group = match crate_name.as_str() {
+                UNKNOWN_CRATE => UNKNOWN_CRATE,
+                _ => groups
+                    .iter()
+                    .find(|(_, crates)| crates.contains(&crate_name))
+                    .map_or(OTHER_GROUP, |(group, _)| group.as_str()),
+            };
+            sizes.iter_mut().find(|(g, _)| g == group).unwrap().1 += size;
+        }
+        sizes
+    }
+}
+
+fn largest_first(sizes: BTreeMap<String, u64>) -> Vec<(String, u64)> {
+    let mut sizes: Vec<_> = sizes.into_iter().collect();
+    sizes.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    sizes
+}
+
+/// The crate of a demangled function name: the first segment of its path,
+/// or of the type of a trait impl, e.g. `serde_json` for
+/// `<serde_json::Error as core::fmt::Display>::fmt`. Functions generated by
+/// wasm-bindgen (`__wbindgen_malloc`, `__wbg_...`) are `wasm_bindgen`.
+pub fn crate_of(name: &str) -> &str {
+    if name.starts_with("__wbindgen") || name.starts_with("__wbg") {
+        return "wasm_bindgen";
+    }
+    let path = name.trim_start_matches(['<', '&', '*']).trim_start_matches("mut ").trim_start_matches("dyn ");
+    match path.find("::") {
+        Some(end) if is_identifier(&path[..end]) => &path[..end],
+        _ => UNKNOWN_CRATE,
+    }
+}
+
+fn is_identifier(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Demangle a legacy Rust symbol (`_ZN4core3fmt5write17h0123456789abcdefE`
+/// is `core::fmt::write`), and remove the hash of a name that wasm-bindgen
+/// already demangled. Other names are returned as they are.
+pub fn demangle(name: &str) -> String {
+    let demangled = match name.strip_prefix("_ZN").and_then(demangle_legacy) {
+        Some(demangled) => demangled,
+        None => name.to_string(),
+    };
+    match demangled.rsplit_once("::h") {
+        Some((path, hash)) if hash.len() == 16 && hash.chars().all(|c| c.is_ascii_hexdigit()) => path.to_string(),
+        _ => demangled,
+    }
+}
+
+fn demangle_legacy(mut rest: &str) -> Option<String> {
+    let mut segments = Vec::new();
+    while !rest.starts_with('E') {
+        let digits = rest.chars().take_while(char::is_ascii_digit).count();
+        let len: usize = rest[..digits].parse().ok()?;
+        let segment = rest.get(digits..digits + len)?;
+        segments.push(unescape(segment));
+        rest = &rest[digits + len..];
+    }
+    Some(segments.join("::"))
+}
+
+// The escapes of legacy symbols, e.g. `$LT$` for `<`
+fn unescape(segment: &str) -> String {
+    const ESCAPES: &[(&str, &str)] = &[
+        ("$SP$", "@"),
+        ("$BP$", "*"),
+        ("$RF$", "&"),
+        ("$LT$", "<"),
+        ("$GT$", ">"),
+        ("$LP$", "("),
+        ("$RP$", ")"),
+        ("$C$", ","),
+        ("$u7e$", "~"),
+        ("$u20$", " "),
+        ("$u27$", "'"),
+        ("$u5b$", "["),
+        ("$u5d$", "]"),
+        ("$u7b$", "{"),
+        ("$u7d$", "}"),
+        ("$u3b$", ";"),
+        ("$u2b$", "+"),
+        ("$u22$", "\""),
+    ];
+    let segment = segment.strip_prefix('_').filter(|s| s.starts_with('$')).unwrap_or(segment);
+    let mut segment = segment.replace("..", "::");
+    for (escape, c) in ESCAPES {
+        segment = segment.replace(escape, c);
+    }
+    segment
+}
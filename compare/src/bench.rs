@@ -0,0 +1,314 @@
+use std::path::Path;
+
+use anyhow::Context;
+
+use crate::browser::{Browser, Page, Server};
+
+/// An operation timed on every implementation, as in js-framework-benchmark
+/// but through the TodoMVC selectors.
+pub struct Operation {
+    /// Short and stable, it is in the history.
+    pub id: &'static str,
+    pub description: &'static str,
+    /// How many todos are added before, every other one completed with
+    /// `completed`.
+    todos: usize,
+    completed: bool,
+    /// Whether every run starts from a new session. Otherwise the todos are
+    /// added once and the action has to be repeatable.
+    fresh: bool,
+    /// JS statements, in an async function with `C` the helpers of
+    /// `HELPERS`. The time starts before them.
+    action: &'static str,
+    /// A JS expression that is true once the action is rendered, the time
+    /// stops then.
+    done: &'static str,
+}
+
+/// In the order they are reported.
+pub const OPERATIONS: &[Operation] = &[
+    Operation {
+        id: "add-1000",
+        description: "add 1,000 todos",
+        todos: 0,
+        completed: false,
+        fresh: true,
+        action: "for (let i = 0; i < 1000; i++) C.add('todo ' + i);",
+        done: "C.todos().length === 1000",
+    },
+    Operation {
+        id: "toggle-all",
+        description: "complete 1,000 todos with `.toggle-all`",
+        todos: 1000,
+        completed: false,
+        fresh: true,
+        action: "document.querySelector('.toggle-all').click();",
+        done: "C.completed() === 1000",
+    },
+    Operation {
+        id: "toggle-every-10th",
+        description: "complete every 10th of 1,000 todos",
+        todos: 1000,
+        completed: false,
+        fresh: true,
+        action: "C.todos().forEach((li, i) => { if (i % 10 === 0) li.querySelector('.toggle').click(); });",
+        done: "C.completed() === 100",
+    },
+    Operation {
+        id: "edit-in-10k",
+        description: "edit a todo in a list of 10,000",
+        todos: 10_000,
+        completed: false,
+        fresh: false,
+        action: "window.edits = (window.edits ?? 0) + 1; await C.edit(5000, 'edited ' + edits);",
+        done: "C.label(5000) === 'edited ' + edits && document.querySelector('.todo-list > li.editing') === null",
+    },
+    Operation {
+        id: "remove-every-other",
+        description: "remove every other of 1,000 todos",
+        todos: 1000,
+        completed: false,
+        fresh: true,
+        // A todo at a time, a framework that renders later would be clicked
+        // on the todos that it removed otherwise
+        action: "for (let i = 0; i < 500; i++) { C.todos()[i].querySelector('.destroy').click(); \
+                 await C.until(() => C.todos().length === 999 - i); }",
+        done: "true",
+    },
+    Operation {
+        id: "switch-filters",
+        description: "show the active, completed then all of 1,000 todos",
+        todos: 1000,
+        completed: true,
+        fresh: true,
+        action: "for (const [name, count] of [['Active', 500], ['Completed', 500], ['All', 1000]]) { C.filter(name); \
+                 await C.until(() => C.todos().length === count && C.completed() === (name === 'Active' ? 0 : 500)); }",
+        done: "true",
+    },
+    Operation {
+        id: "clear-completed",
+        description: "clear 500 completed of 1,000 todos",
+        todos: 1000,
+        completed: true,
+        fresh: true,
+        action: "document.querySelector('.clear-completed').click();",
+        done: "C.todos().length === 500",
+    },
+];
+
+/// Helpers of the operations, as `window.C`. Events are dispatched in JS to
+/// add thousands of todos quickly: `input` then every key event of Enter, as
+/// the implementations listen to different ones.
+///
+/// `C.count()` starts counting the DOM mutations, with a `MutationObserver`
+/// and by wrapping the setters of the properties that it cannot see, until
+/// `C.counted()`. The helpers set values with the setter they saved before,
+/// so that only what the implementations do is counted.
+pub(crate) const HELPERS: &str = "const VALUE = Object.getOwnPropertyDescriptor(HTMLInputElement.prototype, 'value');
+window.C = {
+    tick: () => new Promise(resolve => { const c = new MessageChannel(); c.port1.onmessage = resolve; c.port2.postMessage(0); }),
+    until: async (condition, timeout = 60000) => {
+        const start = performance.now();
+        while (!condition()) {
+            if (performance.now() - start > timeout) throw new Error('timed out');
+            await C.tick();
+        }
+    },
+    todos: () => [...document.querySelectorAll('.todo-list > li')].filter(li => li.getClientRects().length > 0),
+    completed: () => C.todos().filter(li => li.classList.contains('completed')).length,
+    label: index => (C.todos()[index]?.querySelector('label')?.innerText ?? '').trim(),
+    enter: input => ['keydown', 'keypress', 'keyup'].forEach(type => input.dispatchEvent(
+        new KeyboardEvent(type, { key: 'Enter', code: 'Enter', keyCode: 13, which: 13, bubbles: true, cancelable: true }))),
+    add: text => {
+        const input = document.querySelector('.new-todo');
+        VALUE.set.call(input, text);
+        input.dispatchEvent(new Event('input', { bubbles: true }));
+        C.enter(input);
+    },
+    edit: async (index, text) => {
+        C.todos()[index].querySelector('label').dispatchEvent(new MouseEvent('dblclick', { bubbles: true }));
+        await C.until(() => document.querySelector('.todo-list > li .edit') !== null);
+        const input = document.querySelector('.todo-list > li .edit');
+        VALUE.set.call(input, text);
+        input.dispatchEvent(new Event('input', { bubbles: true }));
+        C.enter(input);
+    },
+    filter: name => [...document.querySelectorAll('.filters a')].find(a => a.innerText.trim() === name).click(),
+    count: () => {
+        C.mutations = { insertions: 0, removals: 0, attributes: 0, properties: 0, text: 0 };
+        for (const [prototype, name] of [[HTMLInputElement.prototype, 'value'], [HTMLInputElement.prototype, 'checked']]) {
+            const property = Object.getOwnPropertyDescriptor(prototype, name);
+            Object.defineProperty(prototype, name, {
+                ...property,
+                set(value) { C.mutations.properties++; property.set.call(this, value); },
+            });
+        }
+        C.observer = new MutationObserver(C.record);
+        C.observer.observe(document.body, { subtree: true, childList: true, attributes: true, characterData: true });
+    },
+    record: records => records.forEach(record => {
+        if (record.type === 'childList') {
+            C.mutations.insertions += record.addedNodes.length;
+            C.mutations.removals += record.removedNodes.length;
+        } else if (record.type === 'attributes') {
+            C.mutations.attributes++;
+        } else {
+            C.mutations.text++;
+        }
+    }),
+    counted: () => {
+        C.record(C.observer.takeRecords());
+        C.observer.disconnect();
+        return C.mutations;
+    },
+};";
+
+/// The timings of an operation on an implementation, in milliseconds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stats {
+    pub median: f64,
+    /// The 95% confidence interval of the median.
+    pub low: f64,
+    pub high: f64,
+    pub runs: usize,
+}
+
+impl Stats {
+    /// The median of `samples` and its confidence interval, from the order
+    /// statistics: no distribution is assumed, timings are skewed.
+    pub fn of(samples: &[f64]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        let n = sorted.len();
+        let median = match n % 2 {
+            1 => sorted[n / 2],
+            _ => (sorted[n / 2 - 1] + sorted[n / 2]) / 2.0,
+        };
+        // The ranks (from 1) of the bounds: n/2 -+ 1.96 sqrt(n)/2
+        let spread = 1.96 * (n as f64).sqrt() / 2.0;
+        let low = ((n as f64 / 2.0 - spread).floor() as usize).clamp(1, n);
+        let high = ((1.0 + n as f64 / 2.0 + spread).ceil() as usize).clamp(1, n);
+        Some(Self {
+            median,
+            low: sorted[low - 1],
+            high: sorted[high - 1],
+            runs: n,
+        })
+    }
+}
+
+/// The DOM mutations of an operation on an implementation, the same on every
+/// run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize)]
+pub struct Mutations {
+    /// Nodes inserted and removed, a moved node is both.
+    pub insertions: u64,
+    pub removals: u64,
+    /// Attributes set, `class` included.
+    pub attributes: u64,
+    /// `value` and `checked` of the inputs set, which are not attributes.
+    pub properties: u64,
+    /// Text nodes changed in place.
+    pub text: u64,
+}
+
+impl Mutations {
+    pub fn total(&self) -> u64 {
+        self.insertions + self.removals + self.attributes + self.properties + self.text
+    }
+}
+
+/// An operation on an implementation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timing {
+    /// See `Operation::id`.
+    pub id: String,
+    pub outcome: Result<Stats, String>,
+    /// Counted on a run of its own, the observer would slow down the timed
+    /// ones. `None` when the timed runs failed, as they set the run up.
+    pub mutations: Option<Result<Mutations, String>>,
+}
+
+/// Time every operation on the build in `dist`, `runs` times after a run
+/// that warms up, then count its mutations.
+pub fn run(browser: &mut Browser, dist: &Path, runs: usize) -> anyhow::Result<Vec<Timing>> {
+    let server = Server::start(dist)?;
+    let mut page = browser.open(&server.url)?;
+    let mut timings = Vec::new();
+    for operation in OPERATIONS {
+        let outcome = time(&mut page, operation, runs).and_then(|samples| Stats::of(&samples).context("no runs"));
+        let mutations = match outcome {
+            Ok(_) => Some(count(&mut page, operation).map_err(|e| format!("{e:#}"))),
+            Err(_) => None,
+        };
+        timings.push(Timing {
+            id: operation.id.to_string(),
+            outcome: outcome.map_err(|e| format!("{e:#}")),
+            mutations,
+        });
+    }
+    Ok(timings)
+}
+
+/// The operation of an id, `None` for operations that are in the history
+/// but no longer run.
+pub fn operation(id: &str) -> Option<&'static Operation> {
+    OPERATIONS.iter().find(|operation| operation.id == id)
+}
+
+fn time(page: &mut Page, operation: &Operation, runs: usize) -> anyhow::Result<Vec<f64>> {
+    let measure = format!(
+        "(async () => {{ const start = performance.now(); {} await C.until(() => {}); \
+         document.body.offsetHeight; return performance.now() - start; }})()",
+        operation.action, operation.done
+    );
+    let mut samples = Vec::new();
+    for run in 0..=runs {
+        if run == 0 || operation.fresh {
+            start(page, operation).context("before the operation")?;
+        }
+        let elapsed: f64 = page.eval(&measure)?;
+        // The first run warms up
+        if run > 0 {
+            samples.push(elapsed);
+        }
+    }
+    Ok(samples)
+}
+
+// After `time`, the page is where a run of the operation can start unless it
+// starts fresh
+fn count(page: &mut Page, operation: &Operation) -> anyhow::Result<Mutations> {
+    if operation.fresh {
+        start(page, operation)?;
+    }
+    page.eval(&format!(
+        "(async () => {{ C.count(); {} await C.until(() => {}); return C.counted(); }})()",
+        operation.action, operation.done
+    ))
+}
+
+// In milliseconds, adding 10,000 todos one at a time takes a while
+const SETUP_TIMEOUT: u32 = 600_000;
+
+// A new session with the todos of the operation
+fn start(page: &mut Page, operation: &Operation) -> anyhow::Result<()> {
+    page.reset()?;
+    page.eventually("document.querySelector('.new-todo') !== null", true).context("no `.new-todo`")?;
+    page.run(HELPERS)?;
+    page.eval::<serde_json::Value>(&format!(
+        "(async () => {{ for (let i = 0; i < {todos}; i++) C.add('todo ' + i); await C.until(() => C.todos().length === {todos}, {SETUP_TIMEOUT}); }})()",
+        todos = operation.todos
+    ))?;
+    if operation.completed {
+        page.eval::<serde_json::Value>(&format!(
+            "(async () => {{ C.todos().forEach((li, i) => {{ if (i % 2) li.querySelector('.toggle').click(); }}); \
+             await C.until(() => C.completed() === {}, {SETUP_TIMEOUT}); }})()",
+            operation.todos / 2
+        ))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,349 @@
+use std::io::{BufRead, BufReader};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, bail, Context};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use tungstenite::{Message, WebSocket};
+
+/// How long a page has to load, and to get to what `Page::eventually`
+/// expects.
+pub const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Serves the files of a build on a free port of localhost, until it is
+/// dropped.
+pub struct Server {
+    /// e.g. `http://127.0.0.1:41013/`.
+    pub url: String,
+    server: Arc<tiny_http::Server>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Server {
+    pub fn start(dist: &Path) -> anyhow::Result<Self> {
+        let server = tiny_http::Server::http("127.0.0.1:0").map_err(|e| anyhow!("cannot start a server: {e}"))?;
+        let port = server.server_addr().to_ip().context("the server has no port")?.port();
+        let server = Arc::new(server);
+        let thread = {
+            let server = server.clone();
+            let dist = dist.to_path_buf();
+            std::thread::spawn(move || {
+                for request in server.incoming_requests() {
+                    respond(&dist, request);
+                }
+            })
+        };
+        Ok(Self {
+            url: format!("http://127.0.0.1:{port}/"),
+            server,
+            thread: Some(thread),
+        })
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.server.unblock();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn respond(dist: &Path, request: tiny_http::Request) {
+    let path = request.url().split(['?', '#']).next().unwrap_or_default().trim_start_matches('/');
+    let path = match path.is_empty() {
+        true => "index.html",
+        false => path,
+    };
+    let file = match path.split('/').any(|segment| segment == "..") {
+        true => None,
+        false => std::fs::read(dist.join(path)).ok(),
+    };
+    // Errors are the browser that went away
+    let _ = match file {
+        Some(bytes) => {
+            let header = tiny_http::Header::from_bytes("Content-Type", content_type(path)).unwrap();
+            request.respond(tiny_http::Response::from_data(bytes).with_header(header))
+        }
+        None => request.respond(tiny_http::Response::empty(404)),
+    };
+}
+
+/// The type of the files trunk builds, wasm has to be `application/wasm` to
+/// be compiled while it downloads.
+pub fn content_type(path: &str) -> &'static str {
+    match path.rsplit_once('.').map(|(_, extension)| extension) {
+        Some("html") => "text/html; charset=utf-8",
+        Some("js") => "text/javascript",
+        Some("wasm") => "application/wasm",
+        Some("css") => "text/css",
+        Some("json") => "application/json",
+        Some("svg") => "image/svg+xml",
+        _ => "application/octet-stream",
+    }
+}
+
+/// A headless Chrome or Chromium, driven with the DevTools protocol.
+///
+/// The browser is the one of the `CHROME` environment variable, or the first
+/// of `google-chrome`, `chromium`... in the `PATH`. It is killed when this is
+/// dropped.
+pub struct Browser {
+    process: Child,
+    profile: PathBuf,
+    connection: Connection,
+    // `ws://127.0.0.1:<port>`
+    endpoint: String,
+}
+
+const EXECUTABLES: &[&str] = &["google-chrome", "google-chrome-stable", "chromium", "chromium-browser", "chrome"];
+
+impl Browser {
+    pub fn launch() -> anyhow::Result<Self> {
+        let executable = match std::env::var_os("CHROME") {
+            Some(path) => PathBuf::from(path),
+            None => find_executable().context("no Chrome or Chromium found, set CHROME to its path")?,
+        };
+        let profile = std::env::temp_dir().join(format!("compare-chrome-{}", std::process::id()));
+        let mut process = Command::new(&executable)
+            .args([
+                "--headless=new",
+                "--remote-debugging-port=0",
+                "--no-first-run",
+                "--no-default-browser-check",
+                "--disable-extensions",
+                "--disable-background-timer-throttling",
+                "--disable-renderer-backgrounding",
+            ])
+            .arg(format!("--user-data-dir={}", profile.display()))
+            .arg("about:blank")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("cannot run {}", executable.display()))?;
+
+        // Chrome prints where it listens, then whatever it logs
+        let mut stderr = BufReader::new(process.stderr.take().unwrap());
+        let mut url = None;
+        let mut line = String::new();
+        while stderr.read_line(&mut line)? > 0 {
+            if let Some(start) = line.find("ws://") {
+                url = Some(line[start..].trim().to_string());
+                break;
+            }
+            line.clear();
+        }
+        let url = match url {
+            Some(url) => url,
+            None => {
+                let _ = process.kill();
+                bail!("{} exited without listening", executable.display());
+            }
+        };
+        std::thread::spawn(move || std::io::copy(&mut stderr, &mut std::io::sink()));
+
+        let endpoint = url.split("/devtools/").next().unwrap().to_string();
+        Ok(Self {
+            connection: Connection::open(&url)?,
+            process,
+            profile,
+            endpoint,
+        })
+    }
+
+    /// Open `url` in a new tab, once its page is loaded.
+    pub fn open(&mut self, url: &str) -> anyhow::Result<Page> {
+        let target = self.connection.call("Target.createTarget", json!({ "url": "about:blank" }))?;
+        let id = target["targetId"].as_str().context("no target id")?;
+        let mut page = Page {
+            connection: Connection::open(&format!("{}/devtools/page/{id}", self.endpoint))?,
+            url: url.to_string(),
+        };
+        page.load()?;
+        Ok(page)
+    }
+}
+
+impl Drop for Browser {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+        let _ = std::fs::remove_dir_all(&self.profile);
+    }
+}
+
+fn find_executable() -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    let mut candidates: Vec<PathBuf> = EXECUTABLES
+        .iter()
+        .flat_map(|name| std::env::split_paths(&path).map(move |dir| dir.join(name)))
+        .collect();
+    candidates.push("/Applications/Google Chrome.app/Contents/MacOS/Google Chrome".into());
+    candidates.push("/Applications/Chromium.app/Contents/MacOS/Chromium".into());
+    candidates.into_iter().find(|candidate| candidate.is_file())
+}
+
+/// A websocket to the browser or one of its pages, a DevTools command at a
+/// time.
+struct Connection {
+    socket: WebSocket<TcpStream>,
+    next_id: u64,
+}
+
+impl Connection {
+    fn open(url: &str) -> anyhow::Result<Self> {
+        let address = url.trim_start_matches("ws://").split('/').next().unwrap();
+        let stream = TcpStream::connect(address).with_context(|| format!("cannot connect to {url}"))?;
+        // A page that hangs is a failure, not a hang of the runner. Setting
+        // up the benchmarks can take minutes
+        stream.set_read_timeout(Some(Duration::from_secs(900)))?;
+        let (socket, _) = tungstenite::client(url, stream).map_err(|e| anyhow!("cannot connect to {url}: {e}"))?;
+        Ok(Self { socket, next_id: 1 })
+    }
+
+    /// Send a command and wait for its result, the events on the way are
+    /// ignored.
+    fn call(&mut self, method: &str, params: Value) -> anyhow::Result<Value> {
+        let id = self.next_id;
+        self.next_id += 1;
+        let command = json!({ "id": id, "method": method, "params": params });
+        self.socket.send(Message::text(command.to_string()))?;
+        loop {
+            let message = self.socket.read()?;
+            let text = match &message {
+                Message::Text(text) => text.as_str(),
+                Message::Close(_) => bail!("the browser closed the connection"),
+                _ => continue,
+            };
+            let mut response: Value = serde_json::from_str(text)?;
+            if response["id"] != id {
+                continue;
+            }
+            if let Some(error) = response.get("error") {
+                bail!("{method}: {}", error["message"].as_str().unwrap_or("failed"));
+            }
+            return Ok(response["result"].take());
+        }
+    }
+}
+
+/// A tab of the browser with an app.
+pub struct Page {
+    connection: Connection,
+    url: String,
+}
+
+impl Page {
+    /// Load the app again, e.g. to check what it restores.
+    pub fn load(&mut self) -> anyhow::Result<()> {
+        self.connection.call("Page.navigate", json!({ "url": self.url }))?;
+        self.eventually("document.readyState === 'complete'", true).context("the page did not load")
+    }
+
+    /// Load the app without anything it saved.
+    pub fn reset(&mut self) -> anyhow::Result<()> {
+        self.run("localStorage.clear(); sessionStorage.clear()")?;
+        self.load()
+    }
+
+    /// The value of a JS expression, as JSON. `undefined` is `null`, a
+    /// promise is awaited for its value.
+    pub fn eval<T: DeserializeOwned>(&mut self, expression: &str) -> anyhow::Result<T> {
+        let result = self.connection.call(
+            "Runtime.evaluate",
+            json!({
+                "expression": format!("(async () => JSON.stringify((await ({expression})) ?? null))()"),
+                "returnByValue": true,
+                "awaitPromise": true,
+            }),
+        )?;
+        if let Some(exception) = result.get("exceptionDetails") {
+            let description = exception["exception"]["description"].as_str();
+            bail!("`{expression}` threw {}", description.or(exception["text"].as_str()).unwrap_or("an exception"));
+        }
+        let json = result["result"]["value"].as_str().context("no value")?;
+        serde_json::from_str(json).with_context(|| format!("unexpected value of `{expression}`: {json}"))
+    }
+
+    /// Run a JS script in every document loaded from now on, before the
+    /// scripts of the document.
+    pub fn add_script(&mut self, source: &str) -> anyhow::Result<()> {
+        self.connection.call("Page.addScriptToEvaluateOnNewDocument", json!({ "source": source })).map(drop)
+    }
+
+    /// The bytes used by the JS heap, after a garbage collection.
+    pub fn heap_after_gc(&mut self) -> anyhow::Result<u64> {
+        self.connection.call("HeapProfiler.collectGarbage", json!({}))?;
+        let usage = self.connection.call("Runtime.getHeapUsage", json!({}))?;
+        usage["usedSize"].as_f64().map(|bytes| bytes as u64).context("no heap size")
+    }
+
+    /// Run JS statements.
+    pub fn run(&mut self, statements: &str) -> anyhow::Result<()> {
+        self.eval::<Value>(&format!("(() => {{ {statements} }})()")).map(drop)
+    }
+
+    /// Wait until `expression` is `expected`, as the frameworks may render
+    /// in a later task. Fails with the last value after `TIMEOUT`.
+    pub fn eventually<T>(&mut self, expression: &str, expected: T) -> anyhow::Result<()>
+    where
+        T: DeserializeOwned + PartialEq + std::fmt::Debug,
+    {
+        let start = Instant::now();
+        loop {
+            let value = self.eval::<T>(expression);
+            match value {
+                Ok(value) if value == expected => return Ok(()),
+                Ok(value) if start.elapsed() > TIMEOUT => bail!("expected {expected:?}, got {value:?}"),
+                Err(e) if start.elapsed() > TIMEOUT => return Err(e),
+                _ => std::thread::sleep(Duration::from_millis(20)),
+            }
+        }
+    }
+
+    /// Type text in the focused element, as an IME would: an `input` event
+    /// without key events.
+    pub fn insert_text(&mut self, text: &str) -> anyhow::Result<()> {
+        self.connection.call("Input.insertText", json!({ "text": text })).map(drop)
+    }
+
+    /// Press and release a key, `Enter`, `Escape` or `Backspace`.
+    pub fn press_key(&mut self, key: &str) -> anyhow::Result<()> {
+        let (code, text) = match key {
+            "Enter" => (13, "\r"),
+            "Escape" => (27, ""),
+            "Backspace" => (8, ""),
+            _ => bail!("unknown key {key}"),
+        };
+        let event = |kind: &str, text: &str| {
+            json!({
+                "type": kind,
+                "key": key,
+                "code": key,
+                "windowsVirtualKeyCode": code,
+                "nativeVirtualKeyCode": code,
+                "text": text,
+            })
+        };
+        // As puppeteer does: only keys with a text have a `keypress`
+        let down = match text.is_empty() {
+            true => event("rawKeyDown", ""),
+            false => event("keyDown", text),
+        };
+        self.connection.call("Input.dispatchKeyEvent", down)?;
+        self.connection.call("Input.dispatchKeyEvent", event("keyUp", "")).map(drop)
+    }
+}
+
+impl Drop for Page {
+    fn drop(&mut self) {
+        let _ = self.connection.call("Page.close", json!({}));
+    }
+}
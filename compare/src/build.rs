@@ -0,0 +1,92 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{bail, Context};
+
+use crate::matrix::Cell;
+
+/// The artifacts of a cell.
+#[derive(Debug, Clone)]
+pub struct Artifacts {
+    pub dist: PathBuf,
+    pub wasm: PathBuf,
+    /// The JS glue and its snippets.
+    pub js: Vec<PathBuf>,
+}
+
+/// Build a cell with `trunk build --release` into its own dist directory
+/// under `out`. `root` is the workspace root.
+pub fn build(cell: &Cell, root: &Path, out: &Path) -> anyhow::Result<Artifacts> {
+    trunk_build(cell, root, root.join(cell.dist_dir(out)), &[])
+}
+
+/// Build a cell again without stripping symbols, so that its wasm keeps the
+/// `name` section that `attribution` needs, and return that wasm. It goes to
+/// a dist directory of its own, the one of `build` is what is measured.
+pub fn build_with_names(cell: &Cell, root: &Path, out: &Path) -> anyhow::Result<PathBuf> {
+    let mut dist = root.join(cell.dist_dir(out)).into_os_string();
+    dist.push("-names");
+    let env = [("CARGO_PROFILE_RELEASE_STRIP", "none"), ("CARGO_PROFILE_RELEASE_DEBUG", "false")];
+    Ok(trunk_build(cell, root, dist.into(), &env)?.wasm)
+}
+
+// `env` comes after the one of the profile and overrides it
+fn trunk_build(cell: &Cell, root: &Path, dist: PathBuf, env: &[(&str, &str)]) -> anyhow::Result<Artifacts> {
+    if dist.exists() {
+        std::fs::remove_dir_all(&dist).with_context(|| format!("cannot clean {}", dist.display()))?;
+    }
+    let status = Command::new("trunk")
+        .current_dir(root.join(cell.crate_dir()))
+        .args(["build", "--release", "--filehash=false", "--dist"])
+        .arg(&dist)
+        .args(cell.features.args())
+        .envs(cell.profile.env())
+        .envs(env.iter().copied())
+        .status()
+        .context("cannot run trunk, is it installed?")?;
+    if !status.success() {
+        bail!("trunk build failed: {status}");
+    }
+    let wasm = find_file(&dist, "_bg.wasm")?;
+    let mut js = Vec::new();
+    find_files(&dist, "js", &mut js)?;
+    js.sort();
+    Ok(Artifacts { dist, wasm, js })
+}
+
+// Every file with `extension` in `dir` and its subdirectories
+fn find_files(dir: &Path, extension: &str, found: &mut Vec<PathBuf>) -> anyhow::Result<()> {
+    for entry in std::fs::read_dir(dir).with_context(|| format!("cannot read {}", dir.display()))? {
+        let path = entry?.path();
+        if path.is_dir() {
+            find_files(&path, extension, found)?;
+        } else if path.extension().is_some_and(|e| e == extension) {
+            found.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// The file in `dir` whose name ends with `suffix`, there must be one.
+pub fn find_file(dir: &Path, suffix: &str) -> anyhow::Result<PathBuf> {
+    let mut found = Vec::new();
+    for entry in std::fs::read_dir(dir).with_context(|| format!("cannot read {}", dir.display()))? {
+        let path = entry?.path();
+        if path.file_name().and_then(|name| name.to_str()).is_some_and(|name| name.ends_with(suffix)) {
+            found.push(path);
+        }
+    }
+    match found.len() {
+        1 => Ok(found.remove(0)),
+        0 => bail!("no *{suffix} in {}", dir.display()),
+        _ => bail!("more than one *{suffix} in {}", dir.display()),
+    }
+}
+
+/// The first line of `program --version`, e.g. `rustc 1.65.0 (897e37553
+/// 2022-11-02)`.
+pub fn tool_version(program: &str) -> Option<String> {
+    let output = Command::new(program).arg("--version").output().ok()?;
+    let stdout = String::from_utf8(output.stdout).ok()?;
+    stdout.lines().next().map(|line| line.trim().to_string())
+}
@@ -0,0 +1,463 @@
+use std::path::Path;
+
+use anyhow::{bail, Context};
+use serde::{Deserialize, Serialize};
+
+use crate::browser::{Browser, Page, Server};
+
+/// An item of the TodoMVC spec, checked by driving the app through the
+/// standard selectors: `.new-todo`, `.toggle`, `.destroy`, `.filters a`,
+/// `.clear-completed`, `.edit`...
+pub struct SpecItem {
+    /// Short and stable, it is in the history.
+    pub id: &'static str,
+    pub description: &'static str,
+    check: fn(&mut Page) -> anyhow::Result<()>,
+}
+
+/// The outcome of a spec item for an implementation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Check {
+    /// See `SpecItem::id`.
+    pub id: String,
+    /// Why it failed, `None` if it passed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failure: Option<String>,
+}
+
+impl Check {
+    pub fn passed(&self) -> bool {
+        self.failure.is_none()
+    }
+}
+
+/// In the order they are reported.
+pub const SPEC: &[SpecItem] = &[
+    SpecItem {
+        id: "hide-when-empty",
+        description: "`.main` and `.footer` are hidden without todos",
+        check: hide_when_empty,
+    },
+    SpecItem {
+        id: "add",
+        description: "Enter in `.new-todo` adds a todo at the end and clears the input",
+        check: add,
+    },
+    SpecItem {
+        id: "add-trim",
+        description: "new todos are trimmed, empty ones are not added",
+        check: add_trim,
+    },
+    SpecItem {
+        id: "toggle",
+        description: "`.toggle` completes a todo, and makes it active again",
+        check: toggle,
+    },
+    SpecItem {
+        id: "destroy",
+        description: "`.destroy` removes a todo",
+        check: destroy,
+    },
+    SpecItem {
+        id: "toggle-all",
+        description: "`.toggle-all` completes every todo, then makes them all active",
+        check: toggle_all,
+    },
+    SpecItem {
+        id: "toggle-all-state",
+        description: "`.toggle-all` is checked when every todo is completed, and only then",
+        check: toggle_all_state,
+    },
+    SpecItem {
+        id: "count",
+        description: "`.todo-count` counts the active todos",
+        check: count,
+    },
+    SpecItem {
+        id: "pluralize",
+        description: "`.todo-count` reads `1 item left`, `2 items left`",
+        check: pluralize,
+    },
+    SpecItem {
+        id: "edit",
+        description: "double-clicking a label shows a focused `.edit` with its text",
+        check: edit,
+    },
+    SpecItem {
+        id: "edit-enter",
+        description: "Enter saves the edit",
+        check: edit_enter,
+    },
+    SpecItem {
+        id: "edit-blur",
+        description: "leaving `.edit` saves the edit",
+        check: edit_blur,
+    },
+    SpecItem {
+        id: "edit-escape",
+        description: "Escape cancels the edit",
+        check: edit_escape,
+    },
+    SpecItem {
+        id: "edit-trim",
+        description: "edits are trimmed, an empty edit removes the todo",
+        check: edit_trim,
+    },
+    SpecItem {
+        id: "clear-completed",
+        description: "`.clear-completed` removes the completed todos, and is hidden without them",
+        check: clear_completed,
+    },
+    SpecItem {
+        id: "persistence",
+        description: "todos and their state are restored on reload",
+        check: persistence,
+    },
+    SpecItem {
+        id: "routing",
+        description: "`.filters a` and `#/`, `#/active`, `#/completed` show the matching todos",
+        check: routing,
+    },
+];
+
+/// What the spec items check, as shown in the results: a feature is
+/// implemented when all of its items pass.
+pub struct Feature {
+    pub name: &'static str,
+    /// See `SpecItem::id`.
+    pub items: &'static [&'static str],
+}
+
+pub const FEATURES: &[Feature] = &[
+    Feature {
+        name: "add, toggle, remove",
+        items: &["add", "toggle", "destroy", "toggle-all"],
+    },
+    Feature {
+        name: "edit: Enter",
+        items: &["edit", "edit-enter"],
+    },
+    Feature {
+        name: "edit: Escape",
+        items: &["edit-escape"],
+    },
+    Feature {
+        name: "edit: blur",
+        items: &["edit-blur"],
+    },
+    Feature {
+        name: "trimming",
+        items: &["add-trim", "edit-trim"],
+    },
+    Feature {
+        name: "counter",
+        items: &["count"],
+    },
+    Feature {
+        name: "pluralization",
+        items: &["pluralize"],
+    },
+    Feature {
+        name: "toggle-all state",
+        items: &["toggle-all-state"],
+    },
+    Feature {
+        name: "hidden when empty",
+        items: &["hide-when-empty"],
+    },
+    Feature {
+        name: "clear completed",
+        items: &["clear-completed"],
+    },
+    Feature {
+        name: "persistence",
+        items: &["persistence"],
+    },
+    Feature {
+        name: "routing",
+        items: &["routing"],
+    },
+];
+
+impl Feature {
+    /// Whether every item of the feature passed, `None` if one of them was
+    /// not checked.
+    pub fn passed(&self, checks: &[Check]) -> Option<bool> {
+        let mut passed = true;
+        for id in self.items {
+            passed &= checks.iter().find(|check| check.id == *id)?.passed();
+        }
+        Some(passed)
+    }
+}
+
+/// Check every spec item on the build in `dist`, each on a new session of
+/// the app, without saved data.
+pub fn run(browser: &mut Browser, dist: &Path) -> anyhow::Result<Vec<Check>> {
+    let server = Server::start(dist)?;
+    let mut page = browser.open(&server.url)?;
+    let mut checks = Vec::new();
+    for item in SPEC {
+        let outcome = start(&mut page).and_then(|_| (item.check)(&mut page));
+        checks.push(Check {
+            id: item.id.to_string(),
+            failure: outcome.err().map(|e| format!("{e:#}")),
+        });
+    }
+    Ok(checks)
+}
+
+/// The spec item of an id, `None` for items of an older spec that are in the
+/// history.
+pub fn spec_item(id: &str) -> Option<&'static SpecItem> {
+    SPEC.iter().find(|item| item.id == id)
+}
+
+fn start(page: &mut Page) -> anyhow::Result<()> {
+    page.reset()?;
+    page.eventually("document.querySelector('.new-todo') !== null", true).context("no `.new-todo`")
+}
+
+// The todos that are shown, top-level ones only (not subtasks), as
+// `[label, completed]`
+const ITEMS: &str = "[...document.querySelectorAll('.todo-list > li')]
+    .filter(li => li.getClientRects().length > 0)
+    .map(li => [(li.querySelector('label')?.innerText ?? '').trim(), li.classList.contains('completed')])";
+
+fn items(todos: &[(&str, bool)]) -> Vec<(String, bool)> {
+    todos.iter().map(|(label, completed)| (label.to_string(), *completed)).collect()
+}
+
+fn expect_items(page: &mut Page, todos: &[(&str, bool)]) -> anyhow::Result<()> {
+    page.eventually(ITEMS, items(todos)).context("the todos")
+}
+
+fn visible(selector: &str) -> String {
+    format!("(document.querySelector('{selector}')?.getClientRects().length ?? 0) > 0")
+}
+
+fn add_todo(page: &mut Page, text: &str) -> anyhow::Result<()> {
+    page.run("document.querySelector('.new-todo').focus()")?;
+    page.insert_text(text)?;
+    page.press_key("Enter")
+}
+
+fn add_todos(page: &mut Page, texts: &[&str]) -> anyhow::Result<()> {
+    for (i, text) in texts.iter().enumerate() {
+        add_todo(page, text)?;
+        // Or the next one could be typed before this one is rendered
+        page.eventually(&format!("({ITEMS}).length"), i + 1).with_context(|| format!("adding `{text}`"))?;
+    }
+    Ok(())
+}
+
+// Click a control of the todo at `index` in JS, as `.destroy` is only
+// displayed on hover
+fn click(page: &mut Page, selector: &str, index: usize) -> anyhow::Result<()> {
+    let found: bool = page.eval(&format!(
+        "(() => {{ const e = document.querySelectorAll('.todo-list > li')[{index}]?.querySelector('{selector}'); \
+         e?.click(); return e != null }})()"
+    ))?;
+    match found {
+        true => Ok(()),
+        false => bail!("no `{selector}` in todo {index}"),
+    }
+}
+
+fn click_filter(page: &mut Page, name: &str) -> anyhow::Result<()> {
+    let found: bool = page.eval(&format!(
+        "(() => {{ const a = [...document.querySelectorAll('.filters a')].find(a => a.innerText.trim() === '{name}'); \
+         a?.click(); return a != null }})()"
+    ))?;
+    match found {
+        true => Ok(()),
+        false => bail!("no `{name}` in `.filters a`"),
+    }
+}
+
+// Double-click the label of a todo, then select the text of `.edit` so
+// that what is typed replaces it
+fn start_editing(page: &mut Page, index: usize) -> anyhow::Result<()> {
+    page.run(&format!(
+        "document.querySelectorAll('.todo-list > li')[{index}].querySelector('label')\
+         .dispatchEvent(new MouseEvent('dblclick', {{ bubbles: true }}))"
+    ))?;
+    page.eventually("document.querySelector('.todo-list > li .edit') !== null", true).context("no `.edit`")?;
+    page.run("const edit = document.querySelector('.todo-list > li .edit'); edit.focus(); edit.select()")
+}
+
+fn hide_when_empty(page: &mut Page) -> anyhow::Result<()> {
+    let both = format!("[{}, {}]", visible(".main"), visible(".footer"));
+    page.eventually(&both, [false, false]).context("without todos")?;
+    add_todo(page, "a")?;
+    page.eventually(&both, [true, true]).context("with a todo")?;
+    click(page, ".destroy", 0)?;
+    page.eventually(&both, [false, false]).context("after removing it")
+}
+
+fn add(page: &mut Page) -> anyhow::Result<()> {
+    add_todo(page, "Buy milk")?;
+    expect_items(page, &[("Buy milk", false)])?;
+    page.eventually("document.querySelector('.new-todo').value", "".to_string()).context("the input")?;
+    add_todo(page, "Walk the dog")?;
+    expect_items(page, &[("Buy milk", false), ("Walk the dog", false)])
+}
+
+fn add_trim(page: &mut Page) -> anyhow::Result<()> {
+    add_todo(page, "  Buy milk  ")?;
+    expect_items(page, &[("Buy milk", false)])?;
+    add_todo(page, "   ")?;
+    // Give it the time to add one
+    std::thread::sleep(std::time::Duration::from_millis(200));
+    expect_items(page, &[("Buy milk", false)])
+}
+
+fn toggle(page: &mut Page) -> anyhow::Result<()> {
+    add_todos(page, &["a", "b"])?;
+    click(page, ".toggle", 0)?;
+    expect_items(page, &[("a", true), ("b", false)])?;
+    click(page, ".toggle", 0)?;
+    expect_items(page, &[("a", false), ("b", false)])
+}
+
+fn destroy(page: &mut Page) -> anyhow::Result<()> {
+    add_todos(page, &["a", "b", "c"])?;
+    click(page, ".destroy", 1)?;
+    expect_items(page, &[("a", false), ("c", false)])
+}
+
+fn toggle_all(page: &mut Page) -> anyhow::Result<()> {
+    add_todos(page, &["a", "b"])?;
+    click(page, ".toggle", 0)?;
+    expect_items(page, &[("a", true), ("b", false)])?;
+    // In JS, it is usually hidden behind its label
+    page.run("document.querySelector('.toggle-all').click()")?;
+    expect_items(page, &[("a", true), ("b", true)]).context("after checking `.toggle-all`")?;
+    page.run("document.querySelector('.toggle-all').click()")?;
+    expect_items(page, &[("a", false), ("b", false)]).context("after unchecking `.toggle-all`")
+}
+
+fn toggle_all_state(page: &mut Page) -> anyhow::Result<()> {
+    const CHECKED: &str = "document.querySelector('.toggle-all').checked";
+    add_todos(page, &["a", "b"])?;
+    page.eventually(CHECKED, false).context("with active todos")?;
+    click(page, ".toggle", 0)?;
+    click(page, ".toggle", 1)?;
+    expect_items(page, &[("a", true), ("b", true)])?;
+    page.eventually(CHECKED, true).context("with every todo completed")?;
+    click(page, ".toggle", 1)?;
+    page.eventually(CHECKED, false).context("with an active todo again")
+}
+
+fn count(page: &mut Page) -> anyhow::Result<()> {
+    const COUNT: &str = "document.querySelector('.todo-count strong')?.innerText.trim()";
+    add_todos(page, &["a", "b", "c"])?;
+    page.eventually(COUNT, Some("3".to_string()))?;
+    click(page, ".toggle", 1)?;
+    page.eventually(COUNT, Some("2".to_string())).context("with a completed todo")
+}
+
+fn pluralize(page: &mut Page) -> anyhow::Result<()> {
+    const TEXT: &str = "document.querySelector('.todo-count')?.innerText.replace(/\\s+/g, ' ').trim()";
+    add_todo(page, "a")?;
+    page.eventually(TEXT, Some("1 item left".to_string()))?;
+    add_todo(page, "b")?;
+    page.eventually(TEXT, Some("2 items left".to_string()))
+}
+
+fn edit(page: &mut Page) -> anyhow::Result<()> {
+    add_todos(page, &["Buy milk"])?;
+    page.run(
+        "document.querySelector('.todo-list > li label').dispatchEvent(new MouseEvent('dblclick', { bubbles: true }))",
+    )?;
+    page.eventually("document.querySelector('.todo-list > li').classList.contains('editing')", true)
+        .context("the todo is not `.editing`")?;
+    page.eventually("document.querySelector('.todo-list > li .edit')?.value", Some("Buy milk".to_string()))?;
+    page.eventually("document.activeElement?.classList.contains('edit') ?? false", true)
+        .context("`.edit` has no focus")
+}
+
+fn edit_enter(page: &mut Page) -> anyhow::Result<()> {
+    add_todos(page, &["a", "b"])?;
+    start_editing(page, 0)?;
+    page.insert_text("c")?;
+    page.press_key("Enter")?;
+    expect_items(page, &[("c", false), ("b", false)])?;
+    page.eventually("document.querySelector('.todo-list > li.editing') === null", true).context("still editing")
+}
+
+fn edit_blur(page: &mut Page) -> anyhow::Result<()> {
+    add_todos(page, &["a", "b"])?;
+    start_editing(page, 0)?;
+    page.insert_text("c")?;
+    page.run("document.activeElement.blur()")?;
+    expect_items(page, &[("c", false), ("b", false)])?;
+    page.eventually("document.querySelector('.todo-list > li.editing') === null", true).context("still editing")
+}
+
+fn edit_escape(page: &mut Page) -> anyhow::Result<()> {
+    add_todos(page, &["a", "b"])?;
+    start_editing(page, 0)?;
+    page.insert_text("c")?;
+    page.press_key("Escape")?;
+    page.eventually("document.querySelector('.todo-list > li.editing') === null", true).context("still editing")?;
+    expect_items(page, &[("a", false), ("b", false)])
+}
+
+fn edit_trim(page: &mut Page) -> anyhow::Result<()> {
+    add_todos(page, &["a", "b"])?;
+    start_editing(page, 0)?;
+    page.insert_text("  c  ")?;
+    page.press_key("Enter")?;
+    expect_items(page, &[("c", false), ("b", false)])?;
+    start_editing(page, 0)?;
+    page.press_key("Backspace")?;
+    page.press_key("Enter")?;
+    expect_items(page, &[("b", false)]).context("after an empty edit")
+}
+
+fn clear_completed(page: &mut Page) -> anyhow::Result<()> {
+    let shown = visible(".clear-completed");
+    add_todos(page, &["a", "b", "c"])?;
+    page.eventually(&shown, false).context("`.clear-completed` without completed todos")?;
+    click(page, ".toggle", 0)?;
+    click(page, ".toggle", 2)?;
+    page.eventually(&shown, true).context("`.clear-completed` with completed todos")?;
+    page.run("document.querySelector('.clear-completed').click()")?;
+    expect_items(page, &[("b", false)])?;
+    page.eventually(&shown, false).context("`.clear-completed` once cleared")
+}
+
+fn persistence(page: &mut Page) -> anyhow::Result<()> {
+    add_todos(page, &["a", "b"])?;
+    click(page, ".toggle", 0)?;
+    expect_items(page, &[("a", true), ("b", false)])?;
+    page.load()?;
+    expect_items(page, &[("a", true), ("b", false)]).context("after a reload")
+}
+
+fn routing(page: &mut Page) -> anyhow::Result<()> {
+    const SELECTED: &str = "document.querySelector('.filters a.selected')?.innerText.trim()";
+    add_todos(page, &["a", "b"])?;
+    click(page, ".toggle", 0)?;
+    expect_items(page, &[("a", true), ("b", false)])?;
+    for (filter, todos) in [
+        ("Active", &[("b", false)][..]),
+        ("Completed", &[("a", true)][..]),
+        ("All", &[("a", true), ("b", false)][..]),
+    ] {
+        click_filter(page, filter)?;
+        expect_items(page, todos).with_context(|| format!("with `{filter}`"))?;
+        page.eventually(SELECTED, Some(filter.to_string()))
+            .with_context(|| format!("the selected filter with `{filter}`"))?;
+    }
+    for (hash, todos) in [
+        ("#/active", &[("b", false)][..]),
+        ("#/completed", &[("a", true)][..]),
+        ("#/", &[("a", true), ("b", false)][..]),
+    ] {
+        page.run(&format!("location.hash = '{hash}'"))?;
+        expect_items(page, todos).with_context(|| format!("at `{hash}`"))?;
+    }
+    Ok(())
+}
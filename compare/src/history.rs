@@ -0,0 +1,263 @@
+use std::collections::BTreeMap;
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{bail, Context};
+use serde::{Deserialize, Serialize};
+
+use crate::results::{CellResult, Toolchain};
+
+/// One run of the comparison, a line of the history file.
+///
+/// The history is append-only JSON lines, so that it can be kept in git and
+/// read by other tools, see `load`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Run {
+    /// When the run started, e.g. `2022-11-02T10:04:31Z`.
+    pub started_at: String,
+    /// The commit that was built, `None` outside of a git checkout.
+    pub commit: Option<String>,
+    /// Whether there were uncommitted changes.
+    #[serde(default)]
+    pub dirty: bool,
+    pub toolchain: Toolchain,
+    pub cells: Vec<RunCell>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunCell {
+    pub app: String,
+    pub row: String,
+    pub profile: String,
+    /// Why the cell could not be built or measured.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// By name, e.g. `brotli`, see `CellResult::metrics`. Lower is better for
+    /// every metric.
+    #[serde(default)]
+    pub metrics: BTreeMap<String, f64>,
+}
+
+impl Run {
+    pub fn new(started_at: String, commit: Option<String>, dirty: bool, toolchain: Toolchain, results: &[CellResult]) -> Self {
+        let cells = results
+            .iter()
+            .map(|result| RunCell {
+                app: result.app.clone(),
+                row: result.row.clone(),
+                profile: result.profile.clone(),
+                error: result.outcome.as_ref().err().cloned(),
+                metrics: result.metrics(),
+            })
+            .collect();
+        Self {
+            started_at,
+            commit,
+            dirty,
+            toolchain,
+            cells,
+        }
+    }
+
+    /// e.g. `2022-11-02T10:04:31Z 897e375`, with `+` when dirty.
+    pub fn label(&self) -> String {
+        let commit = self.commit.as_deref().map(|c| &c[..c.len().min(7)]).unwrap_or("no commit");
+        format!("{} {commit}{}", self.started_at, if self.dirty { "+" } else { "" })
+    }
+}
+
+/// Append a run to the history file, creating it.
+pub fn append(path: &Path, run: &Run) -> anyhow::Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("cannot open {}", path.display()))?;
+    writeln!(file, "{}", serde_json::to_string(run)?)?;
+    Ok(())
+}
+
+/// Every run of the history file, oldest first, none if there is no file.
+pub fn load(path: &Path) -> anyhow::Result<Vec<Run>> {
+    let file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("cannot open {}", path.display())),
+    };
+    let mut runs = Vec::new();
+    for (i, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let run = serde_json::from_str(&line).with_context(|| format!("{}:{}: invalid run", path.display(), i + 1))?;
+        runs.push(run);
+    }
+    Ok(runs)
+}
+
+/// Find a run by its index in the history, negative from the end (`-1` is
+/// the last run), or by the start of its commit.
+pub fn find<'a>(runs: &'a [Run], id: &str) -> anyhow::Result<&'a Run> {
+    if let Ok(index) = id.parse::<isize>() {
+        let index = match index < 0 {
+            true => runs.len() as isize + index,
+            false => index,
+        };
+        return match runs.get(index as usize).filter(|_| index >= 0) {
+            Some(run) => Ok(run),
+            None => bail!("there is no run {id}, the history has {} runs", runs.len()),
+        };
+    }
+    let mut found = runs.iter().rev().filter(|run| run.commit.as_deref().is_some_and(|c| c.starts_with(id)));
+    match found.next() {
+        Some(run) => Ok(run),
+        None => bail!("no run of commit {id}"),
+    }
+}
+
+/// A cell that changed between two runs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    pub app: String,
+    pub row: String,
+    pub profile: String,
+    pub kind: ChangeKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChangeKind {
+    /// A metric with a different value.
+    Metric { metric: String, old: f64, new: f64 },
+    /// The cell was measured in the old run and failed in the new one.
+    Failed(String),
+    /// The cell was measured in the old run and is not in the new one.
+    Missing,
+    /// A metric of the cell in the old run that it no longer has, e.g. a
+    /// benchmark that failed.
+    MissingMetric(String),
+}
+
+impl Change {
+    /// In percent of the old value, positive when it got worse. A metric
+    /// that rises from 0 and a cell or a metric that is no longer measured
+    /// are infinitely worse.
+    pub fn percent(&self) -> f64 {
+        match self.kind {
+            ChangeKind::Metric { old, new, .. } => match old == 0.0 {
+                true if new > 0.0 => f64::INFINITY,
+                true => 0.0,
+                false => (new - old) / old * 100.0,
+            },
+            ChangeKind::Failed(_) | ChangeKind::Missing | ChangeKind::MissingMetric(_) => f64::INFINITY,
+        }
+    }
+
+    /// Whether it got worse by more than `threshold` percent.
+    pub fn is_regression(&self, threshold: f64) -> bool {
+        self.percent() > threshold
+    }
+}
+
+/// Every metric of a cell of both runs that changed or is missing in `new`,
+/// and every cell that was measured in `old` but failed or is missing in
+/// `new`, the largest regressions first. Cells that are only in `new` have nothing to compare
+/// with and are left out.
+pub fn diff(old: &Run, new: &Run) -> Vec<Change> {
+    let mut changes = Vec::new();
+    for old_cell in old.cells.iter().filter(|c| c.error.is_none()) {
+        let change = |kind| Change {
+            app: old_cell.app.clone(),
+            row: old_cell.row.clone(),
+            profile: old_cell.profile.clone(),
+            kind,
+        };
+        let cell = new
+            .cells
+            .iter()
+            .find(|c| c.app == old_cell.app && c.row == old_cell.row && c.profile == old_cell.profile);
+        let cell = match cell {
+            Some(cell) => cell,
+            None => {
+                changes.push(change(ChangeKind::Missing));
+                continue;
+            }
+        };
+        if let Some(error) = &cell.error {
+            changes.push(change(ChangeKind::Failed(error.clone())));
+            continue;
+        }
+        for (metric, new_value) in &cell.metrics {
+            match old_cell.metrics.get(metric) {
+                Some(old_value) if old_value != new_value => changes.push(change(ChangeKind::Metric {
+                    metric: metric.clone(),
+                    old: *old_value,
+                    new: *new_value,
+                })),
+                _ => {}
+            }
+        }
+        for metric in old_cell.metrics.keys().filter(|metric| !cell.metrics.contains_key(*metric)) {
+            changes.push(change(ChangeKind::MissingMetric(metric.clone())));
+        }
+    }
+    changes.sort_by(|a, b| b.percent().total_cmp(&a.percent()));
+    changes
+}
+
+/// The versions of `crates` in a `Cargo.lock`, several versions of a crate
+/// are joined with `, `.
+pub fn locked_versions(lock: &str, crates: &[String]) -> anyhow::Result<BTreeMap<String, String>> {
+    #[derive(Deserialize)]
+    struct Lock {
+        #[serde(default)]
+        package: Vec<Package>,
+    }
+    #[derive(Deserialize)]
+    struct Package {
+        name: String,
+        version: String,
+    }
+    let lock: Lock = toml::from_str(lock).context("invalid Cargo.lock")?;
+    let mut versions: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for package in lock.package.into_iter().filter(|p| crates.contains(&p.name)) {
+        versions.entry(package.name).or_default().push(package.version);
+    }
+    Ok(versions.into_iter().map(|(name, versions)| (name, versions.join(", "))).collect())
+}
+
+/// The commit checked out in `root` and whether there are uncommitted
+/// changes, `None` if it is not a git checkout.
+pub fn git_commit(root: &Path) -> Option<(String, bool)> {
+    let git = |args: &[&str]| {
+        let output = Command::new("git").current_dir(root).args(args).output().ok()?;
+        output.status.success().then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    };
+    let commit = git(&["rev-parse", "HEAD"])?;
+    let dirty = git(&["status", "--porcelain"]).is_some_and(|status| !status.is_empty());
+    Some((commit, dirty))
+}
+
+/// Seconds since the Unix epoch as `2022-11-02T10:04:31Z`.
+pub fn format_utc(seconds: u64) -> String {
+    let days = (seconds / 86400) as i64;
+    let time = seconds % 86400;
+    // Howard Hinnant's `civil_from_days`
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
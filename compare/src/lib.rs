@@ -0,0 +1,21 @@
+//! Compares the implementations of the apps in this repository: builds
+//! every cell of `matrix.toml`, measures it and writes the results.
+
+pub mod attribution;
+pub mod bench;
+pub mod browser;
+pub mod build;
+pub mod conformance;
+pub mod history;
+pub mod lines;
+pub mod matrix;
+pub mod memory;
+pub mod report;
+pub mod results;
+pub mod sizes;
+
+pub use matrix::*;
+pub use lines::Lines;
+pub use report::Report;
+pub use results::*;
+pub use sizes::Sizes;
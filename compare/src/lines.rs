@@ -0,0 +1,65 @@
+use std::path::Path;
+
+use anyhow::Context;
+
+/// Lines of the Rust files of a crate.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Lines {
+    pub files: usize,
+    pub code: usize,
+    /// Lines with only a comment, doc comments included.
+    pub comments: usize,
+    pub blank: usize,
+}
+
+impl Lines {
+    /// Every `.rs` file in `dir` and its subdirectories, e.g. the `src` of
+    /// a crate.
+    pub fn count_dir(dir: &Path) -> anyhow::Result<Self> {
+        let mut lines = Self::default();
+        for entry in std::fs::read_dir(dir).with_context(|| format!("cannot read {}", dir.display()))? {
+            let path = entry?.path();
+            if path.is_dir() {
+                lines.add(Self::count_dir(&path)?);
+            } else if path.extension().is_some_and(|e| e == "rs") {
+                let text = std::fs::read_to_string(&path).with_context(|| format!("cannot read {}", path.display()))?;
+                lines.add(Self::count(&text));
+            }
+        }
+        Ok(lines)
+    }
+
+    /// The lines of one file. A line in a block comment, or that starts one
+    /// and has no code before it, is a comment.
+    pub fn count(text: &str) -> Self {
+        let mut lines = Self {
+            files: 1,
+            ..Self::default()
+        };
+        let mut in_block = false;
+        for line in text.lines() {
+            let line = line.trim();
+            if in_block {
+                lines.comments += 1;
+                in_block = !line.contains("*/");
+            } else if line.is_empty() {
+                lines.blank += 1;
+            } else if line.starts_with("//") {
+                lines.comments += 1;
+            } else if line.starts_with("/*") {
+                lines.comments += 1;
+                in_block = !line.contains("*/");
+            } else {
+                lines.code += 1;
+            }
+        }
+        lines
+    }
+
+    fn add(&mut self, other: Self) {
+        self.files += other.files;
+        self.code += other.code;
+        self.comments += other.comments;
+        self.blank += other.blank;
+    }
+}
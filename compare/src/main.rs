@@ -0,0 +1,457 @@
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::bail;
+use clap::{Parser, Subcommand};
+
+use compare::bench::{self, Timing};
+use compare::browser::Browser;
+use compare::conformance::{self, Check};
+use compare::history::{self, ChangeKind, Run};
+use compare::memory::{self, Memory};
+use compare::report::group_digits;
+use compare::{attribution, build, sizes, App, Cell, CellResult, Lines, Matrix, Measurements, Report, Toolchain};
+
+#[derive(Parser)]
+#[command(about = "Builds and compares the implementations of the apps")]
+struct Cli {
+    /// The matrix to build, relative to the workspace root.
+    #[arg(long, default_value = "compare/matrix.toml")]
+    matrix: PathBuf,
+    /// Where the artifacts of every cell go, relative to the workspace root.
+    #[arg(long, default_value = "target/compare")]
+    out: PathBuf,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Build every cell of the matrix and write the results of every app.
+    Build {
+        /// Only build these implementations. The results are printed
+        /// instead of written, as they are incomplete.
+        #[arg(long = "impl")]
+        implementations: Vec<String>,
+        /// Only build with these profiles, by name.
+        #[arg(long = "profile")]
+        profiles: Vec<String>,
+        /// Do not run the builds in a browser, e.g. without Chrome.
+        #[arg(long)]
+        no_browser: bool,
+    },
+    /// Check the last builds of the `[browser]` profile against the TodoMVC
+    /// spec, without building them again.
+    Conformance {
+        /// Only check these implementations.
+        #[arg(long = "impl")]
+        implementations: Vec<String>,
+    },
+    /// Time the last builds of the `[browser]` profile, without building
+    /// them again.
+    Bench {
+        /// Only time these implementations.
+        #[arg(long = "impl")]
+        implementations: Vec<String>,
+        /// How many times every operation is timed, `runs` of `[browser]`
+        /// by default.
+        #[arg(long)]
+        runs: Option<usize>,
+    },
+    /// Follow the memory of the last builds of the `[browser]` profile over
+    /// cycles of adding, editing and removing todos, without building them
+    /// again.
+    Memory {
+        /// Only follow these implementations.
+        #[arg(long = "impl")]
+        implementations: Vec<String>,
+        /// How many cycles, `cycles` of `[browser]` by default.
+        #[arg(long)]
+        cycles: Option<usize>,
+    },
+    /// Break a wasm file down by crate and list its largest functions.
+    Size {
+        wasm: PathBuf,
+        /// How many functions are listed.
+        #[arg(long, default_value_t = 20)]
+        top: usize,
+    },
+    /// List the runs of the history.
+    History,
+    /// Compare two runs of the history, by index (negative from the end) or
+    /// commit. The last two runs by default.
+    Diff {
+        #[arg(default_value = "-2")]
+        old: String,
+        #[arg(default_value = "-1")]
+        new: String,
+        /// Changes by less than this many percent are not shown.
+        #[arg(long, default_value_t = 1.0)]
+        threshold: f64,
+        /// Fail if there is a regression, e.g. in CI.
+        #[arg(long)]
+        check: bool,
+    },
+}
+
+// The workspace root, the parent of this crate
+fn workspace_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap().to_path_buf()
+}
+
+fn toolchain(root: &Path, matrix: &Matrix) -> Toolchain {
+    // There is no Cargo.lock before the first build
+    let frameworks = std::fs::read_to_string(root.join("Cargo.lock"))
+        .ok()
+        .and_then(|lock| history::locked_versions(&lock, &matrix.frameworks).ok())
+        .unwrap_or_default();
+    Toolchain {
+        rustc: build::tool_version("rustc"),
+        trunk: build::tool_version("trunk"),
+        wasm_opt: matrix.wasm_opt.as_ref().and_then(|_| build::tool_version("wasm-opt")),
+        frameworks,
+    }
+}
+
+fn history_path(root: &Path, matrix: &Matrix) -> anyhow::Result<PathBuf> {
+    match &matrix.history {
+        Some(path) => Ok(root.join(path)),
+        None => bail!("the matrix has no history file"),
+    }
+}
+
+// Of every implementation of the app, then of its shared crates
+fn lines_of_code(root: &Path, app: &App) -> anyhow::Result<Vec<(String, Lines)>> {
+    let dir = root.join(&app.dir);
+    app.implementations
+        .iter()
+        .chain(&app.shared)
+        .map(|name| Ok((name.clone(), Lines::count_dir(&dir.join(name).join("src"))?)))
+        .collect()
+}
+
+// The cells that run in the browser with where they were built, which they
+// must have been
+fn built_in_browser<'a>(
+    matrix: &'a Matrix,
+    root: &Path,
+    out: &Path,
+    implementations: &[String],
+) -> anyhow::Result<Vec<(Cell<'a>, PathBuf)>> {
+    let cells: Vec<_> = matrix
+        .cells()
+        .into_iter()
+        .filter(|cell| matrix.runs_in_browser(cell))
+        .filter(|cell| implementations.is_empty() || implementations.iter().any(|i| i == cell.implementation))
+        .collect();
+    if cells.is_empty() {
+        bail!("nothing to run, see `[browser]` in the matrix");
+    }
+    cells
+        .into_iter()
+        .map(|cell| {
+            let dist = root.join(cell.dist_dir(out));
+            match dist.is_dir() {
+                true => Ok((cell, dist)),
+                false => bail!("{cell} is not built, run `build` first"),
+            }
+        })
+        .collect()
+}
+
+fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    let root = workspace_root();
+    let matrix = Matrix::load(&root.join(&cli.matrix))?;
+    match cli.command {
+        Command::Size { wasm, top } => {
+            let attribution = attribution::analyze(&std::fs::read(&wasm)?)?;
+            if !attribution.has_names {
+                eprintln!("{} has no name section, functions cannot be attributed", wasm.display());
+            }
+            println!("{:>10}  total", attribution.total);
+            println!("{:>10}  code", attribution.code);
+            println!("{:>10}  data", attribution.data);
+            println!();
+            for (group, size) in attribution.by_group(&matrix.crate_groups) {
+                println!("{size:>10}  {group}");
+            }
+            println!();
+            for (crate_name, size) in attribution.by_crate() {
+                println!("{size:>10}  {crate_name}");
+            }
+            println!();
+            for function in attribution.functions.iter().take(top) {
+                println!("{:>10}  {}", function.size, function.name);
+            }
+        }
+        Command::Build {
+            implementations,
+            profiles,
+            no_browser,
+        } => {
+            let complete = implementations.is_empty() && profiles.is_empty();
+            let cells: Vec<_> = matrix
+                .cells()
+                .into_iter()
+                .filter(|cell| implementations.is_empty() || implementations.iter().any(|i| i == cell.implementation))
+                .filter(|cell| profiles.is_empty() || profiles.contains(&cell.profile.name))
+                .collect();
+            if cells.is_empty() {
+                bail!("nothing to build");
+            }
+
+            let started_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+            let mut browser = match !no_browser && cells.iter().any(|cell| matrix.runs_in_browser(cell)) {
+                true => Some(Browser::launch()?),
+                false => None,
+            };
+            let mut results = Vec::new();
+            for cell in &cells {
+                eprintln!("building {cell}");
+                let outcome = build::build(cell, &root, &cli.out).and_then(|artifacts| {
+                    // From a build of its own, as the measured one may be stripped
+                    let attribution = match matrix.attributes(cell) {
+                        true => {
+                            let wasm = build::build_with_names(cell, &root, &cli.out)?;
+                            Some(attribution::analyze(&std::fs::read(wasm)?)?)
+                        }
+                        false => None,
+                    };
+                    let (conformance, benchmarks, memory) = match (&mut browser, &matrix.browser) {
+                        (Some(browser), Some(settings)) if matrix.runs_in_browser(cell) => (
+                            Some(conformance::run(browser, &artifacts.dist)?),
+                            Some(bench::run(browser, &artifacts.dist, settings.runs)?),
+                            Some(memory::run(browser, &artifacts.dist, settings.cycles)?),
+                        ),
+                        _ => (None, None, None),
+                    };
+                    Ok(Measurements {
+                        sizes: sizes::measure(&artifacts, matrix.wasm_opt.as_deref())?,
+                        attribution,
+                        conformance,
+                        benchmarks,
+                        memory,
+                    })
+                });
+                if let Err(e) = &outcome {
+                    eprintln!("{cell}: {e:#}");
+                }
+                results.push(CellResult::new(cell, outcome.map_err(|e| format!("{e:#}"))));
+            }
+
+            let toolchain = toolchain(&root, &matrix);
+            for app in &matrix.apps {
+                let md = compare::markdown(&matrix, &app.name, &results, &toolchain);
+                match complete {
+                    true => std::fs::write(root.join(&app.results), md)?,
+                    false => println!("{md}"),
+                }
+            }
+
+            let (commit, dirty) = match history::git_commit(&root) {
+                Some((commit, dirty)) => (Some(commit), dirty),
+                None => (None, false),
+            };
+            let run = Run::new(history::format_utc(started_at), commit, dirty, toolchain.clone(), &results);
+            // An incomplete report would be mistaken for the results, and an
+            // incomplete run in the history for cells that are gone
+            if complete {
+                for app in &matrix.apps {
+                    if let Some(path) = &app.report {
+                        let report = Report {
+                            matrix: &matrix,
+                            app,
+                            results: &results,
+                            toolchain: &toolchain,
+                            built: run.label(),
+                            lines: lines_of_code(&root, app)?,
+                        };
+                        std::fs::write(root.join(path), report.html())?;
+                    }
+                }
+                if let Some(path) = &matrix.history {
+                    history::append(&root.join(path), &run)?;
+                }
+            }
+
+            let failed = results.iter().filter(|r| r.outcome.is_err()).count();
+            if failed > 0 {
+                bail!("{failed} of {} builds failed", results.len());
+            }
+        }
+        Command::Conformance { implementations } => {
+            let cells = built_in_browser(&matrix, &root, &cli.out, &implementations)?;
+            let mut browser = Browser::launch()?;
+            let mut checked = Vec::new();
+            for (cell, dist) in &cells {
+                eprintln!("checking {cell}");
+                checked.push((cell.row(), conformance::run(&mut browser, dist)?));
+            }
+            print_conformance(&checked);
+        }
+        Command::Bench { implementations, runs } => {
+            let cells = built_in_browser(&matrix, &root, &cli.out, &implementations)?;
+            let runs = runs.or(matrix.browser.as_ref().map(|settings| settings.runs)).unwrap_or(1);
+            let mut browser = Browser::launch()?;
+            let mut timed = Vec::new();
+            for (cell, dist) in &cells {
+                eprintln!("timing {cell}");
+                timed.push((cell.row(), bench::run(&mut browser, dist, runs)?));
+            }
+            print_benchmarks(&timed);
+        }
+        Command::Memory { implementations, cycles } => {
+            let cells = built_in_browser(&matrix, &root, &cli.out, &implementations)?;
+            let cycles = cycles.or(matrix.browser.as_ref().map(|settings| settings.cycles)).unwrap_or(memory::SAMPLES);
+            if cycles < memory::SAMPLES {
+                bail!("cycles must be at least {}", memory::SAMPLES);
+            }
+            let mut browser = Browser::launch()?;
+            for (cell, dist) in &cells {
+                eprintln!("following {cell}");
+                print_memory(&cell.row(), &memory::run(&mut browser, dist, cycles)?);
+            }
+        }
+        Command::History => {
+            for (i, run) in history::load(&history_path(&root, &matrix)?)?.iter().enumerate() {
+                let rustc = run.toolchain.rustc.as_deref().unwrap_or("unknown rustc");
+                println!("{i:>4}  {}  {} cells  {rustc}", run.label(), run.cells.len());
+            }
+        }
+        Command::Diff { old, new, threshold, check } => {
+            let runs = history::load(&history_path(&root, &matrix)?)?;
+            let (old, new) = (history::find(&runs, &old)?, history::find(&runs, &new)?);
+            println!("{} -> {}", old.label(), new.label());
+            print_toolchain_changes(&old.toolchain, &new.toolchain);
+
+            let changes = history::diff(old, new);
+            let shown: Vec<_> = changes.iter().filter(|c| c.percent().abs() >= threshold).collect();
+            for change in &shown {
+                let mark = match change.is_regression(threshold) {
+                    true => "REGRESSION",
+                    false => "improvement",
+                };
+                let cell = format!("{}/{} [{}]", change.app, change.row, change.profile);
+                match &change.kind {
+                    ChangeKind::Metric { metric, old, new } => {
+                        println!("{mark:>11}  {:+7.2}%  {old} -> {new}  {cell} {metric}", change.percent())
+                    }
+                    ChangeKind::Failed(error) => println!("{mark:>11}  {cell} failed: {error}"),
+                    ChangeKind::Missing => println!("{mark:>11}  {cell} is missing"),
+                    ChangeKind::MissingMetric(metric) => println!("{mark:>11}  {cell} {metric} is missing"),
+                }
+            }
+            if shown.is_empty() {
+                println!("no change of {threshold}% or more");
+            }
+            let regressions = shown.iter().filter(|c| c.is_regression(threshold)).count();
+            if check && regressions > 0 {
+                bail!("{regressions} regressions");
+            }
+        }
+    }
+    Ok(())
+}
+
+fn print_toolchain_changes(old: &Toolchain, new: &Toolchain) {
+    let mut tools = vec![
+        ("rustc", old.rustc.clone(), new.rustc.clone()),
+        ("trunk", old.trunk.clone(), new.trunk.clone()),
+        ("wasm-opt", old.wasm_opt.clone(), new.wasm_opt.clone()),
+    ];
+    for name in old.frameworks.keys().chain(new.frameworks.keys()) {
+        if !tools.iter().any(|(tool, _, _)| tool == name) {
+            tools.push((name, old.frameworks.get(name).cloned(), new.frameworks.get(name).cloned()));
+        }
+    }
+    for (tool, old, new) in tools {
+        if old != new {
+            let version = |v: Option<String>| v.unwrap_or_else(|| "none".to_string());
+            println!("{tool}: {} -> {}", version(old), version(new));
+        }
+    }
+}
+
+// A spec item per line, an implementation per column, then why they failed
+fn print_conformance(checked: &[(String, Vec<Check>)]) {
+    let width = conformance::SPEC.iter().map(|item| item.id.len()).max().unwrap_or(0);
+    print!("{:width$}", "");
+    for (row, _) in checked {
+        print!("  {row}");
+    }
+    println!();
+    for item in conformance::SPEC {
+        print!("{:width$}", item.id);
+        for (row, checks) in checked {
+            let passed = checks.iter().find(|check| check.id == item.id).is_some_and(Check::passed);
+            print!("  {:>w$}", if passed { "pass" } else { "FAIL" }, w = row.len());
+        }
+        println!();
+    }
+    for (row, checks) in checked {
+        for check in checks {
+            if let Some(failure) = &check.failure {
+                println!("{row} {}: {failure}", check.id);
+            }
+        }
+    }
+}
+
+// A sample per line
+fn print_memory(row: &str, memory: &Memory) {
+    println!("{row}");
+    for sample in &memory.samples {
+        println!(
+            "  {:>8} cycles  wasm {:>12}  heap {:>12}",
+            sample.cycles,
+            group_digits(sample.wasm),
+            group_digits(sample.heap)
+        );
+    }
+    match &memory.failure {
+        Some(e) => println!("  failed: {e}"),
+        None => println!("  growth: {}", compare::format_growth(memory)),
+    }
+}
+
+// An operation per line, an implementation per column
+fn print_benchmarks(timed: &[(String, Vec<Timing>)]) {
+    let width = bench::OPERATIONS.iter().map(|operation| operation.id.len()).max().unwrap_or(0);
+    let columns: Vec<usize> = timed.iter().map(|(row, _)| row.len().max(20)).collect();
+    print!("{:width$}", "");
+    for ((row, _), column) in timed.iter().zip(&columns) {
+        print!("  {row:>column$}");
+    }
+    println!();
+    for operation in bench::OPERATIONS {
+        print!("{:width$}", operation.id);
+        for ((_, timings), column) in timed.iter().zip(&columns) {
+            let cell = match timings.iter().find(|t| t.id == operation.id).map(|t| &t.outcome) {
+                Some(Ok(stats)) => compare::format_stats(stats),
+                _ => "failed".to_string(),
+            };
+            print!("  {cell:>column$}");
+        }
+        println!();
+    }
+    println!();
+    println!("DOM mutations:");
+    for operation in bench::OPERATIONS {
+        for (row, timings) in timed {
+            if let Some(Ok(mutations)) = timings.iter().find(|t| t.id == operation.id).and_then(|t| t.mutations.as_ref()) {
+                println!("{:width$}  {row}: {}", operation.id, compare::format_mutations(mutations));
+            }
+        }
+    }
+    for (row, timings) in timed {
+        for timing in timings {
+            if let Err(e) = &timing.outcome {
+                println!("{row} {}: {e}", timing.id);
+            }
+            if let Some(Err(e)) = &timing.mutations {
+                println!("{row} {}, counting the mutations: {e}", timing.id);
+            }
+        }
+    }
+}
@@ -0,0 +1,361 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context};
+use serde::Deserialize;
+
+use crate::attribution::CrateGroups;
+
+/// What to build, read from `matrix.toml`: every implementation of every app
+/// is built with every profile and every feature set that applies to it.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Matrix {
+    /// The arguments of `wasm-opt`, e.g. `["-Oz"]`, to also measure every
+    /// build after it. Without them it is not run.
+    pub wasm_opt: Option<Vec<String>>,
+    /// Every run of the whole matrix is appended to this file, relative to
+    /// the workspace root, see `history`.
+    pub history: Option<PathBuf>,
+    /// The crates whose versions are recorded with the results, from
+    /// `Cargo.lock`.
+    #[serde(default)]
+    pub frameworks: Vec<String>,
+    /// Which builds are broken down by crate and function.
+    pub attribution: Option<AttributionSettings>,
+    /// What the crates are shown as in the breakdowns.
+    #[serde(default)]
+    pub crate_groups: CrateGroups,
+    /// Which builds are run in a headless browser.
+    pub browser: Option<BrowserSettings>,
+    pub apps: Vec<App>,
+    pub profiles: Vec<Profile>,
+    /// A single `default` set when none is given.
+    #[serde(default = "default_feature_sets")]
+    pub feature_sets: Vec<FeatureSet>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct App {
+    pub name: String,
+    /// Relative to the workspace root, every implementation is a crate in it.
+    pub dir: PathBuf,
+    /// The markdown file the results of the app are written to.
+    pub results: PathBuf,
+    /// The static HTML report of the app, see `report`.
+    pub report: Option<PathBuf>,
+    pub implementations: Vec<String>,
+    /// Crates in `dir` that implementations share, their lines of code are
+    /// counted apart.
+    #[serde(default)]
+    pub shared: Vec<String>,
+}
+
+/// Settings of `[profile.release]`, overriding the ones of the root
+/// `Cargo.toml`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Profile {
+    /// Also the heading of its column in the results.
+    pub name: String,
+    pub opt_level: Setting,
+    pub lto: Option<Setting>,
+    pub codegen_units: Option<u32>,
+    /// `unwind` or `abort`.
+    pub panic: Option<String>,
+}
+
+/// A profile setting that cargo takes as a number, a boolean or a string,
+/// e.g. `opt-level = 3` and `opt-level = "z"`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum Setting {
+    Bool(bool),
+    Int(i64),
+    Str(String),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct FeatureSet {
+    pub name: String,
+    #[serde(default)]
+    pub features: Vec<String>,
+    #[serde(default = "default_true")]
+    pub default_features: bool,
+    /// The implementations that have these features, all of them if `None`.
+    pub implementations: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct AttributionSettings {
+    /// The builds of this profile are broken down.
+    pub profile: String,
+    /// How many of the largest functions are listed.
+    #[serde(default = "default_top")]
+    pub top: usize,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct BrowserSettings {
+    /// The builds of this profile are run.
+    pub profile: String,
+    /// The apps that are TodoMVCs, checked with `conformance::SPEC` and
+    /// timed with `bench::OPERATIONS`.
+    pub apps: Vec<String>,
+    /// How many times every operation is timed.
+    #[serde(default = "default_runs")]
+    pub runs: usize,
+    /// How many add, edit and remove cycles the memory is followed over,
+    /// see `memory::run`.
+    #[serde(default = "default_cycles")]
+    pub cycles: usize,
+}
+
+fn default_runs() -> usize {
+    10
+}
+
+fn default_cycles() -> usize {
+    2000
+}
+
+/// One build: an implementation of an app, with a profile and a feature set.
+#[derive(Debug, Clone, Copy)]
+pub struct Cell<'a> {
+    pub app: &'a App,
+    pub implementation: &'a str,
+    pub profile: &'a Profile,
+    pub features: &'a FeatureSet,
+}
+
+pub const DEFAULT_FEATURE_SET: &str = "default";
+
+fn default_feature_sets() -> Vec<FeatureSet> {
+    vec![FeatureSet {
+        name: DEFAULT_FEATURE_SET.to_string(),
+        features: Vec::new(),
+        default_features: true,
+        implementations: None,
+    }]
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_top() -> usize {
+    10
+}
+
+impl Matrix {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let text = std::fs::read_to_string(path).with_context(|| format!("cannot read {}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("invalid matrix {}", path.display()))
+    }
+
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        let matrix: Self = toml::from_str(text)?;
+        matrix.check()?;
+        Ok(matrix)
+    }
+
+    // Names are used in paths and as keys of the results, they must be unique
+    fn check(&self) -> anyhow::Result<()> {
+        fn unique<'a>(kind: &str, names: impl Iterator<Item = &'a str>) -> anyhow::Result<()> {
+            let mut seen = HashSet::new();
+            for name in names {
+                if !seen.insert(slug(name)) {
+                    bail!("{kind} `{name}` is listed twice");
+                }
+            }
+            if seen.is_empty() {
+                bail!("no {kind} is listed");
+            }
+            Ok(())
+        }
+        unique("app", self.apps.iter().map(|app| app.name.as_str()))?;
+        unique("profile", self.profiles.iter().map(|profile| profile.name.as_str()))?;
+        unique("feature set", self.feature_sets.iter().map(|set| set.name.as_str()))?;
+        for app in &self.apps {
+            unique("implementation", app.implementations.iter().map(String::as_str))?;
+        }
+        for profile in &self.profiles {
+            if let Some(panic) = profile.panic.as_deref() {
+                if !matches!(panic, "unwind" | "abort") {
+                    bail!("profile `{}`: panic must be `unwind` or `abort`, not `{panic}`", profile.name);
+                }
+            }
+        }
+        if let Some(attribution) = &self.attribution {
+            if !self.profiles.iter().any(|profile| profile.name == attribution.profile) {
+                bail!("attribution: there is no profile `{}`", attribution.profile);
+            }
+        }
+        if let Some(browser) = &self.browser {
+            if !self.profiles.iter().any(|profile| profile.name == browser.profile) {
+                bail!("browser: there is no profile `{}`", browser.profile);
+            }
+            if let Some(app) = browser.apps.iter().find(|name| !self.apps.iter().any(|app| &app.name == *name)) {
+                bail!("browser: there is no app `{app}`");
+            }
+            if browser.runs == 0 {
+                bail!("browser: runs must be at least 1");
+            }
+            if browser.cycles < crate::memory::SAMPLES {
+                bail!("browser: cycles must be at least {}", crate::memory::SAMPLES);
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the builds of `cell` are broken down by crate and function.
+    pub fn attributes(&self, cell: &Cell) -> bool {
+        self.attribution.as_ref().is_some_and(|a| a.profile == cell.profile.name)
+    }
+
+    /// Whether `cell` is run in a headless browser.
+    pub fn runs_in_browser(&self, cell: &Cell) -> bool {
+        self.browser
+            .as_ref()
+            .is_some_and(|b| b.profile == cell.profile.name && b.apps.contains(&cell.app.name))
+    }
+
+    /// Every cell, by app, implementation, feature set and then profile, the
+    /// order of the results.
+    pub fn cells(&self) -> Vec<Cell<'_>> {
+        let mut cells = Vec::new();
+        for app in &self.apps {
+            for implementation in &app.implementations {
+                for features in self.feature_sets.iter().filter(|set| set.applies_to(implementation)) {
+                    for profile in &self.profiles {
+                        cells.push(Cell {
+                            app,
+                            implementation,
+                            profile,
+                            features,
+                        });
+                    }
+                }
+            }
+        }
+        cells
+    }
+}
+
+impl Profile {
+    /// The environment variables that make `cargo build --release` use this
+    /// profile.
+    pub fn env(&self) -> Vec<(&'static str, String)> {
+        let mut env = vec![("CARGO_PROFILE_RELEASE_OPT_LEVEL", self.opt_level.to_string())];
+        if let Some(lto) = &self.lto {
+            env.push(("CARGO_PROFILE_RELEASE_LTO", lto.to_string()));
+        }
+        if let Some(units) = self.codegen_units {
+            env.push(("CARGO_PROFILE_RELEASE_CODEGEN_UNITS", units.to_string()));
+        }
+        if let Some(panic) = &self.panic {
+            env.push(("CARGO_PROFILE_RELEASE_PANIC", panic.clone()));
+        }
+        env
+    }
+
+    /// The settings as they would be written in `Cargo.toml`.
+    pub fn describe(&self) -> String {
+        let quote = |setting: &Setting| match setting {
+            Setting::Str(s) => format!("\"{s}\""),
+            _ => setting.to_string(),
+        };
+        let mut settings = vec![format!("opt-level = {}", quote(&self.opt_level))];
+        if let Some(lto) = &self.lto {
+            settings.push(format!("lto = {}", quote(lto)));
+        }
+        if let Some(units) = self.codegen_units {
+            settings.push(format!("codegen-units = {units}"));
+        }
+        if let Some(panic) = &self.panic {
+            settings.push(format!("panic = \"{panic}\""));
+        }
+        settings.join(", ")
+    }
+}
+
+impl fmt::Display for Setting {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Bool(b) => write!(f, "{b}"),
+            Self::Int(i) => write!(f, "{i}"),
+            Self::Str(s) => f.write_str(s),
+        }
+    }
+}
+
+impl FeatureSet {
+    pub fn applies_to(&self, implementation: &str) -> bool {
+        self.implementations
+            .as_ref()
+            .is_none_or(|implementations| implementations.iter().any(|i| i == implementation))
+    }
+
+    /// The arguments of `trunk build` (and cargo) that select these features.
+    pub fn args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if !self.default_features {
+            args.push("--no-default-features".to_string());
+        }
+        if !self.features.is_empty() {
+            args.push("--features".to_string());
+            args.push(self.features.join(","));
+        }
+        args
+    }
+}
+
+impl<'a> Cell<'a> {
+    /// The crate of the implementation, relative to the workspace root.
+    pub fn crate_dir(&self) -> PathBuf {
+        self.app.dir.join(self.implementation)
+    }
+
+    /// Where the build of this cell goes, under `out`, so that every cell
+    /// keeps its artifacts.
+    pub fn dist_dir(&self, out: &Path) -> PathBuf {
+        out.join(slug(&self.app.name))
+            .join(slug(self.implementation))
+            .join(slug(&self.features.name))
+            .join(slug(&self.profile.name))
+    }
+
+    /// The row of the results: the implementation, with the feature set
+    /// unless it is the default one.
+    pub fn row(&self) -> String {
+        match self.features.name == DEFAULT_FEATURE_SET {
+            true => self.implementation.to_string(),
+            false => format!("{} ({})", self.implementation, self.features.name),
+        }
+    }
+}
+
+impl fmt::Display for Cell<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{} [{}] {}", self.app.name, self.implementation, self.features.name, self.profile.name)
+    }
+}
+
+/// A name made usable as a path component: `opt-level = 's'` is
+/// `opt-level-s`.
+pub fn slug(name: &str) -> String {
+    let mut slug = String::with_capacity(name.len());
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
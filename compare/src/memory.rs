@@ -0,0 +1,143 @@
+use std::path::Path;
+
+use anyhow::{bail, Context};
+
+use crate::bench::HELPERS;
+use crate::browser::{Browser, Page, Server};
+
+/// How many times the memory is sampled over a session, after the one before
+/// the first cycle.
+pub const SAMPLES: usize = 10;
+
+/// Keeps the memories of the wasm instances of the page, as
+/// `window.wasmMemories`. wasm-bindgen instantiates with one of these two,
+/// and the memory is the `memory` export.
+const CAPTURE: &str = "window.wasmMemories = [];
+for (const name of ['instantiate', 'instantiateStreaming']) {
+    const original = WebAssembly[name];
+    WebAssembly[name] = async (...args) => {
+        const result = await original.apply(WebAssembly, args);
+        const memory = (result.instance ?? result).exports?.memory;
+        if (memory instanceof WebAssembly.Memory) {
+            window.wasmMemories.push(memory);
+        }
+        return result;
+    };
+}";
+
+/// A cycle: add 10 todos, edit one, complete one, then remove them one at a
+/// time. The app is empty again after it.
+const CYCLE: &str = "async () => {
+    for (let i = 0; i < 10; i++) C.add('todo ' + i);
+    await C.until(() => C.todos().length === 10);
+    await C.edit(0, 'edited');
+    await C.until(() => C.label(0) === 'edited' && document.querySelector('.todo-list > li.editing') === null);
+    C.todos()[1].querySelector('.toggle').click();
+    await C.until(() => C.completed() === 1);
+    for (let left = 9; left >= 0; left--) {
+        C.todos()[0].querySelector('.destroy').click();
+        await C.until(() => C.todos().length === left);
+    }
+}";
+
+/// The memory of an implementation after some cycles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sample {
+    pub cycles: usize,
+    /// The size of the linear memories, which never shrink.
+    pub wasm: u64,
+    /// The JS heap in use, after a garbage collection.
+    pub heap: u64,
+}
+
+/// The memory of an implementation over a session.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Memory {
+    pub samples: Vec<Sample>,
+    /// Why the session stopped early, the samples are those up to then.
+    pub failure: Option<String>,
+}
+
+impl Memory {
+    /// The bytes the wasm memory grew by after the warm-up, if it did.
+    pub fn wasm_growth(&self) -> Option<u64> {
+        growth(&self.samples.iter().map(|sample| sample.wasm).collect::<Vec<_>>())
+    }
+
+    /// The bytes the JS heap grew by after the warm-up, if it grew and never
+    /// went down from a sample to the next.
+    pub fn heap_growth(&self) -> Option<u64> {
+        growth(&self.samples.iter().map(|sample| sample.heap).collect::<Vec<_>>())
+    }
+
+    pub fn leaks(&self) -> bool {
+        self.wasm_growth().is_some() || self.heap_growth().is_some()
+    }
+}
+
+/// How much `values` grew from the second to the last, when they never went
+/// down.
+/// The first is before any cycle and the second after the first batch of
+/// them, which warms up: caches and allocators settle. Anything that still
+/// grows with the same cycles over and over is kept by the app, e.g. closures
+/// that are never dropped.
+pub fn growth(values: &[u64]) -> Option<u64> {
+    let after_warm_up = values.get(1..).filter(|values| values.len() > 2)?;
+    if !after_warm_up.windows(2).all(|pair| pair[0] <= pair[1]) {
+        return None;
+    }
+    let grown = after_warm_up[after_warm_up.len() - 1] - after_warm_up[0];
+    (grown > 0).then_some(grown)
+}
+
+/// Run `cycles` cycles of adding, editing and removing todos on the build in
+/// `dist`, in `SAMPLES` batches with a sample of the memory after each.
+pub fn run(browser: &mut Browser, dist: &Path, cycles: usize) -> anyhow::Result<Memory> {
+    let server = Server::start(dist)?;
+    let mut page = browser.open(&server.url)?;
+    page.add_script(CAPTURE)?;
+    let mut memory = Memory {
+        samples: Vec::new(),
+        failure: None,
+    };
+    if let Err(e) = session(&mut page, cycles, &mut memory.samples) {
+        memory.failure = Some(format!("{e:#}"));
+    }
+    Ok(memory)
+}
+
+fn session(page: &mut Page, cycles: usize, samples: &mut Vec<Sample>) -> anyhow::Result<()> {
+    page.reset()?;
+    page.eventually("document.querySelector('.new-todo') !== null", true).context("no `.new-todo`")?;
+    page.run(HELPERS)?;
+    page.run(&format!("C.cycle = {CYCLE}; C.cycles = 0;"))?;
+    let before = sample(page, 0)?;
+    if before.wasm == 0 {
+        bail!("no wasm memory, the app was not instantiated with `WebAssembly.instantiate`");
+    }
+    samples.push(before);
+    // As even as possible and `cycles` in total, e.g. 25 in batches of 2 and 3
+    let mut done = 0;
+    for i in 1..=SAMPLES {
+        let batch = cycles * i / SAMPLES - done;
+        let finished: usize = page
+            .eval(&format!(
+                "(async () => {{ for (let i = 0; i < {batch}; i++) {{ await C.cycle(); C.cycles++; }} return C.cycles; }})()"
+            ))
+            .with_context(|| format!("after {done} cycles"))?;
+        done += batch;
+        if finished != done {
+            bail!("{finished} of {done} cycles finished");
+        }
+        samples.push(sample(page, done)?);
+    }
+    Ok(())
+}
+
+fn sample(page: &mut Page, cycles: usize) -> anyhow::Result<Sample> {
+    Ok(Sample {
+        cycles,
+        heap: page.heap_after_gc()?,
+        wasm: page.eval("wasmMemories.reduce((bytes, memory) => bytes + memory.buffer.byteLength, 0)")?,
+    })
+}
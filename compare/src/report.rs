@@ -0,0 +1,637 @@
+use std::fmt::Write;
+
+use crate::attribution::Attribution;
+use crate::bench::{self, Timing};
+use crate::conformance::{self, Check};
+use crate::lines::Lines;
+use crate::matrix::{App, Matrix};
+use crate::memory::{Memory, Sample, SAMPLES};
+use crate::results::{format_growth, format_mutations, format_stats, CellResult, Measurements, Toolchain};
+
+/// A self-contained static HTML page with the results of an app: the
+/// styles, the script that sorts the tables and the charts (inline SVG) are
+/// all in it, so that it can be opened from disk or attached anywhere.
+pub struct Report<'a> {
+    pub matrix: &'a Matrix,
+    pub app: &'a App,
+    pub results: &'a [CellResult],
+    pub toolchain: &'a Toolchain,
+    /// When and what was built, see `history::Run::label`.
+    pub built: String,
+    /// Of the implementations, then of the shared crates.
+    pub lines: Vec<(String, Lines)>,
+}
+
+const COLORS: &[&str] = &[
+    "#4e79a7", "#f28e2b", "#e15759", "#76b7b2", "#59a14f", "#edc948", "#b07aa1", "#ff9da7", "#9c755f", "#bab0ac",
+];
+
+const STYLE: &str = "
+body { font: 14px/1.4 system-ui, sans-serif; color: #222; max-width: 1100px; margin: 2em auto; padding: 0 1em; }
+h1 { font-weight: 400; }
+h2 { margin-top: 2em; border-bottom: 1px solid #ddd; }
+pre { background: #f6f6f6; padding: .5em 1em; }
+table { border-collapse: collapse; margin: 1em 0; }
+th, td { padding: 3px 10px; border-bottom: 1px solid #eee; }
+td.number { text-align: right; font-variant-numeric: tabular-nums; }
+td.failed, td.fail { color: #c00; }
+td.pass { color: #080; }
+table.sortable th { cursor: pointer; user-select: none; background: #f6f6f6; }
+table.sortable th[data-order=asc]::after { content: ' \\25B4'; }
+table.sortable th[data-order=desc]::after { content: ' \\25BE'; }
+svg text { font: 11px system-ui, sans-serif; }
+details { margin: .5em 0; }
+.note { color: #666; }
+";
+
+// Sorts a table by the clicked column: by `data-value` when the cell has
+// one, by its text otherwise
+const SCRIPT: &str = "
+document.querySelectorAll('table.sortable th').forEach(th => th.addEventListener('click', () => {
+  const table = th.closest('table');
+  const ascending = th.dataset.order !== 'asc';
+  table.querySelectorAll('th').forEach(h => delete h.dataset.order);
+  th.dataset.order = ascending ? 'asc' : 'desc';
+  const key = row => {
+    const cell = row.cells[th.cellIndex];
+    const value = cell.dataset.value;
+    return value === undefined ? cell.textContent : parseFloat(value);
+  };
+  const compare = (a, b) => {
+    const x = key(a), y = key(b);
+    if (typeof x === 'number' && typeof y === 'number') return x - y;
+    if (typeof x === 'number') return -1;
+    if (typeof y === 'number') return 1;
+    return x.localeCompare(y);
+  };
+  const body = table.tBodies[0];
+  [...body.rows].sort((a, b) => ascending ? compare(a, b) : compare(b, a)).forEach(row => body.appendChild(row));
+}));
+";
+
+/// A cell of a table: what is shown, and the value it is sorted by.
+enum Cell {
+    Text(String),
+    Number(f64, String),
+    Failed,
+    /// A spec item, with why it failed.
+    Check(Option<String>),
+    /// Text shown as a failure, e.g. a leak.
+    Flagged(String),
+}
+
+impl Cell {
+    fn bytes(bytes: u64) -> Self {
+        Self::Number(bytes as f64, group_digits(bytes))
+    }
+
+    fn count(count: usize) -> Self {
+        Self::Number(count as f64, count.to_string())
+    }
+}
+
+impl Report<'_> {
+    pub fn html(&self) -> String {
+        let mut html = String::new();
+        let title = format!("{} comparison", self.app.name);
+        writeln!(html, "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">").unwrap();
+        writeln!(html, "<title>{}</title>\n<style>{STYLE}</style>\n</head>\n<body>", escape(&title)).unwrap();
+        writeln!(html, "<h1>{}</h1>", escape(&title)).unwrap();
+        writeln!(html, "<p>Built {}.</p>", escape(&self.built)).unwrap();
+        let mut versions = vec![self.toolchain.rustc.clone(), self.toolchain.trunk.clone(), self.toolchain.wasm_opt.clone()];
+        versions.extend(self.toolchain.frameworks.iter().map(|(name, version)| Some(format!("{name} {version}"))));
+        writeln!(html, "<pre>{}</pre>", escape(&versions.into_iter().flatten().collect::<Vec<_>>().join("\n"))).unwrap();
+        writeln!(html, "<p class=\"note\">Click a column heading to sort a table. Sizes are in bytes.</p>").unwrap();
+
+        self.wasm_sizes(&mut html);
+        self.transfer_sizes(&mut html);
+        self.conformance(&mut html);
+        self.benchmarks(&mut html);
+        self.memory(&mut html);
+        self.attribution(&mut html);
+        self.lines_of_code(&mut html);
+
+        writeln!(html, "<script>{SCRIPT}</script>\n</body>\n</html>").unwrap();
+        html
+    }
+
+    fn results(&self) -> impl Iterator<Item = &CellResult> {
+        self.results.iter().filter(|r| r.app == self.app.name)
+    }
+
+    // The rows, in the order of the results
+    fn rows(&self) -> Vec<&str> {
+        let mut rows: Vec<&str> = Vec::new();
+        for result in self.results() {
+            if !rows.contains(&result.row.as_str()) {
+                rows.push(&result.row);
+            }
+        }
+        rows
+    }
+
+    fn measurements(&self, row: &str, profile: &str) -> Option<&Result<Measurements, String>> {
+        self.results().find(|r| r.row == row && r.profile == profile).map(|r| &r.outcome)
+    }
+
+    fn wasm_sizes(&self, html: &mut String) {
+        let profiles: Vec<String> = self.matrix.profiles.iter().map(|p| p.name.clone()).collect();
+        let rows: Vec<(String, Vec<Option<f64>>)> = self
+            .rows()
+            .into_iter()
+            .map(|row| {
+                let sizes = profiles
+                    .iter()
+                    .map(|profile| match self.measurements(row, profile) {
+                        Some(Ok(m)) => Some(m.sizes.wasm as f64),
+                        _ => None,
+                    })
+                    .collect();
+                (row.to_string(), sizes)
+            })
+            .collect();
+
+        writeln!(html, "<h2>Wasm size by profile</h2>").unwrap();
+        html.push_str(&grouped_bars(&profiles, &rows, format_bytes));
+        let mut headings = vec!["Implementation".to_string()];
+        headings.extend(profiles.iter().cloned());
+        let table_rows = rows
+            .iter()
+            .map(|(row, sizes)| {
+                let mut cells = vec![Cell::Text(row.clone())];
+                cells.extend(sizes.iter().map(|size| match size {
+                    Some(size) => Cell::bytes(*size as u64),
+                    None => Cell::Failed,
+                }));
+                cells
+            })
+            .collect();
+        html.push_str(&table(&headings, table_rows));
+        writeln!(html, "<ul class=\"note\">").unwrap();
+        for profile in &self.matrix.profiles {
+            writeln!(html, "<li>{}: <code>{}</code></li>", escape(&profile.name), escape(&profile.describe())).unwrap();
+        }
+        writeln!(html, "</ul>").unwrap();
+    }
+
+    fn transfer_sizes(&self, html: &mut String) {
+        let profiles: Vec<String> = self.matrix.profiles.iter().map(|p| p.name.clone()).collect();
+        let totals: Vec<(String, Vec<Option<f64>>)> = self
+            .rows()
+            .into_iter()
+            .map(|row| {
+                let totals = profiles
+                    .iter()
+                    .map(|profile| match self.measurements(row, profile) {
+                        Some(Ok(m)) => Some(m.sizes.total() as f64),
+                        _ => None,
+                    })
+                    .collect();
+                (row.to_string(), totals)
+            })
+            .collect();
+
+        writeln!(html, "<h2>Transfer size</h2>").unwrap();
+        writeln!(html, "<p>What a browser downloads with brotli: the wasm and the JS glue.</p>").unwrap();
+        html.push_str(&grouped_bars(&profiles, &totals, format_bytes));
+        let headings: Vec<String> = ["Implementation", "Profile", "wasm", "wasm-opt", "gzip", "brotli", "JS", "JS brotli", "total"]
+            .iter()
+            .map(|h| h.to_string())
+            .collect();
+        let rows = self
+            .results()
+            .map(|result| {
+                let mut cells = vec![Cell::Text(result.row.clone()), Cell::Text(result.profile.clone())];
+                match &result.outcome {
+                    Ok(Measurements { sizes, .. }) => {
+                        cells.push(Cell::bytes(sizes.wasm));
+                        cells.push(match sizes.wasm_opt {
+                            Some(size) => Cell::bytes(size),
+                            None => Cell::Text("-".to_string()),
+                        });
+                        cells.extend(
+                            [sizes.gzip, sizes.brotli, sizes.js, sizes.js_brotli, sizes.total()]
+                                .into_iter()
+                                .map(Cell::bytes),
+                        );
+                    }
+                    Err(_) => cells.extend((0..7).map(|_| Cell::Failed)),
+                }
+                cells
+            })
+            .collect();
+        html.push_str(&table(&headings, rows));
+        if let Some(args) = &self.matrix.wasm_opt {
+            writeln!(html, "<p class=\"note\">gzip and brotli are of the wasm after <code>wasm-opt {}</code>.</p>", escape(&args.join(" "))).unwrap();
+        }
+    }
+
+    fn conformance(&self, html: &mut String) {
+        let checked: Vec<(&str, &[Check])> = self
+            .results()
+            .filter_map(|r| Some((r.row.as_str(), r.outcome.as_ref().ok()?.conformance.as_deref()?)))
+            .collect();
+        if checked.is_empty() {
+            return;
+        }
+        writeln!(html, "<h2>Conformance</h2>").unwrap();
+        writeln!(
+            html,
+            "<p>The TodoMVC spec, checked in a headless browser. Hover a failure to see why it failed.</p>"
+        )
+        .unwrap();
+        let mut headings = vec!["Spec item".to_string()];
+        headings.extend(checked.iter().map(|(row, _)| row.to_string()));
+        let mut rows: Vec<Vec<Cell>> = conformance::SPEC
+            .iter()
+            .map(|item| {
+                let mut cells = vec![Cell::Text(item.description.to_string())];
+                cells.extend(checked.iter().map(|(_, checks)| match checks.iter().find(|c| c.id == item.id) {
+                    Some(check) => Cell::Check(check.failure.clone()),
+                    None => Cell::Text("-".to_string()),
+                }));
+                cells
+            })
+            .collect();
+        let mut passed = vec![Cell::Text("passed".to_string())];
+        passed.extend(checked.iter().map(|(_, checks)| {
+            let count = checks.iter().filter(|c| c.passed()).count();
+            Cell::Number(count as f64, format!("{count} of {}", checks.len()))
+        }));
+        rows.push(passed);
+        html.push_str(&table(&headings, rows));
+    }
+
+    fn benchmarks(&self, html: &mut String) {
+        let timed: Vec<(&str, &[Timing])> = self
+            .results()
+            .filter_map(|r| Some((r.row.as_str(), r.outcome.as_ref().ok()?.benchmarks.as_deref()?)))
+            .collect();
+        if timed.is_empty() {
+            return;
+        }
+        let implementations: Vec<String> = timed.iter().map(|(row, _)| row.to_string()).collect();
+        let stats = |timings: &[Timing], id: &str| timings.iter().find(|t| t.id == id)?.outcome.as_ref().ok().copied();
+        let medians: Vec<(String, Vec<Option<f64>>)> = bench::OPERATIONS
+            .iter()
+            .map(|operation| {
+                let medians = timed.iter().map(|(_, timings)| Some(stats(timings, operation.id)?.median)).collect();
+                (operation.id.to_string(), medians)
+            })
+            .collect();
+
+        writeln!(html, "<h2>Runtime</h2>").unwrap();
+        writeln!(
+            html,
+            "<p>Medians in milliseconds with their 95% confidence interval, in a headless browser: from the events \
+             of the operation until the DOM is updated and laid out.</p>"
+        )
+        .unwrap();
+        html.push_str(&grouped_bars(&implementations, &medians, format_milliseconds));
+        let mut headings = vec!["Operation".to_string()];
+        headings.extend(implementations.iter().cloned());
+        let rows = bench::OPERATIONS
+            .iter()
+            .map(|operation| {
+                let mut cells = vec![Cell::Text(operation.description.to_string())];
+                cells.extend(timed.iter().map(|(_, timings)| match stats(timings, operation.id) {
+                    Some(stats) => Cell::Number(stats.median, format_stats(&stats)),
+                    None => Cell::Failed,
+                }));
+                cells
+            })
+            .collect();
+        html.push_str(&table(&headings, rows));
+
+        let mutations = |timings: &[Timing], id: &str| timings.iter().find(|t| t.id == id)?.mutations.clone();
+        if !timed.iter().any(|(_, timings)| timings.iter().any(|t| t.mutations.is_some())) {
+            return;
+        }
+        writeln!(html, "<h2>DOM mutations</h2>").unwrap();
+        writeln!(
+            html,
+            "<p>Counted on a run of their own with a <code>MutationObserver</code>: <code>+</code> nodes inserted, \
+             <code>-</code> nodes removed, then attributes, <code>value</code> and <code>checked</code> properties \
+             and text nodes set. Columns sort by the total.</p>"
+        )
+        .unwrap();
+        let rows = bench::OPERATIONS
+            .iter()
+            .map(|operation| {
+                let mut cells = vec![Cell::Text(operation.description.to_string())];
+                cells.extend(timed.iter().map(|(_, timings)| match mutations(timings, operation.id) {
+                    Some(Ok(mutations)) => Cell::Number(mutations.total() as f64, format_mutations(&mutations)),
+                    _ => Cell::Failed,
+                }));
+                cells
+            })
+            .collect();
+        html.push_str(&table(&headings, rows));
+    }
+
+    fn memory(&self, html: &mut String) {
+        let followed: Vec<(&str, &Memory)> = self
+            .results()
+            .filter_map(|r| Some((r.row.as_str(), r.outcome.as_ref().ok()?.memory.as_ref()?)))
+            .collect();
+        if followed.is_empty() {
+            return;
+        }
+        let implementations: Vec<String> = followed.iter().map(|(row, _)| row.to_string()).collect();
+        let last = |memory: &Memory| memory.samples.last().filter(|_| memory.failure.is_none()).copied();
+        let series = |value: fn(&Sample) -> u64| -> Vec<Option<f64>> {
+            followed.iter().map(|(_, memory)| Some(value(&last(memory)?) as f64)).collect()
+        };
+        let ends = vec![
+            ("wasm memory".to_string(), series(|sample| sample.wasm)),
+            ("JS heap".to_string(), series(|sample| sample.heap)),
+        ];
+
+        writeln!(html, "<h2>Memory</h2>").unwrap();
+        let cycles = followed.iter().find_map(|(_, memory)| last(memory)).map_or(0, |sample| sample.cycles);
+        writeln!(
+            html,
+            "<p>After {cycles} cycles of adding 10 todos, editing one, completing one and removing them, the JS heap \
+             after a garbage collection. Growth is after the first {} cycles, which warm up, when the memory grew and \
+             never went down over the {SAMPLES} samples: what every cycle keeps, e.g. closures that are never dropped.</p>",
+            cycles / SAMPLES
+        )
+        .unwrap();
+        html.push_str(&grouped_bars(&implementations, &ends, format_bytes));
+        let headings: Vec<String> =
+            ["Implementation", "wasm memory before", "wasm memory after", "JS heap before", "JS heap after", "Growth"]
+                .iter()
+                .map(|h| h.to_string())
+                .collect();
+        let rows = followed
+            .iter()
+            .map(|(row, memory)| {
+                let (first, last) = (memory.samples.first(), memory.samples.last());
+                let bytes = |sample: Option<&Sample>, value: fn(&Sample) -> u64| {
+                    sample.map_or(Cell::Failed, |sample| Cell::bytes(value(sample)))
+                };
+                let growth = match (&memory.failure, memory.leaks()) {
+                    (Some(failure), _) => Cell::Check(Some(failure.clone())),
+                    (None, true) => Cell::Flagged(format_growth(memory)),
+                    (None, false) => Cell::Text(format_growth(memory)),
+                };
+                vec![
+                    Cell::Text(row.to_string()),
+                    bytes(first, |sample| sample.wasm),
+                    bytes(last, |sample| sample.wasm),
+                    bytes(first, |sample| sample.heap),
+                    bytes(last, |sample| sample.heap),
+                    growth,
+                ]
+            })
+            .collect();
+        html.push_str(&table(&headings, rows));
+    }
+
+    fn attribution(&self, html: &mut String) {
+        let settings = match &self.matrix.attribution {
+            Some(settings) => settings,
+            None => return,
+        };
+        let attributed: Vec<(&str, &Attribution)> = self
+            .results()
+            .filter(|r| r.profile == settings.profile)
+            .filter_map(|r| Some((r.row.as_str(), r.outcome.as_ref().ok()?.attribution.as_ref()?)))
+            .collect();
+        if attributed.is_empty() {
+            return;
+        }
+        let groups: Vec<String> = attributed[0].1.by_group(&self.matrix.crate_groups).into_iter().map(|(g, _)| g).collect();
+        let rows: Vec<(String, Vec<f64>)> = attributed
+            .iter()
+            .map(|(row, attribution)| {
+                let mut sizes: Vec<f64> = attribution.by_group(&self.matrix.crate_groups).into_iter().map(|(_, s)| s as f64).collect();
+                sizes.push(attribution.data as f64);
+                (row.to_string(), sizes)
+            })
+            .collect();
+        let mut segments = groups.clone();
+        segments.push("data".to_string());
+
+        writeln!(html, "<h2>Where the bytes go, {}</h2>", escape(&settings.profile)).unwrap();
+        writeln!(
+            html,
+            "<p>Code by crate and data, as built (before <code>wasm-opt</code>). <code>framework</code> is every crate in no \
+             other group: the framework, its dependencies and the implementation.</p>"
+        )
+        .unwrap();
+        html.push_str(&stacked_bars(&segments, &rows));
+        let mut headings = vec!["Implementation".to_string()];
+        headings.extend(segments.iter().cloned());
+        let table_rows = rows
+            .iter()
+            .map(|(row, sizes)| {
+                let mut cells = vec![Cell::Text(row.clone())];
+                cells.extend(sizes.iter().map(|size| Cell::bytes(*size as u64)));
+                cells
+            })
+            .collect();
+        html.push_str(&table(&headings, table_rows));
+
+        for (row, attribution) in &attributed {
+            writeln!(html, "<details><summary>Largest functions of {}</summary>", escape(row)).unwrap();
+            let headings: Vec<String> = ["Bytes", "Crate", "Function"].iter().map(|h| h.to_string()).collect();
+            let rows = attribution
+                .functions
+                .iter()
+                .take(settings.top)
+                .map(|f| vec![Cell::bytes(f.size), Cell::Text(f.crate_name.clone()), Cell::Text(f.name.clone())])
+                .collect();
+            html.push_str(&table(&headings, rows));
+            writeln!(html, "</details>").unwrap();
+        }
+    }
+
+    fn lines_of_code(&self, html: &mut String) {
+        if self.lines.is_empty() {
+            return;
+        }
+        writeln!(html, "<h2>Lines of code</h2>").unwrap();
+        writeln!(
+            html,
+            "<p>Of the Rust files of each crate. The shared crates are used by several implementations \
+             and counted apart.</p>"
+        )
+        .unwrap();
+        let headings: Vec<String> = ["Crate", "Files", "Code", "Comments", "Blank"].iter().map(|h| h.to_string()).collect();
+        let rows = self
+            .lines
+            .iter()
+            .map(|(name, lines)| {
+                let name = match self.app.shared.contains(name) {
+                    true => format!("{name} (shared)"),
+                    false => name.clone(),
+                };
+                vec![
+                    Cell::Text(name),
+                    Cell::count(lines.files),
+                    Cell::count(lines.code),
+                    Cell::count(lines.comments),
+                    Cell::count(lines.blank),
+                ]
+            })
+            .collect();
+        html.push_str(&table(&headings, rows));
+    }
+}
+
+fn table(headings: &[String], rows: Vec<Vec<Cell>>) -> String {
+    let mut html = String::from("<table class=\"sortable\">\n<thead><tr>");
+    for heading in headings {
+        write!(html, "<th>{}</th>", escape(heading)).unwrap();
+    }
+    html.push_str("</tr></thead>\n<tbody>\n");
+    for row in rows {
+        html.push_str("<tr>");
+        for cell in row {
+            match cell {
+                Cell::Text(text) => write!(html, "<td>{}</td>", escape(&text)).unwrap(),
+                Cell::Number(value, text) => {
+                    write!(html, "<td class=\"number\" data-value=\"{value}\">{}</td>", escape(&text)).unwrap()
+                }
+                Cell::Failed => html.push_str("<td class=\"failed\">failed</td>"),
+                Cell::Check(None) => html.push_str("<td class=\"pass\" data-value=\"1\">pass</td>"),
+                Cell::Check(Some(failure)) => {
+                    write!(html, "<td class=\"fail\" data-value=\"0\" title=\"{}\">fail</td>", escape(&failure)).unwrap()
+                }
+                Cell::Flagged(text) => write!(html, "<td class=\"fail\">{}</td>", escape(&text)).unwrap(),
+            }
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</tbody>\n</table>\n");
+    html
+}
+
+const LABEL_WIDTH: f64 = 170.0;
+const BAR_WIDTH: f64 = 520.0;
+const BAR_HEIGHT: f64 = 12.0;
+const LEGEND_HEIGHT: f64 = 24.0;
+
+fn legend(svg: &mut String, series: &[String]) {
+    let mut x = LABEL_WIDTH;
+    for (i, name) in series.iter().enumerate() {
+        write!(
+            svg,
+            "<rect x=\"{x}\" y=\"4\" width=\"10\" height=\"10\" fill=\"{}\"/><text x=\"{}\" y=\"13\">{}</text>",
+            COLORS[i % COLORS.len()],
+            x + 14.0,
+            escape(name)
+        )
+        .unwrap();
+        x += 24.0 + 7.0 * name.len() as f64;
+    }
+}
+
+fn svg_start(height: f64) -> String {
+    let width = LABEL_WIDTH + BAR_WIDTH + 80.0;
+    format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\">")
+}
+
+/// Horizontal bars, a group per row with a bar per series, labelled with
+/// `format`. Missing values (failed builds) have no bar.
+fn grouped_bars(series: &[String], rows: &[(String, Vec<Option<f64>>)], format: fn(f64) -> String) -> String {
+    let max = rows.iter().flat_map(|(_, values)| values.iter().flatten()).fold(0.0, |a: f64, b| a.max(*b));
+    let group_height = series.len() as f64 * (BAR_HEIGHT + 2.0) + 10.0;
+    let mut svg = svg_start(LEGEND_HEIGHT + rows.len() as f64 * group_height);
+    legend(&mut svg, series);
+    for (r, (label, values)) in rows.iter().enumerate() {
+        let top = LEGEND_HEIGHT + r as f64 * group_height;
+        write!(
+            svg,
+            "<text x=\"{}\" y=\"{}\" text-anchor=\"end\">{}</text>",
+            LABEL_WIDTH - 6.0,
+            top + group_height / 2.0,
+            escape(label)
+        )
+        .unwrap();
+        for (i, value) in values.iter().enumerate() {
+            let value = match value {
+                Some(value) => *value,
+                None => continue,
+            };
+            let y = top + i as f64 * (BAR_HEIGHT + 2.0);
+            let width = if max > 0.0 { value / max * BAR_WIDTH } else { 0.0 };
+            write!(
+                svg,
+                "<rect x=\"{LABEL_WIDTH}\" y=\"{y}\" width=\"{width:.1}\" height=\"{BAR_HEIGHT}\" fill=\"{}\"><title>{}: {}</title></rect>\
+                 <text x=\"{:.1}\" y=\"{}\">{}</text>",
+                COLORS[i % COLORS.len()],
+                escape(&series[i]),
+                format(value),
+                LABEL_WIDTH + width + 4.0,
+                y + BAR_HEIGHT - 2.0,
+                format(value)
+            )
+            .unwrap();
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// A horizontal bar per row, with a segment per series.
+fn stacked_bars(series: &[String], rows: &[(String, Vec<f64>)]) -> String {
+    let max = rows.iter().map(|(_, values)| values.iter().sum::<f64>()).fold(0.0, f64::max);
+    let row_height = BAR_HEIGHT + 10.0;
+    let mut svg = svg_start(LEGEND_HEIGHT + rows.len() as f64 * row_height);
+    legend(&mut svg, series);
+    for (r, (label, values)) in rows.iter().enumerate() {
+        let y = LEGEND_HEIGHT + r as f64 * row_height;
+        write!(
+            svg,
+            "<text x=\"{}\" y=\"{}\" text-anchor=\"end\">{}</text>",
+            LABEL_WIDTH - 6.0,
+            y + BAR_HEIGHT - 2.0,
+            escape(label)
+        )
+        .unwrap();
+        let mut x = LABEL_WIDTH;
+        for (i, value) in values.iter().enumerate() {
+            let width = if max > 0.0 { value / max * BAR_WIDTH } else { 0.0 };
+            write!(
+                svg,
+                "<rect x=\"{x:.1}\" y=\"{y}\" width=\"{width:.1}\" height=\"{BAR_HEIGHT}\" fill=\"{}\"><title>{}: {}</title></rect>",
+                COLORS[i % COLORS.len()],
+                escape(&series[i]),
+                group_digits(*value as u64)
+            )
+            .unwrap();
+            x += width;
+        }
+        let total: f64 = values.iter().sum();
+        write!(svg, "<text x=\"{:.1}\" y=\"{}\">{}</text>", x + 4.0, y + BAR_HEIGHT - 2.0, group_digits(total as u64)).unwrap();
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn format_bytes(bytes: f64) -> String {
+    group_digits(bytes as u64)
+}
+
+fn format_milliseconds(milliseconds: f64) -> String {
+    format!("{milliseconds:.1} ms")
+}
+
+/// `175479` as `175,479`.
+pub fn group_digits(n: u64) -> String {
+    let digits = n.to_string();
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+    grouped
+}
+
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
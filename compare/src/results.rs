@@ -0,0 +1,549 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use serde::{Deserialize, Serialize};
+
+use crate::attribution::Attribution;
+use crate::bench::{self, Timing};
+use crate::conformance::{self, Check};
+use crate::matrix::{Cell, Matrix};
+use crate::memory::{Memory, Sample, SAMPLES};
+use crate::report::group_digits;
+use crate::sizes::Sizes;
+
+/// What was measured for a cell, or why it could not be built.
+#[derive(Debug, Clone)]
+pub struct CellResult {
+    pub app: String,
+    /// See `Cell::row`.
+    pub row: String,
+    pub profile: String,
+    pub outcome: Result<Measurements, String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Measurements {
+    pub sizes: Sizes,
+    /// Only for the profile of `Matrix::attribution`.
+    pub attribution: Option<Attribution>,
+    /// Only for the builds that run in the browser, every item of
+    /// `conformance::SPEC`.
+    pub conformance: Option<Vec<Check>>,
+    /// Only for the builds that run in the browser, every operation of
+    /// `bench::OPERATIONS`.
+    pub benchmarks: Option<Vec<Timing>>,
+    /// Only for the builds that run in the browser.
+    pub memory: Option<Memory>,
+}
+
+/// The versions of the tools and frameworks that built the results.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Toolchain {
+    pub rustc: Option<String>,
+    pub trunk: Option<String>,
+    #[serde(default)]
+    pub wasm_opt: Option<String>,
+    /// From `Cargo.lock`, of the crates of `Matrix::frameworks`.
+    #[serde(default)]
+    pub frameworks: BTreeMap<String, String>,
+}
+
+impl CellResult {
+    pub fn new(cell: &Cell, outcome: Result<Measurements, String>) -> Self {
+        Self {
+            app: cell.app.name.clone(),
+            row: cell.row(),
+            profile: cell.profile.name.clone(),
+            outcome,
+        }
+    }
+
+    /// The measurements by name, as they are recorded in the history. None
+    /// if the cell failed.
+    pub fn metrics(&self) -> BTreeMap<String, f64> {
+        let mut metrics = BTreeMap::new();
+        let measurements = match &self.outcome {
+            Ok(measurements) => measurements,
+            Err(_) => return metrics,
+        };
+        let sizes = &measurements.sizes;
+        let mut size = |name: &str, bytes: u64| metrics.insert(name.to_string(), bytes as f64);
+        size("wasm", sizes.wasm);
+        if let Some(wasm_opt) = sizes.wasm_opt {
+            size("wasm-opt", wasm_opt);
+        }
+        size("gzip", sizes.gzip);
+        size("brotli", sizes.brotli);
+        size("js", sizes.js);
+        size("js-brotli", sizes.js_brotli);
+        size("total", sizes.total());
+        if let Some(attribution) = &measurements.attribution {
+            size("code", attribution.code);
+            size("data", attribution.data);
+        }
+        if let Some(checks) = &measurements.conformance {
+            let failures = checks.iter().filter(|check| !check.passed()).count();
+            metrics.insert("conformance-failures".to_string(), failures as f64);
+        }
+        // The median in milliseconds, e.g. `bench-add-1000`
+        for timing in measurements.benchmarks.iter().flatten() {
+            if let Ok(stats) = &timing.outcome {
+                metrics.insert(format!("bench-{}", timing.id), stats.median);
+            }
+            // Every kind at once, e.g. `mutations-add-1000`
+            if let Some(Ok(mutations)) = &timing.mutations {
+                metrics.insert(format!("mutations-{}", timing.id), mutations.total() as f64);
+            }
+        }
+        // In bytes at the end of a session, e.g. `memory-heap`
+        if let Some(memory) = measurements.memory.as_ref().filter(|memory| memory.failure.is_none()) {
+            if let Some(last) = memory.samples.last() {
+                metrics.insert("memory-wasm".to_string(), last.wasm as f64);
+                metrics.insert("memory-heap".to_string(), last.heap as f64);
+            }
+        }
+        metrics
+    }
+}
+
+/// The results of `app` as markdown: one row per implementation and feature
+/// set, one column per profile.
+pub fn markdown(matrix: &Matrix, app: &str, results: &[CellResult], toolchain: &Toolchain) -> String {
+    let results: Vec<_> = results.iter().filter(|r| r.app == app).collect();
+    let mut rows: Vec<&str> = Vec::new();
+    for result in &results {
+        if !rows.contains(&result.row.as_str()) {
+            rows.push(&result.row);
+        }
+    }
+
+    let mut md = String::new();
+    writeln!(md, "# Build results").unwrap();
+    writeln!(md, "```").unwrap();
+    for version in [&toolchain.rustc, &toolchain.trunk] {
+        writeln!(md, "{}", version.as_deref().unwrap_or("unknown")).unwrap();
+    }
+    if let Some(wasm_opt) = &toolchain.wasm_opt {
+        writeln!(md, "{wasm_opt}").unwrap();
+    }
+    for (name, version) in &toolchain.frameworks {
+        writeln!(md, "{name} {version}").unwrap();
+    }
+    writeln!(md, "```").unwrap();
+
+    writeln!(md, "## Wasm size").unwrap();
+    writeln!(md).unwrap();
+    write!(md, "| Implementations |").unwrap();
+    for profile in &matrix.profiles {
+        write!(md, " {} |", profile.name).unwrap();
+    }
+    write!(md, "\n|-----------------|").unwrap();
+    for profile in &matrix.profiles {
+        write!(md, "{}|", "-".repeat(profile.name.len() + 2)).unwrap();
+    }
+    writeln!(md).unwrap();
+    for row in rows {
+        write!(md, "| {row} |").unwrap();
+        for profile in &matrix.profiles {
+            let result = results.iter().find(|r| r.row == row && r.profile == profile.name);
+            match result.map(|r| &r.outcome) {
+                Some(Ok(measurements)) => write!(md, " {} |", measurements.sizes.wasm).unwrap(),
+                Some(Err(_)) => write!(md, " failed |").unwrap(),
+                None => write!(md, " |").unwrap(),
+            }
+        }
+        writeln!(md).unwrap();
+    }
+    writeln!(md).unwrap();
+    writeln!(md, "Sizes are in bytes, of the `_bg.wasm` file. The profiles are:").unwrap();
+    writeln!(md).unwrap();
+    for profile in &matrix.profiles {
+        writeln!(md, "* {}: `{}`", profile.name, profile.describe()).unwrap();
+    }
+
+    // What is downloaded, one table per profile
+    for profile in &matrix.profiles {
+        writeln!(md).unwrap();
+        writeln!(md, "## Transfer size, {}", profile.name).unwrap();
+        writeln!(md).unwrap();
+        writeln!(md, "| Implementations | wasm | wasm-opt | gzip | brotli | JS | JS brotli | total |").unwrap();
+        writeln!(md, "|-----------------|------|----------|------|--------|----|-----------|-------|").unwrap();
+        for result in results.iter().filter(|r| r.profile == profile.name) {
+            match &result.outcome {
+                Ok(Measurements { sizes, .. }) => {
+                    let wasm_opt = sizes.wasm_opt.map(|size| size.to_string()).unwrap_or_else(|| "-".to_string());
+                    writeln!(
+                        md,
+                        "| {} | {} | {wasm_opt} | {} | {} | {} | {} | {} |",
+                        result.row,
+                        sizes.wasm,
+                        sizes.gzip,
+                        sizes.brotli,
+                        sizes.js,
+                        sizes.js_brotli,
+                        sizes.total()
+                    )
+                    .unwrap();
+                }
+                Err(_) => writeln!(md, "| {} | failed | | | | | | |", result.row).unwrap(),
+            }
+        }
+    }
+    writeln!(md).unwrap();
+    match &matrix.wasm_opt {
+        Some(args) => write!(md, "gzip and brotli are the sizes of the wasm after `wasm-opt {}`", args.join(" ")),
+        None => write!(md, "gzip and brotli are the sizes of the wasm as built"),
+    }
+    .unwrap();
+    writeln!(
+        md,
+        ", with the best compression. JS is every JS file of the build, the glue and \
+         its snippets. The total is what a browser downloads with brotli: the wasm and the JS."
+    )
+    .unwrap();
+
+    if let Some(settings) = &matrix.browser {
+        let checked: Vec<_> = results
+            .iter()
+            .filter(|r| r.profile == settings.profile)
+            .filter_map(|r| {
+                let measurements = r.outcome.as_ref().ok()?;
+                Some((*r, measurements, measurements.conformance.as_deref()?))
+            })
+            .collect();
+        features_markdown(&mut md, &settings.profile, &checked);
+
+        let timed: Vec<_> = results
+            .iter()
+            .filter(|r| r.profile == settings.profile)
+            .filter_map(|r| Some((*r, r.outcome.as_ref().ok()?.benchmarks.as_deref()?)))
+            .collect();
+        benchmarks_markdown(&mut md, &settings.profile, &timed);
+
+        let followed: Vec<_> = results
+            .iter()
+            .filter(|r| r.profile == settings.profile)
+            .filter_map(|r| Some((*r, r.outcome.as_ref().ok()?.memory.as_ref()?)))
+            .collect();
+        memory_markdown(&mut md, &settings.profile, &followed);
+    }
+
+    if let Some(settings) = &matrix.attribution {
+        let attributed: Vec<_> = results
+            .iter()
+            .filter(|r| r.profile == settings.profile)
+            .filter_map(|r| Some((*r, r.outcome.as_ref().ok()?.attribution.as_ref()?)))
+            .collect();
+        attribution_markdown(&mut md, matrix, &settings.profile, settings.top, &attributed);
+    }
+
+    writeln!(md).unwrap();
+    writeln!(md, "This file is generated automatically by `cargo run -p compare -- build`.").unwrap();
+    writeln!(md).unwrap();
+    writeln!(md, "Any changes made to this file will be overwriten by a new generated file.").unwrap();
+    if let Some(history) = &matrix.history {
+        writeln!(md).unwrap();
+        writeln!(
+            md,
+            "Every run is also appended to `{}`, `cargo run -p compare -- diff` compares two runs.",
+            history.display()
+        )
+        .unwrap();
+    }
+    md
+}
+
+// Which features every implementation has, next to its size, then why the
+// others failed
+fn features_markdown(md: &mut String, profile: &str, results: &[(&CellResult, &Measurements, &[Check])]) {
+    if results.is_empty() {
+        return;
+    }
+    writeln!(md).unwrap();
+    writeln!(md, "## Features, {profile}").unwrap();
+    writeln!(md).unwrap();
+    write!(md, "| Implementations | total |").unwrap();
+    for feature in conformance::FEATURES {
+        write!(md, " {} |", feature.name).unwrap();
+    }
+    write!(md, "\n|-----------------|-------|").unwrap();
+    for feature in conformance::FEATURES {
+        write!(md, "{}|", "-".repeat(feature.name.len() + 2)).unwrap();
+    }
+    writeln!(md).unwrap();
+    for (result, measurements, checks) in results {
+        write!(md, "| {} | {} |", result.row, measurements.sizes.total()).unwrap();
+        for feature in conformance::FEATURES {
+            match feature.passed(checks) {
+                Some(true) => write!(md, " yes |"),
+                Some(false) => write!(md, " **no** |"),
+                None => write!(md, " |"),
+            }
+            .unwrap();
+        }
+        writeln!(md).unwrap();
+    }
+    writeln!(md).unwrap();
+    writeln!(
+        md,
+        "Checked against the TodoMVC spec in a headless browser, `cargo run -p compare -- conformance` \
+         checks again. The total is the transfer size in bytes. What failed:"
+    )
+    .unwrap();
+    writeln!(md).unwrap();
+    let mut failed = false;
+    for (result, _, checks) in results {
+        for check in checks.iter() {
+            if let Some(failure) = &check.failure {
+                let description = conformance::spec_item(&check.id).map_or(check.id.as_str(), |item| item.description);
+                writeln!(md, "* {}: {description}: {}", result.row, failure.replace('\n', " ")).unwrap();
+                failed = true;
+            }
+        }
+    }
+    if !failed {
+        writeln!(md, "* nothing").unwrap();
+    }
+}
+
+// An operation per row, as there are more implementations than fit
+fn benchmarks_markdown(md: &mut String, profile: &str, results: &[(&CellResult, &[Timing])]) {
+    if results.is_empty() {
+        return;
+    }
+    writeln!(md).unwrap();
+    writeln!(md, "## Runtime, {profile}").unwrap();
+    writeln!(md).unwrap();
+    write!(md, "| Operation |").unwrap();
+    for (result, _) in results {
+        write!(md, " {} |", result.row).unwrap();
+    }
+    write!(md, "\n|-----------|").unwrap();
+    for (result, _) in results {
+        write!(md, "{}|", "-".repeat(result.row.len() + 2)).unwrap();
+    }
+    writeln!(md).unwrap();
+    for operation in bench::OPERATIONS {
+        write!(md, "| {} |", operation.description).unwrap();
+        for (_, timings) in results {
+            match timings.iter().find(|t| t.id == operation.id).map(|t| &t.outcome) {
+                Some(Ok(stats)) => write!(md, " {} |", format_stats(stats)),
+                Some(Err(_)) => write!(md, " failed |"),
+                None => write!(md, " |"),
+            }
+            .unwrap();
+        }
+        writeln!(md).unwrap();
+    }
+    writeln!(md).unwrap();
+    let runs = results
+        .iter()
+        .flat_map(|(_, timings)| timings.iter())
+        .find_map(|t| t.outcome.as_ref().ok())
+        .map_or(0, |stats| stats.runs);
+    writeln!(
+        md,
+        "Medians of {runs} runs in a headless browser in milliseconds, with their 95% confidence \
+         interval: from the events of the operation until the DOM is updated and laid out."
+    )
+    .unwrap();
+    let failures: Vec<_> = results
+        .iter()
+        .flat_map(|(result, timings)| timings.iter().map(move |timing| (result, timing)))
+        .filter_map(|(result, timing)| Some((result, &timing.id, timing.outcome.as_ref().err()?)))
+        .collect();
+    if !failures.is_empty() {
+        writeln!(md).unwrap();
+    }
+    for (result, id, e) in failures {
+        writeln!(md, "* {} failed {id}: {}", result.row, e.replace('\n', " ")).unwrap();
+    }
+    mutations_markdown(md, profile, results);
+}
+
+// As the timings, which they explain
+fn mutations_markdown(md: &mut String, profile: &str, results: &[(&CellResult, &[Timing])]) {
+    let counted = |timings: &[Timing]| timings.iter().any(|t| t.mutations.is_some());
+    if !results.iter().any(|(_, timings)| counted(timings)) {
+        return;
+    }
+    writeln!(md).unwrap();
+    writeln!(md, "## DOM mutations, {profile}").unwrap();
+    writeln!(md).unwrap();
+    write!(md, "| Operation |").unwrap();
+    for (result, _) in results {
+        write!(md, " {} |", result.row).unwrap();
+    }
+    write!(md, "\n|-----------|").unwrap();
+    for (result, _) in results {
+        write!(md, "{}|", "-".repeat(result.row.len() + 2)).unwrap();
+    }
+    writeln!(md).unwrap();
+    for operation in bench::OPERATIONS {
+        write!(md, "| {} |", operation.description).unwrap();
+        for (_, timings) in results {
+            match timings.iter().find(|t| t.id == operation.id).and_then(|t| t.mutations.as_ref()) {
+                Some(Ok(mutations)) => write!(md, " {} |", format_mutations(mutations)),
+                Some(Err(_)) => write!(md, " failed |"),
+                None => write!(md, " |"),
+            }
+            .unwrap();
+        }
+        writeln!(md).unwrap();
+    }
+    writeln!(md).unwrap();
+    writeln!(
+        md,
+        "Counted on a run of their own with a `MutationObserver`: `+` nodes inserted, `-` nodes removed, \
+         then attributes, `value` and `checked` properties and text nodes set."
+    )
+    .unwrap();
+    let failures: Vec<_> = results
+        .iter()
+        .flat_map(|(result, timings)| timings.iter().map(move |timing| (result, timing)))
+        .filter_map(|(result, timing)| Some((result, &timing.id, timing.mutations.as_ref()?.as_ref().err()?)))
+        .collect();
+    if !failures.is_empty() {
+        writeln!(md).unwrap();
+    }
+    for (result, id, e) in failures {
+        writeln!(md, "* {} failed counting {id}: {}", result.row, e.replace('\n', " ")).unwrap();
+    }
+}
+
+// An implementation per row, with the memory before and after the cycles
+fn memory_markdown(md: &mut String, profile: &str, results: &[(&CellResult, &Memory)]) {
+    if results.is_empty() {
+        return;
+    }
+    writeln!(md).unwrap();
+    writeln!(md, "## Memory, {profile}").unwrap();
+    writeln!(md).unwrap();
+    writeln!(md, "| Implementation | wasm memory | JS heap | Growth |").unwrap();
+    writeln!(md, "|----------------|-------------|---------|--------|").unwrap();
+    for (result, memory) in results {
+        let range = |value: fn(&Sample) -> u64| match (memory.samples.first(), memory.samples.last()) {
+            (Some(first), Some(last)) => format!("{} → {}", group_digits(value(first)), group_digits(value(last))),
+            _ => String::new(),
+        };
+        let growth = match memory.leaks() {
+            true => format!("**{}**", format_growth(memory)),
+            false => format_growth(memory),
+        };
+        writeln!(md, "| {} | {} | {} | {growth} |", result.row, range(|s| s.wasm), range(|s| s.heap)).unwrap();
+    }
+    writeln!(md).unwrap();
+    let cycles = results
+        .iter()
+        .filter(|(_, memory)| memory.failure.is_none())
+        .find_map(|(_, memory)| memory.samples.last())
+        .map_or(0, |last| last.cycles);
+    writeln!(
+        md,
+        "In bytes, before and after {cycles} cycles of adding 10 todos, editing one, completing one and removing \
+         them, the JS heap after a garbage collection. Growth is after the first {} cycles, which warm up, when \
+         the memory grew and never went down over the {SAMPLES} samples: what every cycle keeps, e.g. closures that are \
+         never dropped.",
+        cycles / SAMPLES
+    )
+    .unwrap();
+    let failures: Vec<_> = results
+        .iter()
+        .filter_map(|(result, memory)| Some((result, memory.failure.as_ref()?)))
+        .collect();
+    if !failures.is_empty() {
+        writeln!(md).unwrap();
+    }
+    for (result, e) in failures {
+        writeln!(md, "* {} failed: {}", result.row, e.replace('\n', " ")).unwrap();
+    }
+}
+
+/// e.g. `wasm +65,536 heap +1,024`, or `none`.
+pub fn format_growth(memory: &Memory) -> String {
+    let growths = [("wasm", memory.wasm_growth()), ("heap", memory.heap_growth())];
+    let grown: Vec<String> = growths
+        .iter()
+        .filter_map(|(name, growth)| Some(format!("{name} +{}", group_digits((*growth)?))))
+        .collect();
+    match grown.is_empty() {
+        true => "none".to_string(),
+        false => grown.join(" "),
+    }
+}
+
+/// e.g. `12.3 (11.9-13.0)`, in milliseconds.
+pub fn format_stats(stats: &bench::Stats) -> String {
+    format!("{:.1} ({:.1}-{:.1})", stats.median, stats.low, stats.high)
+}
+
+/// e.g. `+1,000 -2 1,000 attr 500 prop`, without the kinds that are 0.
+pub fn format_mutations(mutations: &bench::Mutations) -> String {
+    let kinds = [
+        (mutations.insertions, "+", ""),
+        (mutations.removals, "-", ""),
+        (mutations.attributes, "", " attr"),
+        (mutations.properties, "", " prop"),
+        (mutations.text, "", " text"),
+    ];
+    let counts: Vec<String> = kinds
+        .iter()
+        .filter(|(count, ..)| *count > 0)
+        .map(|(count, sign, kind)| format!("{sign}{}{kind}", group_digits(*count)))
+        .collect();
+    match counts.is_empty() {
+        true => "none".to_string(),
+        false => counts.join(" "),
+    }
+}
+
+// Code by crate group for every implementation, then the largest functions
+fn attribution_markdown(md: &mut String, matrix: &Matrix, profile: &str, top: usize, results: &[(&CellResult, &Attribution)]) {
+    if results.is_empty() {
+        return;
+    }
+    let groups: Vec<String> = results[0].1.by_group(&matrix.crate_groups).into_iter().map(|(group, _)| group).collect();
+    writeln!(md).unwrap();
+    writeln!(md, "## Where the bytes go, {profile}").unwrap();
+    writeln!(md).unwrap();
+    write!(md, "| Implementations |").unwrap();
+    for group in &groups {
+        write!(md, " {group} |").unwrap();
+    }
+    writeln!(md, " code | data | total |").unwrap();
+    write!(md, "|-----------------|").unwrap();
+    for group in &groups {
+        write!(md, "{}|", "-".repeat(group.len() + 2)).unwrap();
+    }
+    writeln!(md, "------|------|-------|").unwrap();
+    for (result, attribution) in results {
+        write!(md, "| {} |", result.row).unwrap();
+        for (_, size) in attribution.by_group(&matrix.crate_groups) {
+            write!(md, " {size} |").unwrap();
+        }
+        writeln!(md, " {} | {} | {} |", attribution.code, attribution.data, attribution.total).unwrap();
+    }
+    writeln!(md).unwrap();
+    writeln!(
+        md,
+        "Bytes of function bodies by crate, as built (before `wasm-opt`). `framework` is every crate \
+         in no other group: the framework, its dependencies and the implementation. `?` is code \
+         whose crate is not known, e.g. without a `name` section."
+    )
+    .unwrap();
+
+    for (result, attribution) in results {
+        writeln!(md).unwrap();
+        writeln!(md, "### Largest functions, {}", result.row).unwrap();
+        writeln!(md).unwrap();
+        if !attribution.has_names {
+            writeln!(md, "The wasm has no `name` section, build it without stripping symbols.").unwrap();
+            continue;
+        }
+        writeln!(md, "| bytes | crate | function |").unwrap();
+        writeln!(md, "|-------|-------|----------|").unwrap();
+        for function in attribution.functions.iter().take(top) {
+            writeln!(md, "| {} | {} | `{}` |", function.size, function.crate_name, function.name.replace('|', "\\|")).unwrap();
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{bail, Context};
+
+use crate::build::Artifacts;
+
+/// The sizes of a build, in bytes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Sizes {
+    /// The `_bg.wasm` file as built.
+    pub wasm: u64,
+    /// After `wasm-opt`, `None` when it is not run, see `Matrix::wasm_opt`.
+    pub wasm_opt: Option<u64>,
+    /// The wasm that is served, after `wasm-opt` when it is run, compressed.
+    pub gzip: u64,
+    pub brotli: u64,
+    /// Every JS file of the build: the glue and its snippets.
+    pub js: u64,
+    pub js_brotli: u64,
+}
+
+impl Sizes {
+    /// What a browser downloads with brotli, the wasm and the JS.
+    pub fn total(&self) -> u64 {
+        self.brotli + self.js_brotli
+    }
+}
+
+/// Measure the artifacts of a build. With `wasm_opt` arguments, the wasm is
+/// also optimized with `wasm-opt`, next to the original.
+pub fn measure(artifacts: &Artifacts, wasm_opt: Option<&[String]>) -> anyhow::Result<Sizes> {
+    let wasm = read(&artifacts.wasm)?;
+    let optimized = match wasm_opt {
+        Some(args) => Some(read(&run_wasm_opt(&artifacts.wasm, args)?)?),
+        None => None,
+    };
+    let served = optimized.as_deref().unwrap_or(&wasm);
+
+    let mut js = 0;
+    let mut js_brotli = 0;
+    for path in &artifacts.js {
+        let bytes = read(path)?;
+        js += bytes.len() as u64;
+        js_brotli += brotli_size(&bytes);
+    }
+
+    Ok(Sizes {
+        wasm: wasm.len() as u64,
+        wasm_opt: optimized.as_ref().map(|bytes| bytes.len() as u64),
+        gzip: gzip_size(served),
+        brotli: brotli_size(served),
+        js,
+        js_brotli,
+    })
+}
+
+fn read(path: &Path) -> anyhow::Result<Vec<u8>> {
+    std::fs::read(path).with_context(|| format!("cannot read {}", path.display()))
+}
+
+/// `<name>.opt.wasm` next to `wasm`.
+fn run_wasm_opt(wasm: &Path, args: &[String]) -> anyhow::Result<PathBuf> {
+    let output = wasm.with_extension("opt.wasm");
+    let status = Command::new("wasm-opt")
+        .args(args)
+        .arg(wasm)
+        .arg("-o")
+        .arg(&output)
+        .status()
+        .context("cannot run wasm-opt, is binaryen installed?")?;
+    if !status.success() {
+        bail!("wasm-opt failed: {status}");
+    }
+    Ok(output)
+}
+
+/// With the best compression, like a server that compresses ahead of time.
+pub fn gzip_size(bytes: &[u8]) -> u64 {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::best());
+    encoder.write_all(bytes).unwrap();
+    encoder.finish().unwrap().len() as u64
+}
+
+/// With the best quality and the default window, see `gzip_size`.
+pub fn brotli_size(bytes: &[u8]) -> u64 {
+    let mut compressed = Vec::new();
+    let params = brotli::enc::BrotliEncoderParams {
+        quality: 11,
+        lgwin: 22,
+        ..Default::default()
+    };
+    brotli::BrotliCompress(&mut &bytes[..], &mut compressed, &params).unwrap();
+    compressed.len() as u64
+}
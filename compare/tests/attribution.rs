@@ -0,0 +1,143 @@
+use compare::attribution::*;
+
+fn leb128(mut n: usize, out: &mut Vec<u8>) {
+    loop {
+        let byte = (n & 0x7f) as u8;
+        n >>= 7;
+        match n {
+            0 => return out.push(byte),
+            _ => out.push(byte | 0x80),
+        }
+    }
+}
+
+fn name(s: &str, out: &mut Vec<u8>) {
+    leb128(s.len(), out);
+    out.extend_from_slice(s.as_bytes());
+}
+
+fn section(id: u8, content: Vec<u8>, out: &mut Vec<u8>) {
+    out.push(id);
+    leb128(content.len(), out);
+    out.extend(content);
+}
+
+// A module importing one function, with a function of `nops` nops for each
+// entry of `functions`, named unless the name is `None`, and 100 bytes of
+// data
+fn module(functions: &[(Option<&str>, usize)]) -> Vec<u8> {
+    let mut wasm = b"\0asm\x01\0\0\0".to_vec();
+    section(1, vec![1, 0x60, 0, 0], &mut wasm);
+    let mut imports = vec![1];
+    name("env", &mut imports);
+    name("f", &mut imports);
+    imports.extend([0, 0]);
+    section(2, imports, &mut wasm);
+    let mut types = Vec::new();
+    leb128(functions.len(), &mut types);
+    types.extend(functions.iter().map(|_| 0));
+    section(3, types, &mut wasm);
+    let mut code = Vec::new();
+    leb128(functions.len(), &mut code);
+    for (_, nops) in functions {
+        let mut body = vec![0];
+        body.extend(std::iter::repeat_n(0x01, *nops));
+        body.push(0x0b);
+        leb128(body.len(), &mut code);
+        code.extend(body);
+    }
+    section(10, code, &mut wasm);
+    let mut data = vec![1, 0, 0x41, 0, 0x0b];
+    leb128(100, &mut data);
+    data.extend([7; 100]);
+    section(11, data, &mut wasm);
+
+    let mut names = Vec::new();
+    name("name", &mut names);
+    let mut map = Vec::new();
+    let named: Vec<_> = functions.iter().enumerate().filter_map(|(i, (n, _))| Some((i + 1, (*n)?))).collect();
+    leb128(named.len(), &mut map);
+    for (index, function) in named {
+        leb128(index, &mut map);
+        name(function, &mut map);
+    }
+    names.push(1);
+    leb128(map.len(), &mut names);
+    names.extend(map);
+    section(0, names, &mut wasm);
+    wasm
+}
+
+#[test]
+fn attributes_functions_to_crates() {
+    let wasm = module(&[
+        (Some("_ZN4core3fmt5write17h0123456789abcdefE"), 300),
+        (Some("spair::dom::element::Element<C>::render::h0123456789abcdef"), 500),
+        (Some("<serde_json::error::Error as core::fmt::Display>::fmt"), 200),
+        (Some("_ZN49_$LT$uuid..Uuid$u20$as$u20$core..fmt..Display$GT$3fmt17h0123456789abcdefE"), 50),
+        (Some("__wbindgen_malloc"), 40),
+        (Some("todomvc_shared::Todos::new_entry"), 120),
+        (Some("spair::dom::nodes::Nodes<C>::list::h0123456789abcdef"), 100),
+        (None, 10),
+    ]);
+    let attribution = analyze(&wasm).unwrap();
+    assert!(attribution.has_names);
+    assert_eq!(attribution.total, wasm.len() as u64);
+    assert_eq!(attribution.code, 1320 + 8 * 2);
+    assert_eq!(attribution.data, 106);
+
+    let top: Vec<_> = attribution.functions.iter().take(4).map(|f| (f.name.as_str(), f.crate_name.as_str(), f.size)).collect();
+    assert_eq!(
+        top,
+        [
+            ("spair::dom::element::Element<C>::render", "spair", 502),
+            ("core::fmt::write", "core", 302),
+            ("<serde_json::error::Error as core::fmt::Display>::fmt", "serde_json", 202),
+            ("todomvc_shared::Todos::new_entry", "todomvc_shared", 122),
+        ]
+    );
+    let uuid = attribution.functions.iter().find(|f| f.size == 52).unwrap();
+    assert_eq!(uuid.name, "<uuid::Uuid as core::fmt::Display>::fmt");
+    assert_eq!(uuid.crate_name, "uuid");
+    // Indices count the imported function
+    assert_eq!(attribution.functions.last().unwrap().name, "function[8]");
+
+    assert_eq!(attribution.by_crate()[0], ("spair".to_string(), 604));
+    let groups = CrateGroups::from([
+        ("std".to_string(), vec!["core".to_string(), "alloc".to_string()]),
+        ("serde".to_string(), vec!["serde_json".to_string()]),
+        ("wasm-bindgen".to_string(), vec!["wasm_bindgen".to_string()]),
+    ]);
+    assert_eq!(
+        attribution.by_group(&groups),
+        [
+            ("serde".to_string(), 202),
+            ("std".to_string(), 302),
+            ("wasm-bindgen".to_string(), 42),
+            ("framework".to_string(), 604 + 122 + 52),
+            ("?".to_string(), 12),
+        ]
+    );
+}
+
+#[test]
+fn without_a_name_section_nothing_is_attributed() {
+    let wasm = module(&[(None, 10), (None, 20)]);
+    let attribution = analyze(&wasm).unwrap();
+    assert!(attribution.functions.iter().all(|f| f.crate_name == UNKNOWN_CRATE));
+    assert!(analyze(b"not wasm").is_err());
+}
+
+#[test]
+fn demangles_rust_symbols() {
+    assert_eq!(demangle("_ZN5alloc7raw_vec11finish_grow17h0123456789abcdefE"), "alloc::raw_vec::finish_grow");
+    assert_eq!(
+        demangle("_ZN4core3ptr54drop_in_place$LT$spair..component..Comp$LT$App$GT$$GT$17h0123456789abcdefE"),
+        "core::ptr::drop_in_place<spair::component::Comp<App>>"
+    );
+    assert_eq!(demangle("dlmalloc::dlmalloc::Dlmalloc<A>::malloc::h0123456789abcdef"), "dlmalloc::dlmalloc::Dlmalloc<A>::malloc");
+    assert_eq!(demangle("memcpy"), "memcpy");
+    assert_eq!(crate_of("memcpy"), UNKNOWN_CRATE);
+    assert_eq!(crate_of("<&mut yew::html::Scope<C> as core::fmt::Debug>::fmt"), "yew");
+    assert_eq!(crate_of("__wbg_set_value_f0a6d1f1"), "wasm_bindgen");
+}
@@ -0,0 +1,111 @@
+use std::collections::HashSet;
+
+use compare::bench::{self, Mutations, Stats, Timing, OPERATIONS};
+use compare::*;
+
+mod common;
+use common::{measured, MATRIX, Z};
+
+#[test]
+fn medians_and_their_confidence_interval() {
+    assert_eq!(Stats::of(&[]), None);
+    let one = Stats::of(&[4.0]).unwrap();
+    assert_eq!((one.median, one.low, one.high, one.runs), (4.0, 4.0, 4.0, 1));
+    let odd = Stats::of(&[5.0, 1.0, 3.0]).unwrap();
+    assert_eq!(odd.median, 3.0);
+    assert_eq!(Stats::of(&[4.0, 1.0, 3.0, 2.0]).unwrap().median, 2.5);
+
+    // With few runs the interval is every run
+    let ten: Vec<f64> = (1..=10).map(f64::from).collect();
+    let stats = Stats::of(&ten).unwrap();
+    assert_eq!((stats.median, stats.low, stats.high), (5.5, 1.0, 10.0));
+
+    let hundred: Vec<f64> = (1..=100).rev().map(f64::from).collect();
+    let stats = Stats::of(&hundred).unwrap();
+    assert_eq!((stats.median, stats.low, stats.high, stats.runs), (50.5, 40.0, 61.0, 100));
+}
+
+#[test]
+fn operations_are_unique() {
+    let ids: HashSet<_> = OPERATIONS.iter().map(|operation| operation.id).collect();
+    assert_eq!(ids.len(), OPERATIONS.len());
+    assert_eq!(bench::operation("edit-in-10k").unwrap().description, "edit a todo in a list of 10,000");
+    assert!(bench::operation("removed").is_none());
+}
+
+#[test]
+fn timings_are_recorded_and_reported() {
+    let matrix = Matrix::parse(MATRIX).unwrap();
+    assert_eq!(matrix.browser.as_ref().unwrap().runs, 3);
+    let results: Vec<_> = [("spair_keyed", 1.0), ("leptos", 2.0)]
+        .into_iter()
+        .map(|(row, factor)| {
+            let benchmarks = OPERATIONS
+                .iter()
+                .map(|operation| Timing {
+                    id: operation.id.to_string(),
+                    outcome: match operation.id {
+                        "edit-in-10k" if factor > 1.0 => Err("timed out".to_string()),
+                        _ => Ok(Stats::of(&[10.0 * factor, 12.0 * factor, 11.0 * factor]).unwrap()),
+                    },
+                    mutations: match operation.id {
+                        "add-1000" => Some(Ok(Mutations {
+                            insertions: 1000 * factor as u64,
+                            text: 2,
+                            ..Mutations::default()
+                        })),
+                        "toggle-all" if factor > 1.0 => Some(Err("unexpected value: {}".to_string())),
+                        _ => None,
+                    },
+                })
+                .collect();
+            measured(row, Z, |measurements| measurements.benchmarks = Some(benchmarks))
+        })
+        .collect();
+    assert_eq!(results[0].metrics()["bench-add-1000"], 11.0);
+    assert_eq!(results[1].metrics()["bench-add-1000"], 22.0);
+    assert!(!results[1].metrics().contains_key("bench-edit-in-10k"));
+    assert_eq!(results[1].metrics()["mutations-add-1000"], 2002.0);
+    assert!(!results[1].metrics().contains_key("mutations-toggle-all"));
+
+    let md = markdown(&matrix, "todomvc", &results, &Toolchain::default());
+    assert!(md.contains("## Runtime, opt-level = 'z'"));
+    assert!(md.contains("| add 1,000 todos | 11.0 (10.0-12.0) | 22.0 (20.0-24.0) |"));
+    assert!(md.contains("| edit a todo in a list of 10,000 | 11.0 (10.0-12.0) | failed |"));
+    assert!(md.contains("Medians of 3 runs"));
+    assert!(md.contains("* leptos failed edit-in-10k: timed out"));
+    assert!(md.contains("## DOM mutations, opt-level = 'z'"));
+    assert!(md.contains("| add 1,000 todos | +1,000 2 text | +2,000 2 text |"));
+    assert!(md.contains("| clear 500 completed of 1,000 todos | | |"));
+    assert!(md.contains("| complete 1,000 todos with `.toggle-all` | | failed |"));
+    assert!(md.contains("* leptos failed counting toggle-all: unexpected value: {}"));
+
+    let report = Report {
+        matrix: &matrix,
+        app: &matrix.apps[0],
+        results: &results,
+        toolchain: &Toolchain::default(),
+        built: String::new(),
+        lines: Vec::new(),
+    };
+    let html = report.html();
+    assert!(html.contains("<h2>Runtime</h2>"));
+    assert!(html.contains("<td class=\"number\" data-value=\"22\">22.0 (20.0-24.0)</td>"));
+    assert!(html.contains("22.0 ms"));
+    assert!(html.contains("<h2>DOM mutations</h2>"));
+    assert!(html.contains("<td class=\"number\" data-value=\"2002\">+2,000 2 text</td>"));
+}
+
+#[test]
+fn mutations_are_formatted_by_kind() {
+    assert_eq!(format_mutations(&Mutations::default()), "none");
+    let mutations = Mutations {
+        insertions: 1,
+        removals: 1,
+        attributes: 1000,
+        properties: 500,
+        text: 0,
+    };
+    assert_eq!(mutations.total(), 1502);
+    assert_eq!(format_mutations(&mutations), "+1 -1 1,000 attr 500 prop");
+}
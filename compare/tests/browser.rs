@@ -0,0 +1,70 @@
+use std::io::{Read, Write};
+use std::net::TcpStream;
+
+use compare::browser::{content_type, Browser, Server};
+
+fn get(url: &str, path: &str) -> String {
+    let address = url.trim_start_matches("http://").trim_end_matches('/');
+    let mut stream = TcpStream::connect(address).unwrap();
+    write!(stream, "GET {path} HTTP/1.1\r\nHost: {address}\r\nConnection: close\r\n\r\n").unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    response
+}
+
+#[test]
+fn serves_the_build() {
+    let dist = std::env::temp_dir().join(format!("compare-browser-{}", std::process::id()));
+    std::fs::create_dir_all(&dist).unwrap();
+    std::fs::write(dist.join("index.html"), "<!DOCTYPE html>").unwrap();
+    std::fs::write(dist.join("app_bg.wasm"), "\0asm").unwrap();
+
+    let server = Server::start(&dist).unwrap();
+    let index = get(&server.url, "/");
+    let wasm = get(&server.url, "/app_bg.wasm?v=1");
+    let missing = get(&server.url, "/missing.js");
+    let outside = get(&server.url, "/../index.html");
+    drop(server);
+    std::fs::remove_dir_all(&dist).unwrap();
+
+    assert!(index.starts_with("HTTP/1.1 200"));
+    assert!(index.contains("text/html") && index.ends_with("<!DOCTYPE html>"));
+    assert!(wasm.contains("application/wasm"));
+    assert!(missing.starts_with("HTTP/1.1 404"));
+    assert!(outside.starts_with("HTTP/1.1 404"));
+}
+
+#[test]
+fn content_types() {
+    assert_eq!(content_type("todomvc_leptos_bg.wasm"), "application/wasm");
+    assert_eq!(content_type("snippets/x/inline0.js"), "text/javascript");
+    assert_eq!(content_type("LICENSE"), "application/octet-stream");
+}
+
+#[test]
+fn eval_awaits_promises() {
+    // Needs a browser, which CI does not have
+    if std::env::var_os("CHROME").is_none() {
+        return;
+    }
+    let dist = std::env::temp_dir().join(format!("compare-eval-{}", std::process::id()));
+    std::fs::create_dir_all(&dist).unwrap();
+    std::fs::write(dist.join("index.html"), "<!DOCTYPE html>").unwrap();
+    let server = Server::start(&dist).unwrap();
+    let mut browser = Browser::launch().unwrap();
+    let mut page = browser.open(&server.url).unwrap();
+
+    let sum = page.eval::<u32>("1 + 1");
+    let delayed = page.eval::<f64>("(async () => { await new Promise(r => setTimeout(r, 10)); return 0.5 })()");
+    let resolved = page.eval::<Vec<u32>>("Promise.resolve([1, 2])");
+    let undefined = page.eval::<Option<u32>>("Promise.resolve()");
+    drop(page);
+    drop(browser);
+    drop(server);
+    std::fs::remove_dir_all(&dist).unwrap();
+
+    assert_eq!(sum.unwrap(), 2);
+    assert_eq!(delayed.unwrap(), 0.5);
+    assert_eq!(resolved.unwrap(), [1, 2]);
+    assert_eq!(undefined.unwrap(), None);
+}
@@ -0,0 +1,74 @@
+// Every test crate only uses some of the helpers
+#![allow(dead_code)]
+
+use compare::*;
+
+/// Two implementations, one of them also with a feature set, built with two
+/// profiles. The builds of `Z` run in the browser.
+pub const MATRIX: &str = r#"
+[browser]
+profile = "opt-level = 'z'"
+apps = ["todomvc"]
+runs = 3
+
+[[apps]]
+name = "todomvc"
+dir = "todomvc"
+results = "todomvc/results.md"
+report = "todomvc/report.html"
+implementations = ["spair_keyed", "leptos"]
+shared = ["todomvc_shared"]
+
+[[profiles]]
+name = "opt-level = 3"
+opt-level = 3
+lto = true
+codegen-units = 1
+
+[[profiles]]
+name = "opt-level = 'z'"
+opt-level = "z"
+lto = "thin"
+panic = "abort"
+
+[[feature-sets]]
+name = "default"
+
+[[feature-sets]]
+name = "nightly"
+default-features = false
+features = ["csr", "nightly"]
+implementations = ["leptos"]
+"#;
+
+pub const O3: &str = "opt-level = 3";
+pub const Z: &str = "opt-level = 'z'";
+
+/// A build of `MATRIX` that only has its sizes measured, the same for every
+/// build.
+pub fn result(row: &str, profile: &str) -> CellResult {
+    let sizes = Sizes {
+        wasm: 1000,
+        wasm_opt: None,
+        gzip: 500,
+        brotli: 400,
+        js: 300,
+        js_brotli: 100,
+    };
+    CellResult {
+        app: "todomvc".to_string(),
+        row: row.to_string(),
+        profile: profile.to_string(),
+        outcome: Ok(Measurements {
+            sizes,
+            ..Default::default()
+        }),
+    }
+}
+
+/// `result` with more of it measured by `measure`.
+pub fn measured(row: &str, profile: &str, measure: impl FnOnce(&mut Measurements)) -> CellResult {
+    let mut result = result(row, profile);
+    measure(result.outcome.as_mut().unwrap());
+    result
+}
@@ -0,0 +1,84 @@
+use std::collections::HashSet;
+
+use compare::conformance::{self, Check, SPEC};
+use compare::*;
+
+mod common;
+use common::{measured, MATRIX, Z};
+
+#[test]
+fn spec_items_are_unique() {
+    let ids: HashSet<_> = SPEC.iter().map(|item| item.id).collect();
+    assert_eq!(ids.len(), SPEC.len());
+    assert_eq!(conformance::spec_item("edit-escape").unwrap().description, "Escape cancels the edit");
+    assert!(conformance::spec_item("removed").is_none());
+}
+
+fn checked(failing: &[&str]) -> Vec<Check> {
+    SPEC.iter()
+        .map(|item| Check {
+            id: item.id.to_string(),
+            failure: failing.contains(&item.id).then(|| "expected true, got false".to_string()),
+        })
+        .collect()
+}
+
+#[test]
+fn failures_are_recorded_and_reported() {
+    let matrix = Matrix::parse(MATRIX).unwrap();
+    assert_eq!(matrix.cells().iter().filter(|cell| matrix.runs_in_browser(cell)).count(), 3);
+    let results = [
+        measured("leptos", Z, |measurements| measurements.conformance = Some(checked(&["toggle-all-state"]))),
+        measured("leptos (nightly)", Z, |measurements| measurements.conformance = Some(checked(&[]))),
+    ];
+    assert_eq!(results[0].metrics()["conformance-failures"], 1.0);
+    assert_eq!(results[1].metrics()["conformance-failures"], 0.0);
+
+    let report = Report {
+        matrix: &matrix,
+        app: &matrix.apps[0],
+        results: &results,
+        toolchain: &Toolchain::default(),
+        built: String::new(),
+        lines: Vec::new(),
+    };
+    let html = report.html();
+    assert!(html.contains("<h2>Conformance</h2>"));
+    assert!(html.contains("<td class=\"fail\" data-value=\"0\" title=\"expected true, got false\">fail</td>"));
+    let total = SPEC.len();
+    assert!(html.contains(&format!(">{} of {total}</td>", total - 1)));
+    assert!(html.contains(&format!(">{total} of {total}</td>")));
+}
+
+#[test]
+fn the_browser_profile_must_exist() {
+    let error = Matrix::parse(&MATRIX.replace("profile = \"opt-level = 'z'\"", "profile = \"O3\"")).unwrap_err();
+    assert_eq!(error.to_string(), "browser: there is no profile `O3`");
+}
+
+#[test]
+fn features_next_to_the_sizes() {
+    let matrix = Matrix::parse(MATRIX).unwrap();
+    let failing: [(&str, &[&str]); 3] = [
+        ("spair_keyed", &["hide-when-empty"]),
+        ("leptos", &["edit-escape", "count"]),
+        ("leptos (nightly)", &[]),
+    ];
+    let results = failing.map(|(row, failing)| {
+        measured(row, Z, |measurements| measurements.conformance = Some(checked(failing)))
+    });
+    let md = markdown(&matrix, "todomvc", &results, &Toolchain::default());
+
+    assert!(md.contains("## Features, opt-level = 'z'"));
+    let row = |name: &str| md.lines().find(|line| line.starts_with(&format!("| {name} | 500 |"))).unwrap().to_string();
+    let no = |row: &str| row.matches("**no**").count();
+    assert_eq!(no(&row("spair_keyed")), 1);
+    assert_eq!(no(&row("leptos")), 2);
+    assert_eq!(no(&row("leptos (nightly)")), 0);
+    assert!(md.contains("* leptos: Escape cancels the edit: expected true, got false"));
+
+    let header = md.lines().find(|line| line.starts_with("| Implementations | total |")).unwrap();
+    let edit_escape = header.split('|').position(|column| column.trim() == "edit: Escape").unwrap();
+    assert_eq!(row("leptos").split('|').nth(edit_escape).unwrap().trim(), "**no**");
+    assert_eq!(row("spair_keyed").split('|').nth(edit_escape).unwrap().trim(), "yes");
+}
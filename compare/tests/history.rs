@@ -0,0 +1,176 @@
+use std::collections::BTreeMap;
+
+use compare::history::*;
+use compare::Toolchain;
+
+// Row, profile and metrics
+type Cell<'a> = (&'a str, &'a str, &'a [(&'a str, f64)]);
+
+fn run(commit: &str, rustc: &str, cells: &[Cell]) -> Run {
+    Run {
+        started_at: "2022-11-02T10:04:31Z".to_string(),
+        commit: Some(commit.to_string()),
+        dirty: false,
+        toolchain: Toolchain {
+            rustc: Some(rustc.to_string()),
+            ..Toolchain::default()
+        },
+        cells: cells
+            .iter()
+            .map(|(row, profile, metrics)| RunCell {
+                app: "todomvc".to_string(),
+                row: row.to_string(),
+                profile: profile.to_string(),
+                error: None,
+                metrics: metrics.iter().map(|(name, value)| (name.to_string(), *value)).collect(),
+            })
+            .collect(),
+    }
+}
+
+#[test]
+fn runs_are_appended_and_found() {
+    let path = std::env::temp_dir().join(format!("compare-history-{}.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    assert!(load(&path).unwrap().is_empty());
+    let first = run("897e375aaaa", "rustc 1.65.0", &[("yew_non_keyed", "z", &[("wasm", 1000.0)])]);
+    let second = run("1234567bbbb", "rustc 1.66.0", &[]);
+    append(&path, &first).unwrap();
+    append(&path, &second).unwrap();
+    let runs = load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(runs, [first, second]);
+
+    assert_eq!(find(&runs, "-1").unwrap().commit.as_deref(), Some("1234567bbbb"));
+    assert_eq!(find(&runs, "0").unwrap().commit.as_deref(), Some("897e375aaaa"));
+    assert_eq!(find(&runs, "897e").unwrap().toolchain.rustc.as_deref(), Some("rustc 1.65.0"));
+    assert!(find(&runs, "-3").is_err());
+    assert!(find(&runs, "2").is_err());
+    assert!(find(&runs, "abcdef").is_err());
+    assert_eq!(runs[0].label(), "2022-11-02T10:04:31Z 897e375");
+}
+
+#[test]
+fn diffs_show_the_largest_regressions_first() {
+    let old = run(
+        "a",
+        "rustc 1.65.0",
+        &[
+            ("spair_keyed", "z", &[("wasm", 1000.0), ("brotli", 400.0)]),
+            ("yew_non_keyed", "z", &[("wasm", 2000.0), ("brotli", 800.0)]),
+            ("leptos", "z", &[("wasm", 3000.0)]),
+        ],
+    );
+    let new = run(
+        "b",
+        "rustc 1.66.0",
+        &[
+            ("spair_keyed", "z", &[("wasm", 1010.0), ("brotli", 400.0)]),
+            ("yew_non_keyed", "z", &[("wasm", 1900.0), ("brotli", 880.0)]),
+            ("dominator", "z", &[("wasm", 3000.0)]),
+        ],
+    );
+    let changes: Vec<_> = diff(&old, &new).iter().map(|c| (c.row.clone(), c.percent().round())).collect();
+    assert_eq!(
+        changes,
+        [
+            ("leptos".to_string(), f64::INFINITY),
+            ("yew_non_keyed".to_string(), 10.0),
+            ("spair_keyed".to_string(), 1.0),
+            ("yew_non_keyed".to_string(), -5.0),
+        ]
+    );
+    let changes = diff(&old, &new);
+    assert_eq!(changes[0].kind, ChangeKind::Missing);
+    assert!(changes[1].is_regression(5.0));
+    assert!(!changes[2].is_regression(5.0));
+    assert!(!changes[3].is_regression(0.0));
+}
+
+#[test]
+fn rises_from_zero_are_regressions() {
+    let old = run("a", "rustc 1.65.0", &[("leptos", "z", &[("conformance-failures", 0.0), ("wasm", 1000.0)])]);
+    let new = run("b", "rustc 1.65.0", &[("leptos", "z", &[("conformance-failures", 2.0), ("wasm", 1000.0)])]);
+    let changes = diff(&old, &new);
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].percent(), f64::INFINITY);
+    assert!(changes[0].is_regression(1000.0));
+}
+
+#[test]
+fn failed_and_missing_cells_are_regressions() {
+    let mut old = run(
+        "a",
+        "rustc 1.65.0",
+        &[
+            ("leptos", "z", &[("wasm", 1000.0)]),
+            ("dominator", "z", &[("wasm", 1000.0)]),
+            ("sycamore", "z", &[]),
+        ],
+    );
+    let mut new = run("b", "rustc 1.65.0", &[("leptos", "z", &[]), ("sycamore", "z", &[])]);
+    new.cells[0].error = Some("build failed".to_string());
+    // Failing in both runs is not a change
+    old.cells[2].error = Some("build failed".to_string());
+    new.cells[1].error = Some("build failed".to_string());
+    let changes = diff(&old, &new);
+    let kinds: Vec<_> = changes.iter().map(|c| (c.row.as_str(), c.kind.clone())).collect();
+    assert_eq!(
+        kinds,
+        [
+            ("leptos", ChangeKind::Failed("build failed".to_string())),
+            ("dominator", ChangeKind::Missing),
+        ]
+    );
+    assert!(changes.iter().all(|c| c.is_regression(1000.0)));
+}
+
+#[test]
+fn missing_metrics_are_regressions() {
+    let old = run("a", "rustc 1.65.0", &[("leptos", "z", &[("bench-add-1000", 12.0), ("wasm", 1000.0)])]);
+    let new = run("b", "rustc 1.65.0", &[("leptos", "z", &[("wasm", 1000.0), ("gzip", 400.0)])]);
+    let changes = diff(&old, &new);
+    // New metrics have nothing to compare with
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].kind, ChangeKind::MissingMetric("bench-add-1000".to_string()));
+    assert!(changes[0].is_regression(1000.0));
+}
+
+#[test]
+fn versions_come_from_the_lock_file() {
+    let lock = r#"
+version = 3
+
+[[package]]
+name = "spair"
+version = "0.0.9"
+
+[[package]]
+name = "wasm-bindgen"
+version = "0.2.83"
+
+[[package]]
+name = "serde"
+version = "1.0.147"
+
+[[package]]
+name = "wasm-bindgen"
+version = "0.2.84"
+"#;
+    let crates = ["spair".to_string(), "wasm-bindgen".to_string(), "yew".to_string()];
+    assert_eq!(
+        locked_versions(lock, &crates).unwrap(),
+        BTreeMap::from([
+            ("spair".to_string(), "0.0.9".to_string()),
+            ("wasm-bindgen".to_string(), "0.2.83, 0.2.84".to_string()),
+        ])
+    );
+}
+
+#[test]
+fn formats_utc_times() {
+    assert_eq!(format_utc(0), "1970-01-01T00:00:00Z");
+    assert_eq!(format_utc(1667383471), "2022-11-02T10:04:31Z");
+    assert_eq!(format_utc(951782400), "2000-02-29T00:00:00Z");
+    assert_eq!(format_utc(4107542399), "2100-02-28T23:59:59Z");
+}
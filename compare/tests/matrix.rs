@@ -0,0 +1,116 @@
+use std::path::Path;
+
+use compare::*;
+
+mod common;
+use common::{result, MATRIX};
+
+#[test]
+fn every_cell_of_the_matrix() {
+    let matrix = Matrix::parse(MATRIX).unwrap();
+    let cells: Vec<_> = matrix.cells().iter().map(ToString::to_string).collect();
+    assert_eq!(
+        cells,
+        [
+            "todomvc/spair_keyed [default] opt-level = 3",
+            "todomvc/spair_keyed [default] opt-level = 'z'",
+            "todomvc/leptos [default] opt-level = 3",
+            "todomvc/leptos [default] opt-level = 'z'",
+            "todomvc/leptos [nightly] opt-level = 3",
+            "todomvc/leptos [nightly] opt-level = 'z'",
+        ]
+    );
+
+    let cell = matrix.cells()[5];
+    assert_eq!(cell.crate_dir(), Path::new("todomvc/leptos"));
+    assert_eq!(
+        cell.dist_dir(Path::new("target/compare")),
+        Path::new("target/compare/todomvc/leptos/nightly/opt-level-z")
+    );
+    assert_eq!(cell.row(), "leptos (nightly)");
+    assert_eq!(cell.features.args(), ["--no-default-features", "--features", "csr,nightly"]);
+    assert_eq!(matrix.cells()[0].row(), "spair_keyed");
+    assert!(matrix.cells()[0].features.args().is_empty());
+}
+
+#[test]
+fn profiles_are_passed_as_environment_variables() {
+    let matrix = Matrix::parse(MATRIX).unwrap();
+    assert_eq!(
+        matrix.profiles[0].env(),
+        [
+            ("CARGO_PROFILE_RELEASE_OPT_LEVEL", "3".to_string()),
+            ("CARGO_PROFILE_RELEASE_LTO", "true".to_string()),
+            ("CARGO_PROFILE_RELEASE_CODEGEN_UNITS", "1".to_string()),
+        ]
+    );
+    assert_eq!(
+        matrix.profiles[1].env(),
+        [
+            ("CARGO_PROFILE_RELEASE_OPT_LEVEL", "z".to_string()),
+            ("CARGO_PROFILE_RELEASE_LTO", "thin".to_string()),
+            ("CARGO_PROFILE_RELEASE_PANIC", "abort".to_string()),
+        ]
+    );
+    assert_eq!(matrix.profiles[1].describe(), r#"opt-level = "z", lto = "thin", panic = "abort""#);
+}
+
+#[test]
+fn rejects_invalid_matrices() {
+    let error = |text: &str| format!("{:#}", Matrix::parse(text).unwrap_err());
+    let twice = MATRIX.replace("\"opt-level = 'z'\"", "\"opt-level = 3\"");
+    assert_eq!(error(&twice), "profile `opt-level = 3` is listed twice");
+    let panic = MATRIX.replace("\"abort\"", "\"crash\"");
+    assert!(error(&panic).contains("panic must be `unwind` or `abort`"));
+    assert!(error(&MATRIX.replace("lto = true", "lto = true\nstrip = true")).contains("strip"));
+    let attribution = format!("[attribution]\nprofile = \"opt-level = 's'\"\n{MATRIX}");
+    assert_eq!(error(&attribution), "attribution: there is no profile `opt-level = 's'`");
+
+    // Without feature sets, everything is built with its default features
+    let matrix = Matrix::parse(MATRIX.split("[[feature-sets]]").next().unwrap()).unwrap();
+    assert_eq!(matrix.cells().len(), 4);
+    assert_eq!(matrix.feature_sets[0].name, DEFAULT_FEATURE_SET);
+}
+
+#[test]
+fn the_checked_in_matrix_is_valid() {
+    let matrix = Matrix::load(&Path::new(env!("CARGO_MANIFEST_DIR")).join("matrix.toml")).unwrap();
+    assert_eq!(matrix.cells().len(), 18);
+}
+
+#[test]
+fn results_have_a_column_per_profile() {
+    let matrix = Matrix::parse(MATRIX).unwrap();
+    let results: Vec<_> = matrix
+        .cells()
+        .iter()
+        .enumerate()
+        .map(|(i, cell)| {
+            let mut result = result(&cell.row(), &cell.profile.name);
+            match i {
+                3 => result.outcome = Err("trunk build failed".to_string()),
+                _ => result.outcome.as_mut().unwrap().sizes.wasm = 1000 + i as u64,
+            }
+            result
+        })
+        .collect();
+    let toolchain = Toolchain {
+        rustc: Some("rustc 1.65.0".to_string()),
+        trunk: None,
+        wasm_opt: None,
+        frameworks: [("spair".to_string(), "0.0.9".to_string())].into(),
+    };
+    let md = markdown(&matrix, "todomvc", &results, &toolchain);
+    assert!(md.contains("```\nrustc 1.65.0\nunknown\nspair 0.0.9\n```"));
+    assert!(md.contains("| Implementations | opt-level = 3 | opt-level = 'z' |\n"));
+    assert!(md.contains("| spair_keyed | 1000 | 1001 |\n| leptos | 1002 | failed |\n| leptos (nightly) | 1004 | 1005 |\n"));
+    assert!(md.contains("* opt-level = 'z': `opt-level = \"z\", lto = \"thin\", panic = \"abort\"`"));
+    assert!(md.contains(
+        "## Transfer size, opt-level = 'z'\n\n\
+         | Implementations | wasm | wasm-opt | gzip | brotli | JS | JS brotli | total |\n\
+         |-----------------|------|----------|------|--------|----|-----------|-------|\n\
+         | spair_keyed | 1001 | - | 500 | 400 | 300 | 100 | 500 |\n\
+         | leptos | failed | | | | | | |\n"
+    ));
+    assert!(md.contains("the sizes of the wasm as built, with the best compression"));
+}
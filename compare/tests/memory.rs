@@ -0,0 +1,92 @@
+use compare::memory::{self, Memory, Sample};
+use compare::*;
+
+mod common;
+use common::{measured, MATRIX, Z};
+
+// A sample every 200 cycles, from the values after each batch
+fn memory(wasm: &[u64], heap: &[u64]) -> Memory {
+    Memory {
+        samples: wasm
+            .iter()
+            .zip(heap)
+            .enumerate()
+            .map(|(i, (&wasm, &heap))| Sample { cycles: i * 200, wasm, heap })
+            .collect(),
+        failure: None,
+    }
+}
+
+#[test]
+fn growth_after_the_warm_up() {
+    // The warm-up can grow, what follows cannot
+    assert_eq!(memory::growth(&[100, 200, 200, 200, 200]), None);
+    assert_eq!(memory::growth(&[100, 200, 200, 300, 300]), Some(100));
+    assert_eq!(memory::growth(&[100, 200, 210, 205, 220]), None);
+    assert_eq!(memory::growth(&[100, 200, 210, 220, 230]), Some(30));
+    // A heap that shrinks after the warm-up does not leak
+    assert_eq!(memory::growth(&[100, 200, 190, 180]), None);
+    assert_eq!(memory::growth(&[100, 200, 150, 150, 150]), None);
+    // Too few samples to tell
+    assert_eq!(memory::growth(&[100, 200, 300]), None);
+    assert_eq!(memory::growth(&[]), None);
+
+    let leaking = memory(&[65536, 131072, 131072, 196608], &[1000, 2000, 2100, 2200]);
+    assert_eq!(leaking.wasm_growth(), Some(65536));
+    assert_eq!(leaking.heap_growth(), Some(200));
+    assert!(leaking.leaks());
+    assert_eq!(format_growth(&leaking), "wasm +65,536 heap +200");
+    let steady = memory(&[65536, 131072, 131072, 131072], &[1000, 2000, 1900, 2000]);
+    assert!(!steady.leaks());
+    assert_eq!(format_growth(&steady), "none");
+}
+
+#[test]
+fn cycles_must_cover_every_sample() {
+    let matrix = Matrix::parse(MATRIX).unwrap();
+    assert_eq!(matrix.browser.as_ref().unwrap().cycles, 2000);
+    let error = Matrix::parse(&MATRIX.replace("apps = [\"todomvc\"]\n", "apps = [\"todomvc\"]\ncycles = 5\n")).unwrap_err();
+    assert_eq!(error.to_string(), "browser: cycles must be at least 10");
+}
+
+#[test]
+fn memory_is_recorded_and_reported() {
+    let matrix = Matrix::parse(MATRIX).unwrap();
+    let results = [
+        measured("spair_keyed", Z, |measurements| {
+            measurements.memory = Some(memory(
+                &[1_114_112, 1_179_648, 1_179_648, 1_179_648],
+                &[900_000, 950_000, 940_000, 951_000],
+            ))
+        }),
+        measured("leptos", Z, |measurements| {
+            measurements.memory = Some(Memory {
+                samples: vec![Sample { cycles: 0, wasm: 1_114_112, heap: 900_000 }],
+                failure: Some("after 200 cycles: timed out".to_string()),
+            })
+        }),
+    ];
+    assert_eq!(results[0].metrics()["memory-wasm"], 1_179_648.0);
+    assert_eq!(results[0].metrics()["memory-heap"], 951_000.0);
+    // An incomplete session is no measurement
+    assert!(!results[1].metrics().contains_key("memory-heap"));
+
+    let md = markdown(&matrix, "todomvc", &results, &Toolchain::default());
+    assert!(md.contains("## Memory, opt-level = 'z'"));
+    assert!(md.contains("| spair_keyed | 1,114,112 → 1,179,648 | 900,000 → 951,000 | none |"));
+    assert!(md.contains("after 600 cycles"));
+    assert!(md.contains("* leptos failed: after 200 cycles: timed out"));
+
+    let report = Report {
+        matrix: &matrix,
+        app: &matrix.apps[0],
+        results: &results,
+        toolchain: &Toolchain::default(),
+        built: String::new(),
+        lines: Vec::new(),
+    };
+    let html = report.html();
+    assert!(html.contains("<h2>Memory</h2>"));
+    assert!(html.contains("<td class=\"number\" data-value=\"951000\">951,000</td>"));
+    assert!(html.contains("title=\"after 200 cycles: timed out\">fail</td>"));
+}
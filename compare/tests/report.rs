@@ -0,0 +1,62 @@
+use compare::*;
+
+mod common;
+use common::{result, MATRIX, O3, Z};
+
+#[test]
+fn a_self_contained_page() {
+    let matrix = Matrix::parse(MATRIX).unwrap();
+    let mut results = vec![result("spair_keyed", O3), result("spair_keyed", Z), result("leptos", O3)];
+    results[0].outcome.as_mut().unwrap().sizes.wasm = 175479;
+    results.push(CellResult {
+        outcome: Err("trunk failed".to_string()),
+        ..result("leptos", Z)
+    });
+    let report = Report {
+        matrix: &matrix,
+        app: &matrix.apps[0],
+        results: &results,
+        toolchain: &Toolchain::default(),
+        built: "2022-11-02T10:04:31Z 897e375".to_string(),
+        lines: vec![("spair_keyed".to_string(), Lines::count("fn main() {}\n"))],
+    };
+    let html = report.html();
+
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<table class=\"sortable\">"));
+    assert!(html.contains("<svg"));
+    assert!(html.contains("<script>"));
+    // Nothing is loaded from elsewhere, the namespace of the SVG is no link
+    assert!(!html.replace("xmlns=\"http://www.w3.org/2000/svg\"", "").contains("http"));
+    assert!(html.contains("data-value=\"175479\">175,479</td>"));
+    assert!(html.contains("<td class=\"failed\">failed</td>"));
+    assert!(html.contains("opt-level = &quot;z&quot;"));
+    assert!(html.contains("<h2>Lines of code</h2>"));
+    // No attribution without its settings
+    assert!(!html.contains("Where the bytes go"));
+}
+
+#[test]
+fn escaping_and_digit_groups() {
+    assert_eq!(report::escape("<Vec<T> as \"Drop\">::drop & co"), "&lt;Vec&lt;T&gt; as &quot;Drop&quot;&gt;::drop &amp; co");
+    assert_eq!(report::group_digits(0), "0");
+    assert_eq!(report::group_digits(999), "999");
+    assert_eq!(report::group_digits(1000), "1,000");
+    assert_eq!(report::group_digits(175479), "175,479");
+    assert_eq!(report::group_digits(12345678), "12,345,678");
+}
+
+#[test]
+fn counting_lines() {
+    let text = "//! Docs\n\nuse std::fmt;\n\n/* a\n   block */\nfn f() {} // trailing\n    /// doc\nstruct S;\n";
+    let lines = Lines::count(text);
+    assert_eq!(
+        lines,
+        Lines {
+            files: 1,
+            code: 3,
+            comments: 4,
+            blank: 2,
+        }
+    );
+}
@@ -0,0 +1,38 @@
+use compare::build::Artifacts;
+use compare::sizes::{brotli_size, gzip_size, measure};
+
+#[test]
+fn compressed_sizes() {
+    let repetitive = "<li class=\"todo\">".repeat(1000);
+    assert!(gzip_size(repetitive.as_bytes()) < 200);
+    assert!(brotli_size(repetitive.as_bytes()) < gzip_size(repetitive.as_bytes()));
+    // Compressing adds a header to what cannot be compressed
+    assert!(gzip_size(&[7]) > 1);
+}
+
+#[test]
+fn measures_the_wasm_and_every_js_file() {
+    let dist = std::env::temp_dir().join(format!("compare-sizes-{}", std::process::id()));
+    std::fs::create_dir_all(dist.join("snippets")).unwrap();
+    let wasm = dist.join("todomvc_spair_keyed_bg.wasm");
+    let glue = dist.join("todomvc_spair_keyed.js");
+    let snippet = dist.join("snippets").join("inline0.js");
+    std::fs::write(&wasm, vec![0u8; 4096]).unwrap();
+    std::fs::write(&glue, "export function f() {}\n".repeat(50)).unwrap();
+    std::fs::write(&snippet, "x").unwrap();
+
+    let artifacts = Artifacts {
+        dist: dist.clone(),
+        wasm,
+        js: vec![glue, snippet],
+    };
+    let sizes = measure(&artifacts, None).unwrap();
+    std::fs::remove_dir_all(&dist).unwrap();
+
+    assert_eq!(sizes.wasm, 4096);
+    assert_eq!(sizes.wasm_opt, None);
+    assert!(sizes.gzip < 100 && sizes.brotli < 100);
+    assert_eq!(sizes.js, 50 * 23 + 1);
+    assert!(sizes.js_brotli < sizes.js);
+    assert_eq!(sizes.total(), sizes.brotli + sizes.js_brotli);
+}
@@ -1,9 +1,11 @@
 use std::sync::Arc;
 use web_sys::{Url, HtmlInputElement};
 use serde_derive::{Serialize, Deserialize};
+use futures_signals::map_ref;
 use futures_signals::signal::{Signal, SignalExt, Mutable};
 use futures_signals::signal_vec::{SignalVec, SignalVecExt, MutableVec};
-use dominator::{Dom, EventOptions, text_signal, html, clone, events, link, with_node, routing};
+use dominator::{Dom, EventOptions, text_signal, html, clone, events, with_node, routing};
+use todomvc_shared::{Filter, Search, SortOrder, TagIndex};
 
 use crate::todo::Todo;
 use crate::util::{trim, local_storage};
@@ -16,22 +18,49 @@ pub enum Route {
     All,
 }
 
+// The hash is parsed by todomvc_shared, so that it understands the same
+// parameters (e.g. `?q=milk&sort=alpha`) as the other implementations
+fn parse_url(url: &str) -> todomvc_shared::Route {
+    let url = Url::new(&url).unwrap();
+    todomvc_shared::Route::parse(&url.hash())
+}
+
 impl Route {
-    // This could use more advanced URL parsing, but it isn't needed
     pub fn from_url(url: &str) -> Self {
-        let url = Url::new(&url).unwrap();
-        match url.hash().as_str() {
-            "#/active" => Route::Active,
-            "#/completed" => Route::Completed,
-            _ => Route::All,
-        }
+        parse_url(url).filter.into()
     }
 
-    pub fn to_url(&self) -> &'static str {
+    pub fn fits(&self, completed: bool) -> bool {
         match self {
-            Route::Active => "#/active",
-            Route::Completed => "#/completed",
-            Route::All => "#/",
+            Route::Active => !completed,
+            Route::Completed => completed,
+            Route::All => true,
+        }
+    }
+}
+
+impl From<Filter> for Route {
+    fn from(filter: Filter) -> Self {
+        match filter {
+            Filter::Active => Route::Active,
+            Filter::Completed => Route::Completed,
+            Filter::All => Route::All,
+            // This implementation has no due dates
+            Filter::Overdue | Filter::DueToday | Filter::Upcoming => Route::All,
+            // Nor dependencies, so every active todo can be done next
+            Filter::Next => Route::Active,
+            // Nor a board, the list shows every todo
+            Filter::Board | Filter::Archive => Route::All,
+        }
+    }
+}
+
+impl From<Route> for Filter {
+    fn from(route: Route) -> Self {
+        match route {
+            Route::Active => Filter::Active,
+            Route::Completed => Filter::Completed,
+            Route::All => Filter::All,
         }
     }
 }
@@ -53,6 +82,18 @@ pub struct App {
 
     #[serde(skip)]
     route: Mutable<Route>,
+
+    #[serde(skip)]
+    sort: Mutable<SortOrder>,
+
+    #[serde(skip)]
+    tag: Mutable<Option<String>>,
+
+    #[serde(skip)]
+    query: Mutable<String>,
+
+    #[serde(skip)]
+    pub dragging: Mutable<Option<uuid::Uuid>>,
 }
 
 impl App {
@@ -61,6 +102,10 @@ impl App {
             new_todo_title: Mutable::new("".to_owned()),
             todo_list: MutableVec::new(),
             route: Mutable::new(Route::default()),
+            sort: Mutable::new(parse_url(&routing::url().lock_ref()).sort),
+            tag: Mutable::new(parse_url(&routing::url().lock_ref()).tag),
+            query: Mutable::new(parse_url(&routing::url().lock_ref()).query),
+            dragging: Mutable::new(None),
         })
     }
 
@@ -86,6 +131,52 @@ impl App {
         self.route.signal()
     }
 
+    pub fn tag(&self) -> impl Signal<Item = Option<String>> {
+        self.tag.signal_cloned()
+    }
+
+    // Counted by todomvc_shared, like in the other implementations
+    fn tag_index(&self) -> impl Signal<Item = TagIndex> {
+        self.todo_list.signal_vec_cloned()
+            .to_signal_map(|todos| TagIndex::new(todos.iter().map(|todo| &todo.tags)))
+    }
+
+    pub fn search(&self) -> impl Signal<Item = Search> {
+        self.query.signal_ref(|query| Search::new(query))
+    }
+
+    // Everything that is in the URL hash
+    fn shared_route(&self) -> impl Signal<Item = todomvc_shared::Route> {
+        map_ref! {
+            let route = self.route.signal(),
+            let sort = self.sort.signal(),
+            let tag = self.tag.signal_cloned(),
+            let query = self.query.signal_cloned() =>
+            todomvc_shared::Route {
+                filter: (*route).into(),
+                sort: *sort,
+                tag: tag.clone(),
+                query: query.clone(),
+                ..Default::default()
+            }
+        }
+    }
+
+    fn set_query(&self, query: String) {
+        let route = todomvc_shared::Route {
+            filter: self.route.get().into(),
+            sort: self.sort.get(),
+            tag: self.tag.get_cloned(),
+            query,
+            ..Default::default()
+        };
+
+        // Typing must not add history entries
+        route.replace_location_hash();
+
+        self.query.set_neq(route.query);
+    }
+
     fn create_new_todo(&self) {
         let mut title = self.new_todo_title.lock_mut();
 
@@ -103,6 +194,56 @@ impl App {
         self.todo_list.lock_mut().retain(|x| **x != *todo);
     }
 
+    /// Move the todo with `id` to the place of `target`, the way `MutableVec::move_from_to` does:
+    /// after `target` when moving down, before it when moving up.
+    pub fn move_todo(&self, id: uuid::Uuid, target: uuid::Uuid) {
+        // The manual order is not visible when the list is sorted
+        if self.sort.get() != SortOrder::Insertion {
+            return;
+        }
+
+        {
+            let mut todos = self.todo_list.lock_mut();
+            let from = todos.iter().position(|todo| todo.id == id);
+            let to = todos.iter().position(|todo| todo.id == target);
+
+            match (from, to) {
+                (Some(from), Some(to)) if from != to => todos.move_from_to(from, to),
+                _ => return,
+            }
+        }
+
+        self.serialize();
+    }
+
+    /// Swap a todo with its closest neighbor that is visible with the current route and search.
+    pub fn move_todo_by_one(&self, todo: &Todo, up: bool) {
+        let route = self.route.get();
+        let tag = self.tag.get_cloned();
+        let search = Search::new(&self.query.lock_ref());
+
+        let target = {
+            let todos = self.todo_list.lock_ref();
+            let index = match todos.iter().position(|x| **x == *todo) {
+                Some(index) => index,
+                None => return,
+            };
+            let visible = |x: &&Arc<Todo>| {
+                route.fits(x.completed.get()) && x.has_tag(tag.as_deref()) && search.matches(&x.title.lock_ref())
+            };
+
+            if up {
+                todos[..index].iter().rev().find(visible).map(|x| x.id)
+            } else {
+                todos[index + 1..].iter().find(visible).map(|x| x.id)
+            }
+        };
+
+        if let Some(target) = target {
+            self.move_todo(todo.id, target);
+        }
+    }
+
     fn remove_all_completed_todos(&self) {
         self.todo_list.lock_mut().retain(|todo| todo.completed.get() == false);
     }
@@ -132,6 +273,27 @@ impl App {
             .len()
     }
 
+    // The todos in the order of the current `SortOrder`, `todo_list` itself is not reordered
+    fn sorted_todos(app: Arc<Self>) -> impl SignalVec<Item = Arc<Todo>> {
+        app.sort.signal().switch_signal_vec(clone!(app => move |sort| {
+            if sort == SortOrder::Insertion {
+                return app.todo_list.signal_vec_cloned().boxed_local();
+            }
+
+            app.todo_list.signal_vec_cloned()
+                // Emit the todo again when a field that the sort depends on changes,
+                // so that `sort_by_cloned` moves it to its new place
+                .map_signal(|todo| map_ref! {
+                    let _title = todo.title.signal_cloned(),
+                    let _completed = todo.completed.signal(),
+                    let _priority = todo.priority.signal() =>
+                    todo.clone()
+                })
+                .sort_by_cloned(move |a, b| sort.compare(a, b))
+                .boxed_local()
+        }))
+    }
+
     fn has_todos(&self) -> impl Signal<Item = bool> {
         self.todo_list.signal_vec_cloned()
             .len()
@@ -166,6 +328,18 @@ impl App {
                         }
                     }))
                 }),
+
+                html!("input" => HtmlInputElement, {
+                    .class("search")
+                    .attr("placeholder", "Search")
+                    .prop_signal("value", app.query.signal_cloned())
+
+                    .with_node!(element => {
+                        .event(clone!(app => move |_: events::Input| {
+                            app.set_query(element.value());
+                        }))
+                    })
+                }),
             ])
         })
     }
@@ -197,17 +371,20 @@ impl App {
 
                 html!("ul", {
                     .class("todo-list")
-                    .children_signal_vec(app.todo_list.signal_vec_cloned()
+                    .children_signal_vec(Self::sorted_todos(app.clone())
                         .map(clone!(app => move |todo| Todo::render(todo, app.clone()))))
                 }),
             ])
         })
     }
 
+    // The links keep the parts of the URL that they don't change, so these are plain
+    // hash links instead of `link!`s with a fixed URL
     fn render_button(app: &App, text: &str, route: Route) -> Dom {
         html!("li", {
             .children(&mut [
-                link!(route.to_url(), {
+                html!("a", {
+                    .attr_signal("href", app.shared_route().map(move |x| x.with_filter(route.into()).to_hash()))
                     .text(text)
                     .class_signal("selected", app.route().map(move |x| x == route))
                 })
@@ -215,6 +392,40 @@ impl App {
         })
     }
 
+    fn render_sort_button(app: &App, sort: SortOrder) -> Dom {
+        html!("li", {
+            .children(&mut [
+                html!("a", {
+                    .attr_signal("href", app.shared_route().map(move |x| x.with_sort(sort).to_hash()))
+                    .text(sort.label())
+                    .class_signal("selected", app.sort.signal().map(move |x| x == sort))
+                })
+            ])
+        })
+    }
+
+    // Clicking the selected tag again shows all tags
+    fn render_tag_button(app: &App, tag: String, count: usize) -> Dom {
+        html!("li", {
+            .children(&mut [
+                html!("a", {
+                    .attr_signal("href", app.shared_route().map(clone!(tag => move |x| {
+                        let selected = x.tag.as_ref() == Some(&tag);
+                        x.with_tag((!selected).then_some(tag.as_str())).to_hash()
+                    })))
+                    .class_signal("selected", app.tag.signal_ref(clone!(tag => move |x| x.as_ref() == Some(&tag))))
+                    .text(&format!("#{}", tag))
+                    .children(&mut [
+                        html!("span", {
+                            .class("count")
+                            .text(&count.to_string())
+                        }),
+                    ])
+                })
+            ])
+        })
+    }
+
     fn render_footer(app: Arc<Self>) -> Dom {
         html!("footer", {
             .class("footer")
@@ -262,6 +473,26 @@ impl App {
 
                     .text("Clear completed")
                 }),
+
+                html!("ul", {
+                    .class("sort-orders")
+                    .children(&mut [
+                        Self::render_sort_button(&app, SortOrder::Insertion),
+                        Self::render_sort_button(&app, SortOrder::Alphabetical),
+                        Self::render_sort_button(&app, SortOrder::Created),
+                        Self::render_sort_button(&app, SortOrder::CompletedLast),
+                        Self::render_sort_button(&app, SortOrder::ActiveFirst),
+                        Self::render_sort_button(&app, SortOrder::Priority),
+                    ])
+                }),
+
+                html!("ul", {
+                    .class("tags")
+                    .children_signal_vec(app.tag_index()
+                        .map(|index| index.iter().map(|(tag, count)| (tag.to_string(), count)).collect::<Vec<_>>())
+                        .to_signal_vec()
+                        .map(clone!(app => move |(tag, count)| Self::render_tag_button(&app, tag, count))))
+                }),
             ])
         })
     }
@@ -272,9 +503,12 @@ impl App {
 
             // Update the Route when the URL changes
             .future(routing::url()
-                .signal_ref(|url| Route::from_url(url))
+                .signal_ref(|url| parse_url(url))
                 .for_each(clone!(app => move |route| {
-                    app.route.set_neq(route);
+                    app.route.set_neq(route.filter.into());
+                    app.sort.set_neq(route.sort);
+                    app.tag.set_neq(route.tag);
+                    app.query.set_neq(route.query);
                     async {}
                 })))
 
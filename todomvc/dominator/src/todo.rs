@@ -1,30 +1,48 @@
+use std::collections::BTreeSet;
 use std::sync::Arc;
 use serde_derive::{Serialize, Deserialize};
 use futures_signals::map_ref;
 use futures_signals::signal::{Signal, SignalExt, Mutable};
-use dominator::{Dom, html, clone, events, with_node};
+use futures_signals::signal_vec::SignalVecExt;
+use dominator::{Dom, EventOptions, html, clone, events, text, with_node};
 use web_sys::HtmlInputElement;
+use todomvc_shared::{Priority, Sortable, Timestamp};
 
 use crate::util::trim;
-use crate::app::{App, Route};
+use crate::app::App;
 
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Todo {
-    id: uuid::Uuid,
-    title: Mutable<String>,
+    pub id: uuid::Uuid,
+    pub title: Mutable<String>,
     pub completed: Mutable<bool>,
 
+    #[serde(default)]
+    created_at: Timestamp,
+
+    #[serde(default)]
+    pub tags: BTreeSet<String>,
+
+    #[serde(default)]
+    pub priority: Mutable<Priority>,
+
     #[serde(skip)]
     editing: Mutable<Option<String>>,
 }
 
 impl Todo {
+    // `#tags` in the title become the tags of the todo
     pub fn new(id: uuid::Uuid, title: String) -> Arc<Self> {
+        let (title, tags) = todomvc_shared::extract_tags(&title);
+
         Arc::new(Self {
             id,
             title: Mutable::new(title),
             completed: Mutable::new(false),
+            created_at: todomvc_shared::now(),
+            tags,
+            priority: Mutable::new(Priority::None),
             editing: Mutable::new(None),
         })
     }
@@ -34,23 +52,33 @@ impl Todo {
         app.serialize();
     }
 
+    fn cycle_priority(&self, app: &App) {
+        self.priority.replace_with(|priority| priority.next());
+        app.serialize();
+    }
+
     fn remove(&self, app: &App) {
         app.remove_todo(&self);
         app.serialize();
     }
 
-    fn is_visible(&self, app: &App) -> impl Signal<Item = bool> {
+    fn is_visible(self: &Arc<Self>, app: &App) -> impl Signal<Item = bool> {
+        let todo = self.clone();
+
         (map_ref! {
             let route = app.route(),
-            let completed = self.completed.signal() =>
-            match *route {
-                Route::Active => !completed,
-                Route::Completed => *completed,
-                Route::All => true,
-            }
+            let tag = app.tag(),
+            let completed = self.completed.signal(),
+            let search = app.search(),
+            let title = self.title.signal_cloned() =>
+            route.fits(*completed) && todo.has_tag(tag.as_deref()) && search.matches(title)
         }).dedupe()
     }
 
+    pub fn has_tag(&self, tag: Option<&str>) -> bool {
+        tag.is_none_or(|tag| self.tags.contains(tag))
+    }
+
     fn is_editing(&self) -> impl Signal<Item = bool> {
         self.editing.signal_ref(|x| x.is_some()).dedupe()
     }
@@ -76,9 +104,52 @@ impl Todo {
         html!("li", {
             .class_signal("editing", todo.is_editing())
             .class_signal("completed", todo.completed.signal())
+            .class_signal("priority-low", todo.priority.signal_ref(|x| *x == Priority::Low))
+            .class_signal("priority-medium", todo.priority.signal_ref(|x| *x == Priority::Medium))
+            .class_signal("priority-high", todo.priority.signal_ref(|x| *x == Priority::High))
+            .class_signal("dragging", app.dragging.signal_ref(clone!(todo => move |x| *x == Some(todo.id))))
 
             .visible_signal(todo.is_visible(&app))
 
+            .attr("draggable", "true")
+
+            .event(clone!(todo, app => move |event: events::DragStart| {
+                // Firefox does not start dragging without any data
+                if let Some(data) = event.data_transfer() {
+                    data.set_data("text/plain", &todo.id.to_string()).unwrap();
+                }
+                app.dragging.set_neq(Some(todo.id));
+            }))
+
+            .event(clone!(app => move |_: events::DragEnd| {
+                app.dragging.set_neq(None);
+            }))
+
+            // The default action of dragover is to refuse the drop
+            .event_with_options(&EventOptions::preventable(), |event: events::DragOver| {
+                event.prevent_default();
+            })
+
+            .event_with_options(&EventOptions::preventable(), clone!(todo, app => move |event: events::Drop| {
+                event.prevent_default();
+
+                if let Some(id) = app.dragging.replace(None) {
+                    app.move_todo(id, todo.id);
+                }
+            }))
+
+            .event(clone!(todo, app => move |event: events::KeyDown| {
+                if !event.alt_key() || todo.editing.lock_ref().is_some() {
+                    return;
+                }
+
+                match event.key().as_str() {
+                    "ArrowUp" => app.move_todo_by_one(&todo, true),
+                    "ArrowDown" => app.move_todo_by_one(&todo, false),
+                    _ => {}
+                }
+            }))
+
             .children(&mut [
                 html!("div", {
                     .class("view")
@@ -100,7 +171,34 @@ impl Todo {
                                 todo.editing.set_neq(Some(todo.title.get_cloned()));
                             }))
 
-                            .text_signal(todo.title.signal_cloned())
+                            // Matches of the search are wrapped in spans, the rest are text nodes
+                            .children_signal_vec(map_ref! {
+                                let title = todo.title.signal_cloned(),
+                                let search = app.search() =>
+                                search.split_matches(title)
+                                    .into_iter()
+                                    .map(|segment| (segment.text.to_string(), segment.matched))
+                                    .collect::<Vec<_>>()
+                            }.to_signal_vec().map(|(segment, matched)| {
+                                if matched {
+                                    html!("span", {
+                                        .class("highlight")
+                                        .text(&segment)
+                                    })
+                                } else {
+                                    text(&segment)
+                                }
+                            }))
+                        }),
+
+                        // A button, so that it can also be cycled with Enter or Space
+                        html!("button", {
+                            .class("priority")
+                            .attr_signal("title", todo.priority.signal_ref(|x| format!("Priority: {}", x.label())))
+                            .text_signal(todo.priority.signal_ref(|x| x.marker()))
+                            .event(clone!(todo, app => move |_: events::Click| {
+                                todo.cycle_priority(&app);
+                            }))
                         }),
 
                         html!("button", {
@@ -150,6 +248,24 @@ impl Todo {
     }
 }
 
+impl Sortable for Todo {
+    fn with_description<R>(&self, f: impl FnOnce(&str) -> R) -> R {
+        f(&self.title.lock_ref())
+    }
+
+    fn created_at(&self) -> Timestamp {
+        self.created_at
+    }
+
+    fn is_completed(&self) -> bool {
+        self.completed.get()
+    }
+
+    fn priority(&self) -> Priority {
+        self.priority.get()
+    }
+}
+
 impl PartialEq<Todo> for Todo {
     fn eq(&self, other: &Self) -> bool {
         self.id == other.id
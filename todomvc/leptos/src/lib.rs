@@ -1,5 +1,6 @@
 use leptos::{web_sys::HtmlInputElement, *};
 use storage::TodoSerialized;
+use todomvc_shared::{Filter, Priority, Route, SortOrder, Sortable, Timestamp};
 use uuid::Uuid;
 
 mod storage;
@@ -45,6 +46,27 @@ impl Todos {
         self.0.retain(|todo| todo.id != id);
     }
 
+    // Move the todo with `id` to the place of `target`: after `target` when moving down,
+    // before it when moving up
+    pub fn move_todo(&mut self, id: Uuid, target: Uuid) {
+        let from = self.0.iter().position(|todo| todo.id == id);
+        let to = self.0.iter().position(|todo| todo.id == target);
+        if let (Some(from), Some(to)) = (from, to) {
+            let todo = self.0.remove(from);
+            self.0.insert(to, todo);
+        }
+    }
+
+    // The closest todo before (or after) `id` that is visible in the given mode
+    pub fn visible_neighbor(&self, id: Uuid, mode: Mode, up: bool) -> Option<Uuid> {
+        let index = self.0.iter().position(|todo| todo.id == id)?;
+        if up {
+            self.0[..index].iter().rev().find(|todo| mode.fits(todo)).map(|todo| todo.id)
+        } else {
+            self.0[index + 1..].iter().find(|todo| mode.fits(todo)).map(|todo| todo.id)
+        }
+    }
+
     pub fn remaining(&self) -> usize {
         // `todo.completed` is a signal, so we call .get() to access its value
         self.0.iter().filter(|todo| !todo.completed.get()).count()
@@ -84,14 +106,23 @@ pub struct Todo {
     pub id: Uuid,
     pub title: RwSignal<String>,
     pub completed: RwSignal<bool>,
+    pub created_at: Timestamp,
+    pub priority: RwSignal<Priority>,
 }
 
 impl Todo {
     pub fn new(cx: Scope, id: Uuid, title: String) -> Self {
-        Self::new_with_completed(cx, id, title, false)
+        Self::new_with_completed(cx, id, title, false, todomvc_shared::now(), Priority::None)
     }
 
-    pub fn new_with_completed(cx: Scope, id: Uuid, title: String, completed: bool) -> Self {
+    pub fn new_with_completed(
+        cx: Scope,
+        id: Uuid,
+        title: String,
+        completed: bool,
+        created_at: Timestamp,
+        priority: Priority,
+    ) -> Self {
         // RwSignal combines the getter and setter in one struct, rather than separating
         // the getter from the setter. This makes it more convenient in some cases, such
         // as when we're putting the signals into a struct and passing it around. There's
@@ -99,10 +130,13 @@ impl Todo {
         // everywhere.
         let title = create_rw_signal(cx, title);
         let completed = create_rw_signal(cx, completed);
+        let priority = create_rw_signal(cx, priority);
         Self {
             id,
             title,
             completed,
+            created_at,
+            priority,
         }
     }
 
@@ -113,6 +147,25 @@ impl Todo {
     }
 }
 
+// Lets todomvc_shared sort our todos exactly like the other implementations sort theirs
+impl Sortable for Todo {
+    fn with_description<R>(&self, f: impl FnOnce(&str) -> R) -> R {
+        self.title.with(|title| f(title))
+    }
+
+    fn created_at(&self) -> Timestamp {
+        self.created_at
+    }
+
+    fn is_completed(&self) -> bool {
+        self.completed.get()
+    }
+
+    fn priority(&self) -> Priority {
+        self.priority.get()
+    }
+}
+
 const ESCAPE_KEY: u32 = 27;
 const ENTER_KEY: u32 = 13;
 
@@ -128,11 +181,30 @@ pub fn TodoMVC(cx: Scope) -> Element {
     // segregation.)
     provide_context(cx, set_todos);
 
-    // Handle the three filter modes: All, Active, and Completed
-    let (mode, set_mode) = create_signal(cx, Mode::All);
+    // Handle the three filter modes: All, Active, and Completed, and the sort order.
+    // The hash is parsed by todomvc_shared, so it understands `#/active?sort=alpha`
+    let initial_route = location_hash()
+        .map(|hash| Route::parse(&hash))
+        .unwrap_or_default();
+    let (mode, set_mode) = create_signal(cx, Mode::from(initial_route.filter));
+    let (sort, set_sort) = create_signal(cx, initial_route.sort);
+    // The whole route, so that links carry over the parts this app does not show, like the query
+    let (route, set_route) = create_signal(cx, initial_route);
+    // <Todo/> needs the mode to skip hidden todos when reordering with the keyboard,
+    // and the sort to know whether the manual order is visible at all
+    provide_context(cx, mode);
+    provide_context(cx, sort);
+
+    // The todo that is being dragged, shared by all <Todo/>s
+    let dragging = create_rw_signal(cx, None::<Uuid>);
+    provide_context(cx, dragging);
     window_event_listener("hashchange", move |_| {
-        let new_mode = location_hash().map(|hash| route(&hash)).unwrap_or_default();
-        set_mode.set(new_mode);
+        let new_route = location_hash()
+            .map(|hash| Route::parse(&hash))
+            .unwrap_or_default();
+        set_mode.set(new_route.filter.into());
+        set_sort.set(new_route.sort);
+        set_route.set(new_route);
     });
 
     // Callback to add a todo on pressing the `Enter` key, if the field isn't empty
@@ -151,24 +223,36 @@ pub fn TodoMVC(cx: Scope) -> Element {
         }
     };
 
-    // A derived signal that filters the list of the todos depending on the filter mode
+    // A derived signal that filters the list of the todos depending on the filter mode,
+    // then sorts it; the order of `todos` itself is not changed
     // This doesn't need to be a `Memo`, because we're only reading it in one place
     let filtered_todos = move || {
-        todos.with(|todos| match mode.get() {
-            Mode::All => todos.0.to_vec(),
-            Mode::Active => todos
+        let mut filtered = todos.with(|todos| {
+            let mode = mode.get();
+            todos
                 .0
                 .iter()
-                .filter(|todo| !todo.completed.get())
+                .filter(|todo| mode.fits(todo))
                 .cloned()
-                .collect(),
-            Mode::Completed => todos
-                .0
-                .iter()
-                .filter(|todo| todo.completed.get())
-                .cloned()
-                .collect(),
-        })
+                .collect::<Vec<_>>()
+        });
+        sort.get().sort(&mut filtered);
+        filtered
+    };
+
+    // Links keep the rest of the route: filter links keep the sort and the query, and vice versa
+    let filter_href = move |filter: Filter| Route { filter, ..route.get() }.to_hash();
+    let sort_link = move |order: SortOrder| {
+        view! { cx,
+            <li>
+                <a
+                    href=move || Route { sort: order, ..route.get() }.to_hash()
+                    class:selected={move || sort.get() == order}
+                >
+                    {order.label()}
+                </a>
+            </li>
+        }
     };
 
     // Serialization
@@ -232,9 +316,9 @@ pub fn TodoMVC(cx: Scope) -> Element {
                         " left"
                     </span>
                     <ul class="filters">
-                        <li><a href="#/" class="selected" class:selected={move || mode.get() == Mode::All}>"All"</a></li>
-                        <li><a href="#/active" class:selected={move || mode.get() == Mode::Active}>"Active"</a></li>
-                        <li><a href="#/completed" class:selected={move || mode.get() == Mode::Completed}>"Completed"</a></li>
+                        <li><a href=move || filter_href(Filter::All) class="selected" class:selected={move || mode.get() == Mode::All}>"All"</a></li>
+                        <li><a href=move || filter_href(Filter::Active) class:selected={move || mode.get() == Mode::Active}>"Active"</a></li>
+                        <li><a href=move || filter_href(Filter::Completed) class:selected={move || mode.get() == Mode::Completed}>"Completed"</a></li>
                     </ul>
                     <button
                         class="clear-completed hidden"
@@ -243,6 +327,14 @@ pub fn TodoMVC(cx: Scope) -> Element {
                     >
                         "Clear completed"
                     </button>
+                    <ul class="sort-orders">
+                        {sort_link(SortOrder::Insertion)}
+                        {sort_link(SortOrder::Alphabetical)}
+                        {sort_link(SortOrder::Created)}
+                        {sort_link(SortOrder::CompletedLast)}
+                        {sort_link(SortOrder::ActiveFirst)}
+                        {sort_link(SortOrder::Priority)}
+                    </ul>
                 </footer>
             </section>
             <footer class="info">
@@ -258,6 +350,12 @@ pub fn TodoMVC(cx: Scope) -> Element {
 pub fn Todo(cx: Scope, todo: Todo) -> Element {
     let (editing, set_editing) = create_signal(cx, false);
     let set_todos = use_context::<WriteSignal<Todos>>(cx).unwrap();
+    let mode = use_context::<ReadSignal<Mode>>(cx).unwrap();
+    let sort = use_context::<ReadSignal<SortOrder>>(cx).unwrap();
+    let dragging = use_context::<RwSignal<Option<Uuid>>>(cx).unwrap();
+    let id = todo.id;
+    // Signals are `Copy`, a closure that calls a method of `todo` would move it
+    let priority = todo.priority;
 
     // this will be filled by _ref=input below
     let input: Element;
@@ -277,6 +375,48 @@ pub fn Todo(cx: Scope, todo: Todo) -> Element {
             class="todo"
             class:editing={editing.get()}
             class:completed={move || todo.completed.get()}
+            class:dragging={move || dragging.get() == Some(id)}
+            class:priority-low={move || todo.priority.get() == Priority::Low}
+            class:priority-medium={move || todo.priority.get() == Priority::Medium}
+            class:priority-high={move || todo.priority.get() == Priority::High}
+            draggable="true"
+            on:dragstart=move |ev| {
+                // Firefox does not start dragging without any data
+                if let Some(data) = ev.unchecked_ref::<web_sys::DragEvent>().data_transfer() {
+                    data.set_data("text/plain", &id.to_string()).unwrap();
+                }
+                dragging.set(Some(id));
+            }
+            // the default action of dragover is to refuse the drop
+            on:dragover=move |ev| ev.prevent_default()
+            on:drop=move |ev| {
+                ev.prevent_default();
+                if let Some(dragged) = dragging.get() {
+                    dragging.set(None);
+                    // the manual order is only visible when the list is not sorted
+                    if sort.get() == SortOrder::Insertion {
+                        set_todos.update(|t| t.move_todo(dragged, id));
+                    }
+                }
+            }
+            on:dragend=move |_| dragging.set(None)
+            on:keydown=move |ev| {
+                let ev = ev.unchecked_ref::<web_sys::KeyboardEvent>();
+                if !ev.alt_key() || editing.get() || sort.get() != SortOrder::Insertion {
+                    return;
+                }
+                let up = match ev.key().as_str() {
+                    "ArrowUp" => true,
+                    "ArrowDown" => false,
+                    _ => return,
+                };
+                let mode = mode.get();
+                set_todos.update(|t| {
+                    if let Some(target) = t.visible_neighbor(id, mode, up) {
+                        t.move_todo(id, target);
+                    }
+                });
+            }
         >
             <div class="view">
                 <input
@@ -300,6 +440,14 @@ pub fn Todo(cx: Scope, todo: Todo) -> Element {
                 }>
                     {move || todo.title.get()}
                 </label>
+                // a button, so that it can also be cycled with Enter or Space
+                <button
+                    class="priority"
+                    title=move || format!("Priority: {}", todo.priority.get().label())
+                    on:click=move |_| priority.update(|priority| *priority = priority.next())
+                >
+                    {move || todo.priority.get().marker()}
+                </button>
                 <button class="destroy" on:click=move |_| set_todos.update(|t| t.remove(todo.id))/>
             </div>
             {move || editing.get().then(|| view! { cx,
@@ -330,16 +478,44 @@ pub enum Mode {
     All,
 }
 
+impl Mode {
+    pub fn fits(&self, todo: &Todo) -> bool {
+        match self {
+            Mode::All => true,
+            Mode::Active => !todo.completed.get(),
+            Mode::Completed => todo.completed.get(),
+        }
+    }
+}
+
 impl Default for Mode {
     fn default() -> Self {
         Mode::All
     }
 }
 
-pub fn route(hash: &str) -> Mode {
-    match hash {
-        "/active" => Mode::Active,
-        "/completed" => Mode::Completed,
-        _ => Mode::All,
+impl From<Filter> for Mode {
+    fn from(filter: Filter) -> Self {
+        match filter {
+            Filter::All => Mode::All,
+            Filter::Active => Mode::Active,
+            Filter::Completed => Mode::Completed,
+            // This implementation has no due dates
+            Filter::Overdue | Filter::DueToday | Filter::Upcoming => Mode::All,
+            // Nor dependencies, so every active todo can be done next
+            Filter::Next => Mode::Active,
+            // Nor a board, the list shows every todo
+            Filter::Board | Filter::Archive => Mode::All,
+        }
+    }
+}
+
+impl From<Mode> for Filter {
+    fn from(mode: Mode) -> Self {
+        match mode {
+            Mode::All => Filter::All,
+            Mode::Active => Filter::Active,
+            Mode::Completed => Filter::Completed,
+        }
     }
 }
@@ -1,6 +1,7 @@
 use crate::Todo;
 use leptos::Scope;
 use serde::{Deserialize, Serialize};
+use todomvc_shared::{Priority, Timestamp};
 use uuid::Uuid;
 
 #[derive(Serialize, Deserialize)]
@@ -8,11 +9,15 @@ pub struct TodoSerialized {
     pub id: Uuid,
     pub title: String,
     pub completed: bool,
+    #[serde(default)]
+    pub created_at: Timestamp,
+    #[serde(default)]
+    pub priority: Priority,
 }
 
 impl TodoSerialized {
     pub fn into_todo(self, cx: Scope) -> Todo {
-        Todo::new_with_completed(cx, self.id, self.title, self.completed)
+        Todo::new_with_completed(cx, self.id, self.title, self.completed, self.created_at, self.priority)
     }
 }
 
@@ -22,6 +27,8 @@ impl From<&Todo> for TodoSerialized {
             id: todo.id,
             title: todo.title.get(),
             completed: todo.completed.get(),
+            created_at: todo.created_at,
+            priority: todo.priority.get(),
         }
     }
 }
@@ -1,4 +1,5 @@
 use spair::prelude::*;
+use todomvc_shared::{ArchivedEntry, TodoEntry};
 
 pub const KEY: &str = "todos-spair-keyed";
 
@@ -9,9 +10,41 @@ impl<'a> spair::Keyed for super::RenderEntry<'a> {
     }
 }
 
+impl<'a> spair::Keyed for super::CardView<'a> {
+    type Key = uuid::Uuid;
+    fn key(&self) -> &Self::Key {
+        &self.0.id
+    }
+}
+
+impl<'a> spair::Keyed for super::ArchivedView<'a> {
+    type Key = uuid::Uuid;
+    fn key(&self) -> &Self::Key {
+        &self.0.entry.id
+    }
+}
+
 pub fn render_list(e: spair::Element<super::App>) {
     let state = e.state();
     e.static_attributes().class("todo-list").keyed_list_clone(
-        state.data.get_filtered_entries().map(super::RenderEntry),
+        state.data.get_visible_entries().into_iter().map(super::RenderEntry),
+    );
+}
+
+pub fn render_subtasks(e: spair::Element<super::App>, entries: Vec<&TodoEntry>) {
+    e.static_attributes().class("subtasks").keyed_list_clone(
+        entries.into_iter().map(super::RenderEntry),
+    );
+}
+
+pub fn render_cards(e: spair::Element<super::App>, entries: Vec<&TodoEntry>) {
+    e.static_attributes().class("cards").keyed_list_clone(
+        entries.into_iter().map(super::CardView),
+    );
+}
+
+pub fn render_archived(e: spair::Element<super::App>, entries: &[ArchivedEntry]) {
+    e.static_attributes().class("archive-list").keyed_list_clone(
+        entries.iter().map(super::ArchivedView),
     );
 }
@@ -1,6 +1,11 @@
 use spair::prelude::*;
+use strum::IntoEnumIterator;
+use wasm_bindgen::JsCast;
 
-use todomvc_shared::{Todos, Filter, TodoEntry};
+use todomvc_shared::{
+    ArchivedEntry, Column, Date, Filter, Priority, Route, Segment, SortOrder, Status, TodoEntry, TodoList,
+    TodoLists, Todos,
+};
 
 #[cfg(feature = "keyed-app")]
 mod keyed;
@@ -15,17 +20,63 @@ use non_keyed::*;
 
 
 pub struct App {
-    data: Todos,
+    data: TodoLists,
+    renaming_list: Option<uuid::Uuid>,
     editing_id: Option<uuid::Uuid>,
     new_description: String,
+    show_times: bool,
+    dragging_id: Option<uuid::Uuid>,
+    adding_subtask_to: Option<uuid::Uuid>,
+    dependency_error: Option<String>,
 }
 
 impl App {
     fn save_data(&self) {
-        self.data.save_todos(KEY).unwrap_throw();
+        self.data.save().unwrap_throw();
     }
-    fn set_filter(&mut self, filter: Filter) {
-        self.data.filter = filter;
+
+    // Show the current list in the address bar after it changed without a
+    // route change
+    fn show_current_list(&mut self) {
+        self.editing_id = None;
+        self.adding_subtask_to = None;
+        self.data.route().replace_location_hash();
+        self.save_data();
+    }
+
+    fn create_list(&mut self, name: String) {
+        if self.data.create(&name).is_some() {
+            self.show_current_list();
+        }
+    }
+
+    fn start_renaming_list(&mut self, id: uuid::Uuid) {
+        self.renaming_list = Some(id);
+    }
+
+    fn end_renaming_list(&mut self, name: Option<String>) {
+        if let (Some(id), Some(name)) = (self.renaming_list.take(), name) {
+            if self.data.rename(&id, &name) {
+                self.save_data();
+            }
+        }
+    }
+
+    fn delete_list(&mut self, id: uuid::Uuid) {
+        if self.data.delete(&id) {
+            self.show_current_list();
+        }
+    }
+
+    fn set_route(&mut self, route: Route) {
+        self.data.set_route(route);
+
+        self.save_data();
+    }
+
+    fn set_query(&mut self, query: String) {
+        self.data.query = query;
+        self.data.route().replace_location_hash();
 
         self.save_data();
     }
@@ -36,12 +87,108 @@ impl App {
         self.save_data();
     }
 
-    fn toggle_completion(&mut self, id: uuid::Uuid) {
+    // Returns false if the entry is blocked
+    fn toggle_completion(&mut self, id: uuid::Uuid) -> bool {
+        let result = self.data.toggle_completion(&id);
+        self.dependency_error = result.as_ref().err().map(ToString::to_string);
+
+        self.save_data();
+        result.is_ok()
+    }
+
+    fn add_blocker(&mut self, id: uuid::Uuid, blocker_id: uuid::Uuid) {
+        match self.data.add_dependency(&id, &blocker_id) {
+            Ok(()) => {
+                self.dependency_error = None;
+                self.save_data();
+            }
+            Err(error) => self.dependency_error = Some(error.to_string()),
+        }
+    }
+
+    fn remove_blocker(&mut self, id: uuid::Uuid, blocker_id: uuid::Uuid) {
+        if self.data.remove_dependency(&id, &blocker_id) {
+            self.save_data();
+        }
+    }
+
+    // Cards are not reordered, so any of them can be dragged
+    fn start_dragging_card(&mut self, id: uuid::Uuid) {
+        self.dragging_id = Some(id);
+    }
+
+    fn drop_on_column(&mut self, status: Status) {
+        let id = match self.dragging_id.take() {
+            Some(id) => id,
+            None => return,
+        };
+        let result = self.data.set_status(&id, status);
+        self.dependency_error = result.err().map(|error| error.to_string());
+
+        self.save_data();
+    }
+
+    fn add_column(&mut self, name: String) {
+        if self.data.add_column(&name) {
+            self.save_data();
+        }
+    }
+
+    fn remove_column(&mut self, name: &str) {
+        if self.data.remove_column(name) {
+            self.save_data();
+        }
+    }
+
+    fn restore(&mut self, id: uuid::Uuid) {
+        if self.data.restore(&id) {
+            self.save_data();
+        }
+    }
+
+    fn delete_archived(&mut self, id: uuid::Uuid) {
+        if self.data.delete_archived(&id) {
+            self.save_data();
+        }
+    }
+
+    fn set_retention_days(&mut self, days: Option<u32>) {
+        self.data.set_retention_days(days);
+        self.save_data();
+    }
+
+    fn dismiss_dependency_error(&mut self) {
+        self.dependency_error = None;
+    }
+
+    fn toggle_collapsed(&mut self, id: uuid::Uuid) {
+        self.data.toggle_collapsed(&id);
+
+        self.save_data();
+    }
+
+    fn start_adding_subtask(&mut self, id: uuid::Uuid) {
+        self.adding_subtask_to = Some(id);
+    }
+
+    fn end_adding_subtask(&mut self, description: Option<String>) {
+        let parent_id = match self.adding_subtask_to.take() {
+            Some(id) => id,
+            None => return,
+        };
+        if let Some(description) = description {
+            if self.data.add_subtask_parsed(&parent_id, description) {
+                self.save_data();
+            }
+        }
+    }
+
+    fn cycle_priority(&mut self, id: uuid::Uuid) {
         if let Some(e) = self.data.get_entry_by_id_mut(&id) {
-            e.toggle_completion();
+            e.cycle_priority();
         }
 
-        self.save_data()
+        self.save_data();
     }
 
     fn remove_by_id(&mut self, id: uuid::Uuid) {
@@ -61,7 +208,7 @@ impl App {
     }
 
     fn create_new_todo(&mut self) {
-        self.data.new_entry(self.new_description.clone());
+        self.data.new_entry_parsed(self.new_description.clone());
         self.new_description.clear();
 
         self.save_data();
@@ -79,11 +226,9 @@ impl App {
         match new_description {
             Some(new_description) => {
                 self.data
-                    .entries
-                    .iter_mut()
-                    .find(|item| item.id == id)
+                    .get_entry_by_id_mut(&id)
                     .expect_throw("Why editing item with an invalid id?")
-                    .description = new_description;
+                    .set_description(new_description);
                 self.save_data();
             }
             None => self.remove_by_id(id),
@@ -91,9 +236,75 @@ impl App {
         self.editing_id = None;
     }
 
+    // Keep editing: the focus moved from the description to the due date
+    fn save_description(&mut self, new_description: Option<String>) {
+        let (id, new_description) = match (self.editing_id, new_description) {
+            (Some(id), Some(new_description)) => (id, new_description),
+            _ => return,
+        };
+        if let Some(e) = self.data.get_entry_by_id_mut(&id) {
+            e.set_description(new_description);
+            self.save_data();
+        }
+    }
+
+    fn set_due(&mut self, id: uuid::Uuid, due: Option<Date>) {
+        if let Some(e) = self.data.get_entry_by_id_mut(&id) {
+            e.set_due(due);
+        }
+
+        self.save_data();
+    }
+
     fn cancel_editing(&mut self) {
         self.editing_id = None;
     }
+
+    // Only top-level entries can be moved. The event bubbles up from a
+    // subtask to its top-level ancestor, which is the one dragged.
+    fn start_dragging(&mut self, id: uuid::Uuid) {
+        if self.data.filtered_position(&id).is_some() {
+            self.dragging_id = Some(id);
+        }
+    }
+
+    fn stop_dragging(&mut self) {
+        self.dragging_id = None;
+    }
+
+    fn drop_on(&mut self, target_id: uuid::Uuid) {
+        // Dropped on a subtask, wait for the event to reach its top-level ancestor
+        let position = match self.data.filtered_position(&target_id) {
+            Some(position) => position,
+            None => return,
+        };
+        let id = match self.dragging_id.take() {
+            Some(id) if id != target_id => id,
+            _ => return,
+        };
+        if self.data.move_entry(&id, position) {
+            self.save_data();
+        }
+    }
+
+    fn move_by(&mut self, id: uuid::Uuid, up: bool) {
+        let position = match self.data.filtered_position(&id) {
+            Some(position) => position,
+            None => return,
+        };
+        let new_position = match up {
+            true if position == 0 => return,
+            true => position - 1,
+            false => position + 1,
+        };
+        if self.data.move_entry(&id, new_position) {
+            self.save_data();
+        }
+    }
+
+    fn toggle_show_times(&mut self) {
+        self.show_times = !self.show_times;
+    }
 }
 
 impl spair::Component for App {
@@ -102,6 +313,7 @@ impl spair::Component for App {
         e.section(|s| {
             s.static_attributes()
                 .class("todoapp")
+                .rupdate(ListSwitcher)
                 .rupdate(Header)
                 .rupdate(Main)
                 .rupdate(Footer);
@@ -110,11 +322,108 @@ impl spair::Component for App {
     }
 }
 
+struct ListSwitcher;
+impl spair::Render<App> for ListSwitcher {
+    fn render(self, nodes: spair::Nodes<App>) {
+        let comp = nodes.comp();
+        let state = nodes.state();
+        let route = state.data.route();
+        let current_id = state.data.current().id;
+        let deletable = state.data.lists.len() > 1;
+        nodes.nav(|n| {
+            n.static_attributes()
+                .class("lists")
+                .ul(|u| {
+                    u.list_clone(state.data.lists.iter().map(|list| ListTab {
+                        route: state.data.route_to(&list.id, &route),
+                        list,
+                        current: list.id == current_id,
+                        renaming: state.renaming_list == Some(list.id),
+                        deletable,
+                    }));
+                })
+                .input(|i| {
+                    i.static_attributes()
+                        .class("new-list")
+                        .placeholder("New list")
+                        .on_key_down(comp.handler_arg_mut(|state, arg: spair::KeyboardEvent| {
+                            if arg.raw().code().as_str() == "Enter" {
+                                let input = arg.current_target_as::<spair::web_sys::HtmlInputElement>();
+                                if let Some(name) = get_value(input.clone()) {
+                                    state.create_list(name);
+                                }
+                                if let Some(input) = input {
+                                    input.set_value("");
+                                }
+                            }
+                        }));
+                });
+        });
+    }
+}
+
+struct ListTab<'a> {
+    list: &'a TodoList<Todos>,
+    route: Route,
+    current: bool,
+    renaming: bool,
+    deletable: bool,
+}
+
+impl<'a> spair::ElementRender<App> for ListTab<'a> {
+    const ELEMENT_TAG: &'static str = "li";
+    fn render(self, li: spair::Element<App>) {
+        let comp = li.comp();
+        let id = self.list.id;
+        li.class_if(self.current, "selected").match_if(|mi| match self.renaming {
+            true => spair::set_arm!(mi)
+                .input(|i| {
+                    i.focus(true)
+                        .value(&self.list.name)
+                        .static_attributes()
+                        .class("rename-list")
+                        .on_blur(comp.handler_arg_mut(|state, arg: spair::FocusEvent| {
+                            state.end_renaming_list(get_value(arg.current_target_as()));
+                        }))
+                        .on_key_down(comp.handler_arg_mut(|state, arg: spair::KeyboardEvent| {
+                            match arg.raw().code().as_str() {
+                                "Escape" => state.end_renaming_list(None),
+                                "Enter" => state.end_renaming_list(get_value(arg.current_target_as())),
+                                _ => {}
+                            }
+                        }));
+                })
+                .done(),
+            false => spair::set_arm!(mi)
+                .a(|a| {
+                    a.href(&router::Routes(self.route))
+                        .title("Double-click to rename")
+                        .on_double_click(comp.handler_mut(move |state| state.start_renaming_list(id)))
+                        .rupdate(&self.list.name)
+                        .span(|s| {
+                            s.static_attributes().class("count").rupdate(self.list.active_count());
+                        });
+                })
+                .button(|b| {
+                    b.class_if(!self.deletable, "hidden")
+                        .on_click(comp.handler_mut(move |state| state.delete_list(id)))
+                        .static_attributes()
+                        .class("delete-list")
+                        .title("Delete this list and its todos")
+                        .static_nodes()
+                        .rstatic("×");
+                })
+                .done(),
+        });
+    }
+}
+
 struct Header;
 impl spair::Render<App> for Header {
     fn render(self, nodes: spair::Nodes<App>) {
         let comp = nodes.comp();
         let state = nodes.state();
+        let query_error = state.data.parse_query().err();
         nodes.header(|h| {
             h.static_attributes()
                 .class("header")
@@ -139,6 +448,25 @@ impl spair::Render<App> for Header {
                                 state.create_new_todo();
                             }
                         }));
+                })
+                .input(|i| {
+                    i.value(&state.data.query)
+                        .class_if(query_error.is_some(), "invalid")
+                        .static_attributes()
+                        .class("search")
+                        .placeholder("Search, e.g. is:active tag:work \"buy milk\"")
+                        .on_input(comp.handler_arg_mut(|state, arg: spair::InputEvent| {
+                            if let Some(input) = arg.current_target_as_input_element() {
+                                state.set_query(input.value());
+                            }
+                        }));
+                })
+                .p(|p| {
+                    let message = query_error.as_ref().map(ToString::to_string).unwrap_or_default();
+                    p.class_if(query_error.is_none(), "hidden")
+                        .static_attributes()
+                        .class("query-error")
+                        .rupdate(message);
                 });
         });
     }
@@ -152,7 +480,9 @@ impl spair::Render<App> for Main {
         let todo_count = state.data.entry_count();
         let all_completed = state.data.is_all_completed();
         nodes.section(|s| {
-            s.class_if(todo_count == 0, "hidden")
+            s.class_if(todo_count == 0 && state.data.archive.is_empty(), "hidden")
+                // Dropping outside of any item cancels the drag
+                .on_mouse_up(comp.handler_mut(App::stop_dragging))
                 .static_attributes()
                 .class("main")
                 .input(move |i| {
@@ -171,11 +501,199 @@ impl spair::Render<App> for Main {
                         .rstatic("Mark all as complete");
                 })
                 .update_nodes()
-                .ul(render_list);
+                .p(|p| {
+                    let message = state.dependency_error.as_deref().unwrap_or_default();
+                    p.class_if(state.dependency_error.is_none(), "hidden")
+                        .on_click(comp.handler_mut(App::dismiss_dependency_error))
+                        .static_attributes()
+                        .class("dependency-error")
+                        .title("Click to dismiss")
+                        .update_nodes()
+                        .rupdate(message);
+                })
+                .match_if(|mi| match state.data.filter {
+                    Filter::Board => spair::set_arm!(mi).div(render_board).done(),
+                    Filter::Archive => spair::set_arm!(mi).div(render_archive).done(),
+                    _ => spair::set_arm!(mi).ul(render_list).done(),
+                });
         });
     }
 }
 
+fn render_board(d: spair::Element<App>) {
+    let comp = d.comp();
+    let state = d.state();
+    d.static_attributes()
+        .class("board")
+        .div(|d| {
+            d.static_attributes()
+                .class("columns")
+                .list_clone(state.data.board().into_iter().map(ColumnView));
+        })
+        .input(|i| {
+            i.static_attributes()
+                .class("new-column")
+                .placeholder("Add a column")
+                .on_key_down(comp.handler_arg_mut(|state, arg: spair::KeyboardEvent| {
+                    if arg.raw().code().as_str() == "Enter" {
+                        if let Some(input) = arg.current_target_as::<spair::web_sys::HtmlInputElement>() {
+                            state.add_column(input.value());
+                            input.set_value("");
+                        }
+                    }
+                }));
+        });
+}
+
+struct ColumnView<'a>(Column<'a>);
+impl<'a> spair::ElementRender<App> for ColumnView<'a> {
+    const ELEMENT_TAG: &'static str = "section";
+    fn render(self, section: spair::Element<App>) {
+        let comp = section.comp();
+        let Column { status, entries } = self.0;
+        let custom_name = match &status {
+            Status::Custom(name) => Some(name.clone()),
+            _ => None,
+        };
+        let drop_status = status.clone();
+        section
+            .on_mouse_up(comp.handler_mut(move |state| state.drop_on_column(drop_status.clone())))
+            .static_attributes()
+            .class("column")
+            .h2(|h| {
+                h.rupdate(status.label())
+                    .span(|s| {
+                        s.static_attributes().class("count").rupdate(entries.len());
+                    })
+                    .button(|b| {
+                        b.class_if(custom_name.is_none(), "hidden")
+                            .on_click(comp.handler_mut(move |state| {
+                                if let Some(name) = &custom_name {
+                                    state.remove_column(name);
+                                }
+                            }))
+                            .static_attributes()
+                            .class("remove-column")
+                            .title("Remove the column, its todos go back to To do")
+                            .static_nodes()
+                            .rstatic("×");
+                    });
+            })
+            .ul(|u| render_cards(u, entries));
+    }
+}
+
+// Retention periods to choose from, in days, `None` is forever
+const RETENTION_CHOICES: [Option<u32>; 4] = [Some(7), Some(30), Some(90), None];
+
+fn render_archive(d: spair::Element<App>) {
+    let comp = d.comp();
+    let state = d.state();
+    let retention_days = state.data.archive.retention_days;
+    d.static_attributes()
+        .class("archive")
+        .label(|l| {
+            l.static_attributes()
+                .class("retention")
+                .static_nodes()
+                .rstatic("Delete archived todos after ")
+                .update_nodes()
+                .select(|s| {
+                    s.on_change(comp.handler_arg_mut(|state, arg: spair::Event| {
+                        if let Some(select) = arg.current_target_as::<spair::web_sys::HtmlSelectElement>() {
+                            state.set_retention_days(select.value().parse().ok());
+                        }
+                    }))
+                    .list_clone(RETENTION_CHOICES.iter().map(|&days| RetentionOption {
+                        days,
+                        selected: days == retention_days,
+                    }));
+                });
+        })
+        .ul(|u| render_archived(u, &state.data.archive.entries));
+}
+
+struct RetentionOption {
+    days: Option<u32>,
+    selected: bool,
+}
+
+impl spair::ElementRender<App> for RetentionOption {
+    const ELEMENT_TAG: &'static str = "option";
+    fn render(self, option: spair::Element<App>) {
+        let (value, label) = match self.days {
+            Some(days) => (days.to_string(), format!("{days} days")),
+            None => (String::new(), "never".to_string()),
+        };
+        option.selected(self.selected).value(&value).rupdate(label.as_str());
+    }
+}
+
+struct ArchivedView<'a>(&'a ArchivedEntry);
+impl<'a> spair::ElementRender<App> for ArchivedView<'a> {
+    const ELEMENT_TAG: &'static str = "li";
+    fn render(self, li: spair::Element<App>) {
+        let comp = li.comp();
+        let entry = &self.0.entry;
+        let id = entry.id;
+        let archived = format!("archived {}", todomvc_shared::relative_time(self.0.archived_at, todomvc_shared::now()));
+        li.class_if(entry.is_completed(), "completed")
+            .span(|s| {
+                s.static_attributes().class("description").rupdate(&entry.description);
+            })
+            .span(|s| {
+                s.static_attributes().class("archived-at").rupdate(archived.as_str());
+            })
+            .button(|b| {
+                b.on_click(comp.handler_mut(move |state| state.restore(id)))
+                    .static_attributes()
+                    .class("restore")
+                    .title("Put it back in the list")
+                    .static_nodes()
+                    .rstatic("Restore");
+            })
+            .button(|b| {
+                b.on_click(comp.handler_mut(move |state| state.delete_archived(id)))
+                    .static_attributes()
+                    .class("delete")
+                    .title("Delete for good")
+                    .static_nodes()
+                    .rstatic("Delete");
+            });
+    }
+}
+
+struct CardView<'a>(&'a TodoEntry);
+impl<'a> spair::ElementRender<App> for CardView<'a> {
+    const ELEMENT_TAG: &'static str = "li";
+    fn render(self, li: spair::Element<App>) {
+        let comp = li.comp();
+        let state = li.state();
+        let id = self.0.id;
+        let today = Date::today();
+        li.class_if(self.0.is_completed(), "completed")
+            .class_if(self.0.priority == Priority::Low, "priority-low")
+            .class_if(self.0.priority == Priority::Medium, "priority-medium")
+            .class_if(self.0.priority == Priority::High, "priority-high")
+            .class_if(state.data.is_blocked(&id), "blocked")
+            .class_if(state.dragging_id == Some(id), "dragging")
+            .on_mouse_down(comp.handler_mut(move |state| state.start_dragging_card(id)))
+            .static_attributes()
+            .class("card")
+            .span(|s| {
+                s.static_attributes().class("description").rupdate(&self.0.description);
+            })
+            .match_if(|mi| match self.0.due_label(today) {
+                Some(label) => spair::set_arm!(mi)
+                    .span(|s| {
+                        s.static_attributes().class("due").rupdate(label.as_str());
+                    })
+                    .done(),
+                None => spair::set_arm!(mi).done(),
+            });
+    }
+}
+
 struct Footer;
 impl spair::Render<App> for Footer {
     fn render(self, nodes: spair::Nodes<App>) {
@@ -184,8 +702,10 @@ impl spair::Render<App> for Footer {
         let item_count = state.data.entry_count();
         let item_left = item_count - state.data.completed_count();
         let some_completed = item_left < item_count;
+        let route = &state.data.route();
+        let tag_index = state.data.tag_index();
         nodes.footer(|f| {
-            f.class_if(item_count == 0, "hidden")
+            f.class_if(item_count == 0 && state.data.archive.is_empty(), "hidden")
                 .static_attributes()
                 .class("footer")
                 .update_nodes()
@@ -203,16 +723,40 @@ impl spair::Render<App> for Footer {
                     u.static_attributes()
                         .class("filters")
                         .rupdate(FilterView {
-                            current_filter: state.data.filter,
+                            route,
                             filter: Filter::All,
                         })
                         .rupdate(FilterView {
-                            current_filter: state.data.filter,
+                            route,
                             filter: Filter::Active,
                         })
                         .rupdate(FilterView {
-                            current_filter: state.data.filter,
+                            route,
                             filter: Filter::Completed,
+                        })
+                        .rupdate(FilterView {
+                            route,
+                            filter: Filter::Overdue,
+                        })
+                        .rupdate(FilterView {
+                            route,
+                            filter: Filter::DueToday,
+                        })
+                        .rupdate(FilterView {
+                            route,
+                            filter: Filter::Upcoming,
+                        })
+                        .rupdate(FilterView {
+                            route,
+                            filter: Filter::Next,
+                        })
+                        .rupdate(FilterView {
+                            route,
+                            filter: Filter::Board,
+                        })
+                        .rupdate(FilterView {
+                            route,
+                            filter: Filter::Archive,
                         });
                 })
                 .button(|b| {
@@ -221,23 +765,33 @@ impl spair::Render<App> for Footer {
                         .class("clear-completed")
                         .on_click(comp.handler_mut(App::clear_completed))
                         .rstatic("Clear completed");
+                })
+                .ul(|u| {
+                    u.static_attributes()
+                        .class("sort-orders")
+                        .list_clone(SortOrder::iter().map(|sort| SortView { route, sort }));
+                })
+                .ul(|u| {
+                    u.class_if(tag_index.is_empty(), "hidden")
+                        .static_attributes()
+                        .class("tags")
+                        .list_clone(tag_index.iter().map(|(tag, count)| TagView { route, tag, count }));
                 });
         });
     }
 }
 
-struct FilterView {
-    current_filter: Filter,
+struct FilterView<'a> {
+    route: &'a Route,
     filter: Filter,
 }
 
-impl spair::Render<App> for FilterView {
+impl<'a> spair::Render<App> for FilterView<'a> {
     fn render(self, nodes: spair::Nodes<App>) {
         nodes.li(|l| {
             l.a(|a| {
-                a.class_if(self.current_filter == self.filter, "selected")
-                    .static_attributes()
-                    .href(&router::Routes(self.filter))
+                a.class_if(self.route.filter == self.filter, "selected")
+                    .href(&router::Routes(self.route.with_filter(self.filter)))
                     .static_nodes()
                     .rstatic(&self.filter.to_string());
             });
@@ -245,12 +799,66 @@ impl spair::Render<App> for FilterView {
     }
 }
 
+struct SortView<'a> {
+    route: &'a Route,
+    sort: SortOrder,
+}
+
+impl<'a> spair::ElementRender<App> for SortView<'a> {
+    const ELEMENT_TAG: &'static str = "li";
+    fn render(self, li: spair::Element<App>) {
+        li.a(|a| {
+            a.class_if(self.route.sort == self.sort, "selected")
+                .href(&router::Routes(self.route.with_sort(self.sort)))
+                .static_nodes()
+                .rstatic(self.sort.label());
+        });
+    }
+}
+
+struct TagView<'a> {
+    route: &'a Route,
+    tag: &'a str,
+    count: usize,
+}
+
+impl<'a> spair::ElementRender<App> for TagView<'a> {
+    const ELEMENT_TAG: &'static str = "li";
+    fn render(self, li: spair::Element<App>) {
+        let selected = self.route.tag.as_deref() == Some(self.tag);
+        // Clicking the selected tag again shows all tags
+        let target = self.route.with_tag((!selected).then_some(self.tag));
+        li.a(|a| {
+            a.class_if(selected, "selected")
+                .href(&router::Routes(target))
+                .rupdate("#")
+                .rupdate(self.tag)
+                .span(|s| {
+                    s.static_attributes().class("count").rupdate(self.count);
+                });
+        });
+    }
+}
+
 struct Info;
 impl spair::Render<App> for Info {
     fn render(self, nodes: spair::Nodes<App>) {
+        let comp = nodes.comp();
+        let state = nodes.state();
         nodes.footer(|f| {
             f.static_attributes()
                 .class("info")
+                .p(|p| {
+                    p.label(|l| {
+                        l.input(|i| {
+                            i.checked(state.show_times)
+                                .on_change(comp.handler_mut(App::toggle_show_times))
+                                .static_attributes()
+                                .r#type(spair::InputType::CheckBox);
+                        })
+                        .rstatic(" Show relative times");
+                    });
+                })
                 .static_nodes()
                 .p(|p| p.rstatic("Double-click to edit a todo").done())
                 .p(|p| p.rstatic("Created by 'aclueless'").done())
@@ -273,53 +881,191 @@ impl<'a> spair::ElementRender<App> for RenderEntry<'a> {
         let state = li.state();
         let id = self.0.id;
         let is_editing_me = state.editing_id == Some(id);
-        li.class_if(self.0.completed, "completed")
+        let is_adding_subtask = state.adding_subtask_to == Some(id);
+        let search = state.data.search();
+        let today = Date::today();
+        let children = state.data.get_visible_children(self.0);
+        let progress = self.0.subtask_progress();
+        let blockers = match self.0.is_completed() {
+            true => Vec::new(),
+            false => state.data.active_blockers(&id),
+        };
+        li.class_if(self.0.is_completed(), "completed")
+            .class_if(self.0.is_overdue(today), "overdue")
+            .class_if(self.0.priority == Priority::Low, "priority-low")
+            .class_if(self.0.priority == Priority::Medium, "priority-medium")
+            .class_if(self.0.priority == Priority::High, "priority-high")
             .class_if(is_editing_me, "editing")
+            .class_if(state.dragging_id == Some(id), "dragging")
+            .class_if(self.0.status == Status::InProgress, "status-in-progress")
+            .class_if(matches!(self.0.status, Status::Custom(_)), "status-custom")
+            .class_if(self.0.has_subtasks(), "has-subtasks")
+            .class_if(self.0.collapsed, "collapsed")
+            .class_if(!blockers.is_empty(), "blocked")
+            // Spair has no wrappers for HTML5 drag events, so dragging is
+            // done with plain mouse events: press on an item, release on another.
+            .on_mouse_down(comp.handler_mut(move |state| state.start_dragging(id)))
+            .on_mouse_up(comp.handler_mut(move |state| state.drop_on(id)))
+            .on_key_down(comp.handler_arg_mut(move |state, arg: spair::KeyboardEvent| {
+                let event = arg.raw();
+                if !event.alt_key() || state.editing_id == Some(id) {
+                    return;
+                }
+                match event.code().as_str() {
+                    "ArrowUp" => state.move_by(id, true),
+                    "ArrowDown" => state.move_by(id, false),
+                    _ => {}
+                }
+            }))
             .div(move |d| {
                 d.static_attributes()
                     .class("view")
                     .input(|i| {
-                        i.on_change(comp.handler_mut(move |state| state.toggle_completion(id)))
-                            .checked(self.0.completed)
+                        i.on_change(comp.handler_arg_mut(move |state, arg: spair::Event| {
+                            if !state.toggle_completion(id) {
+                                // Nothing changed in the state, so spair would leave the box checked
+                                if let Some(input) = arg.current_target_as::<spair::web_sys::HtmlInputElement>() {
+                                    input.set_checked(false);
+                                }
+                            }
+                        }))
+                            .checked(self.0.is_completed())
                             .static_attributes()
                             .class("toggle")
                             .r#type(spair::InputType::CheckBox);
                     })
                     .label(|l| {
                         l.on_double_click(comp.handler_mut(move |state| state.start_editing(id)))
-                            .rupdate(&self.0.description);
+                            .match_if(|mi| match search.is_empty() {
+                                true => spair::set_arm!(mi).rupdate(&self.0.description).done(),
+                                // A list needs an element of its own
+                                false => spair::set_arm!(mi)
+                                    .span(|s| {
+                                        s.list_clone(
+                                            search
+                                                .split_matches(&self.0.description)
+                                                .into_iter()
+                                                .map(Highlight),
+                                        );
+                                    })
+                                    .done(),
+                            });
+                    })
+                    // A button, so that it can also be cycled with Enter or Space
+                    .button(|b| {
+                        b.on_click(comp.handler_mut(move |state| state.cycle_priority(id)))
+                            .title(&format!("Priority: {}", self.0.priority.label()))
+                            .static_attributes()
+                            .class("priority")
+                            .update_nodes()
+                            .rupdate(self.0.priority.marker());
                     })
                     .button(|b| {
                         b.on_click(comp.handler_mut(move |state| state.remove_by_id(id)))
                             .static_attributes()
                             .class("destroy");
+                    })
+                    .button(|b| {
+                        b.on_click(comp.handler_mut(move |state| state.start_adding_subtask(id)))
+                            .static_attributes()
+                            .class("add-subtask")
+                            .title("Add a subtask")
+                            .static_nodes()
+                            .rstatic("+");
+                    })
+                    .button(|b| {
+                        b.class_if(!self.0.has_subtasks(), "hidden")
+                            .on_click(comp.handler_mut(move |state| state.toggle_collapsed(id)))
+                            .title(if self.0.collapsed { "Expand" } else { "Collapse" })
+                            .static_attributes()
+                            .class("collapse")
+                            .update_nodes()
+                            .rupdate(if self.0.collapsed { "▸" } else { "▾" });
+                    })
+                    .match_if(|mi| match blockers.is_empty() {
+                        true => spair::set_arm!(mi).done(),
+                        false => spair::set_arm!(mi)
+                            .span(|s| {
+                                let names: Vec<_> = blockers.iter().map(|e| e.description.as_str()).collect();
+                                s.static_attributes()
+                                    .class("blocked-by")
+                                    .rupdate(format!("blocked by {}", names.join(", ")).as_str());
+                            })
+                            .done(),
+                    })
+                    .match_if(|mi| match progress {
+                        Some((completed, total)) => spair::set_arm!(mi)
+                            .span(|s| {
+                                s.static_attributes()
+                                    .class("subtask-progress")
+                                    .rupdate(format!("{completed}/{total} subtasks").as_str());
+                            })
+                            .done(),
+                        None => spair::set_arm!(mi).done(),
+                    })
+                    .match_if(|mi| match self.0.due_label(today) {
+                        Some(label) => spair::set_arm!(mi)
+                            .span(|s| {
+                                s.static_attributes().class("due").rupdate(label.as_str());
+                            })
+                            .done(),
+                        None => spair::set_arm!(mi).done(),
+                    })
+                    .match_if(|mi| match self.0.recurrence {
+                        Some(recurrence) => spair::set_arm!(mi)
+                            .span(|s| {
+                                s.static_attributes()
+                                    .class("recurrence")
+                                    .title("Completing it adds the next one")
+                                    .rupdate(recurrence.to_string().as_str());
+                            })
+                            .done(),
+                        None => spair::set_arm!(mi).done(),
+                    })
+                    .match_if(|mi| match state.show_times {
+                        true => spair::set_arm!(mi)
+                            .span(|s| {
+                                s.static_attributes()
+                                    .class("time")
+                                    .rupdate(self.0.relative_time_label(todomvc_shared::now()).as_str());
+                            })
+                            .done(),
+                        false => spair::set_arm!(mi).done(),
                     });
             })
             .match_if(|mi| match is_editing_me {
                 true => spair::set_arm!(mi)
-                    .rupdate(EditingInput(&self.0.description))
+                    .rupdate(EditingInput(self.0))
                     .done(),
                 false => spair::set_arm!(mi).done(),
-            });
+            })
+            .match_if(|mi| match is_adding_subtask {
+                true => spair::set_arm!(mi)
+                    .rupdate(SubtaskInput)
+                    .done(),
+                false => spair::set_arm!(mi).done(),
+            })
+            // Recursive: each subtask is a `RenderEntry` too
+            .ul(|u| render_subtasks(u, children));
     }
 }
 
-struct EditingInput<'a>(&'a String);
-impl<'a> spair::Render<App> for EditingInput<'a> {
+struct SubtaskInput;
+impl spair::Render<App> for SubtaskInput {
     fn render(self, nodes: spair::Nodes<App>) {
         let comp = nodes.comp();
         nodes.input(|i| {
             i.focus(true)
-                .value(self.0)
                 .static_attributes()
-                .class("edit")
+                .class("new-subtask")
+                .placeholder("Add a subtask")
                 .on_blur(comp.handler_arg_mut(|state, arg: spair::FocusEvent| {
-                    state.end_editing(get_value(arg.current_target_as()))
+                    state.end_adding_subtask(get_value(arg.current_target_as()));
                 }))
                 .on_key_down(comp.handler_arg_mut(|state, arg: spair::KeyboardEvent| {
                     match arg.raw().code().as_str() {
-                        "Escape" => state.cancel_editing(),
-                        "Enter" => state.end_editing(get_value(arg.current_target_as())),
+                        "Escape" => state.end_adding_subtask(None),
+                        "Enter" => state.end_adding_subtask(get_value(arg.current_target_as())),
                         _ => {}
                     }
                 }));
@@ -327,6 +1073,163 @@ impl<'a> spair::Render<App> for EditingInput<'a> {
     }
 }
 
+struct Highlight<'a>(Segment<'a>);
+impl<'a> spair::ElementRender<App> for Highlight<'a> {
+    const ELEMENT_TAG: &'static str = "span";
+    fn render(self, span: spair::Element<App>) {
+        span.class_if(self.0.matched, "highlight").rupdate(self.0.text);
+    }
+}
+
+struct EditingInput<'a>(&'a TodoEntry);
+impl<'a> spair::Render<App> for EditingInput<'a> {
+    fn render(self, nodes: spair::Nodes<App>) {
+        let comp = nodes.comp();
+        let id = self.0.id;
+        let due = self.0.due.map(|due| due.to_string()).unwrap_or_default();
+        nodes
+            .input(|i| {
+                i.focus(true)
+                    .value(&self.0.description)
+                    .static_attributes()
+                    .class("edit")
+                    .on_blur(comp.handler_arg_mut(|state, arg: spair::FocusEvent| {
+                        let value = get_value(arg.current_target_as());
+                        match focus_moves_to(&arg, &["edit-due", "edit-blocker", "remove-blocker"]) {
+                            true => state.save_description(value),
+                            false => state.end_editing(value),
+                        }
+                    }))
+                    .on_key_down(comp.handler_arg_mut(|state, arg: spair::KeyboardEvent| {
+                        match arg.raw().code().as_str() {
+                            "Escape" => state.cancel_editing(),
+                            "Enter" => state.end_editing(get_value(arg.current_target_as())),
+                            _ => {}
+                        }
+                    }));
+            })
+            .input(|i| {
+                i.value(&due)
+                    .static_attributes()
+                    .class("edit-due")
+                    .r#type(spair::InputType::Date)
+                    .on_change(comp.handler_arg_mut(move |state, arg: spair::Event| {
+                        if let Some(input) = arg.current_target_as::<spair::web_sys::HtmlInputElement>() {
+                            // An empty value clears the due date
+                            state.set_due(id, Date::parse_iso(&input.value()));
+                        }
+                    }))
+                    .on_blur(comp.handler_arg_mut(|state, arg: spair::FocusEvent| {
+                        if !focus_moves_to(&arg, EDITING_CONTROLS) {
+                            state.cancel_editing();
+                        }
+                    }))
+                    .on_key_down(comp.handler_arg_mut(|state, arg: spair::KeyboardEvent| {
+                        if matches!(arg.raw().code().as_str(), "Escape" | "Enter") {
+                            state.cancel_editing();
+                        }
+                    }));
+            })
+            .rupdate(EditingBlockers(self.0));
+    }
+}
+
+const EDITING_CONTROLS: &[&str] = &["edit", "edit-due", "edit-blocker", "remove-blocker"];
+
+// The declared blockers of the entry being edited, and a select to add one
+struct EditingBlockers<'a>(&'a TodoEntry);
+impl<'a> spair::Render<App> for EditingBlockers<'a> {
+    fn render(self, nodes: spair::Nodes<App>) {
+        let comp = nodes.comp();
+        let state = nodes.state();
+        let id = self.0.id;
+        let blockers: Vec<_> = state
+            .data
+            .dependencies
+            .blockers(&id)
+            .filter_map(|blocker_id| state.data.get_entry_by_id(blocker_id))
+            .collect();
+        // A todo cannot be blocked by itself, its subtasks or its ancestors
+        let path = state.data.path_to(&id).unwrap_or_default();
+        let candidates = state.data.iter_tree().filter(|e| {
+            !path.iter().any(|a| a.id == e.id)
+                && !self.0.iter_tree().any(|d| d.id == e.id)
+                && !state.data.dependencies.contains(&id, &e.id)
+        });
+        nodes
+            .ul(|u| {
+                u.static_attributes()
+                    .class("edit-blockers")
+                    .list_clone(blockers.into_iter().map(|blocker| BlockerView { id, blocker }));
+            })
+            .select(|s| {
+                s.static_attributes()
+                    .class("edit-blocker")
+                    .on_change(comp.handler_arg_mut(move |state, arg: spair::Event| {
+                        if let Some(select) = arg.current_target_as::<spair::web_sys::HtmlSelectElement>() {
+                            if let Ok(blocker_id) = uuid::Uuid::parse_str(&select.value()) {
+                                state.add_blocker(id, blocker_id);
+                            }
+                            select.set_value("");
+                        }
+                    }))
+                    .on_blur(comp.handler_arg_mut(|state, arg: spair::FocusEvent| {
+                        if !focus_moves_to(&arg, EDITING_CONTROLS) {
+                            state.cancel_editing();
+                        }
+                    }))
+                    .static_nodes()
+                    .option(|o| {
+                        o.static_attributes().value("").rstatic("Blocked by…");
+                    })
+                    .update_nodes()
+                    .list_clone(candidates.map(BlockerOption));
+            });
+    }
+}
+
+struct BlockerView<'a> {
+    id: uuid::Uuid,
+    blocker: &'a TodoEntry,
+}
+
+impl<'a> spair::ElementRender<App> for BlockerView<'a> {
+    const ELEMENT_TAG: &'static str = "li";
+    fn render(self, li: spair::Element<App>) {
+        let comp = li.comp();
+        let (id, blocker_id) = (self.id, self.blocker.id);
+        li.class_if(self.blocker.is_completed(), "completed")
+            .rupdate(&self.blocker.description)
+            .button(|b| {
+                b.on_click(comp.handler_mut(move |state| state.remove_blocker(id, blocker_id)))
+                    .static_attributes()
+                    .class("remove-blocker")
+                    .title("Not blocked by this anymore")
+                    .static_nodes()
+                    .rstatic("×");
+            });
+    }
+}
+
+struct BlockerOption<'a>(&'a TodoEntry);
+impl<'a> spair::ElementRender<App> for BlockerOption<'a> {
+    const ELEMENT_TAG: &'static str = "option";
+    fn render(self, option: spair::Element<App>) {
+        option
+            .value(&self.0.id.to_string())
+            .rupdate(&self.0.description);
+    }
+}
+
+// Whether the focus goes to an element with one of `classes`, so that leaving
+// one control of the editing row for another one does not end editing.
+fn focus_moves_to(arg: &spair::FocusEvent, classes: &[&str]) -> bool {
+    arg.raw()
+        .related_target()
+        .and_then(|target| target.dyn_into::<spair::web_sys::Element>().ok())
+        .is_some_and(|element| element.class_name().split_whitespace().any(|c| classes.contains(&c)))
+}
+
 fn get_value(i: Option<spair::web_sys::HtmlInputElement>) -> Option<String> {
     i.and_then(|i| {
         let text = i.value();
@@ -341,9 +1244,14 @@ fn get_value(i: Option<spair::web_sys::HtmlInputElement>) -> Option<String> {
 impl spair::Application for App {
     fn init(_comp: &spair::Comp<Self>) -> Self {
         Self {
-            data: Todos::load_todos(KEY),
+            data: TodoLists::load_todos(KEY),
+            renaming_list: None,
             editing_id: None,
             new_description: String::new(),
+            show_times: false,
+            dragging_id: None,
+            adding_subtask_to: None,
+            dependency_error: None,
         }
     }
 
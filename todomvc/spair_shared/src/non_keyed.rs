@@ -1,10 +1,29 @@
 use spair::prelude::*;
+use todomvc_shared::{ArchivedEntry, TodoEntry};
 
 pub const KEY: &str = "todos-spair-non-keyed";
 
 pub fn render_list(e: spair::Element<super::App>) {
     let state = e.state();
     e.static_attributes().class("todo-list").list_clone(
-        state.data.get_filtered_entries().map(super::RenderEntry),
+        state.data.get_visible_entries().into_iter().map(super::RenderEntry),
+    );
+}
+
+pub fn render_subtasks(e: spair::Element<super::App>, entries: Vec<&TodoEntry>) {
+    e.static_attributes().class("subtasks").list_clone(
+        entries.into_iter().map(super::RenderEntry),
+    );
+}
+
+pub fn render_cards(e: spair::Element<super::App>, entries: Vec<&TodoEntry>) {
+    e.static_attributes().class("cards").list_clone(
+        entries.into_iter().map(super::CardView),
+    );
+}
+
+pub fn render_archived(e: spair::Element<super::App>, entries: &[ArchivedEntry]) {
+    e.static_attributes().class("archive-list").list_clone(
+        entries.iter().map(super::ArchivedView),
     );
 }
@@ -1,23 +1,19 @@
-use todomvc_shared::Filter;
+use todomvc_shared::Route;
 
 pub use super::App;
 
 pub struct Router(pub spair::Comp<App>);
 impl spair::Router for Router {
     fn routing(&self, location: spair::web_sys::Location) {
-        let filter = match location.hash().unwrap_or_else(|_| String::new()).as_str() {
-            "#completed" => Filter::Completed,
-            "#active" => Filter::Active,
-            _ => Filter::All,
-        };
-        self.0.callback_arg_mut(App::set_filter).emit(filter);
+        let route = Route::parse(&location.hash().unwrap_or_else(|_| String::new()));
+        self.0.callback_arg_mut(App::set_route).emit(route);
     }
 }
 
-pub struct Routes(pub Filter);
+pub struct Routes(pub Route);
 impl spair::Routes for Routes {
     type Router = Router;
     fn url(&self) -> String {
-        self.0.as_href().to_string()
+        self.0.to_hash()
     }
 }
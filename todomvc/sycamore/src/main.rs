@@ -1,14 +1,38 @@
 use serde::{Deserialize, Serialize};
 use sycamore::prelude::*;
+use todomvc_shared::{Priority, Route, SortOrder, Sortable, Timestamp, TodoLists};
 use uuid::Uuid;
 use wasm_bindgen::JsCast;
-use web_sys::{Event, HtmlInputElement, KeyboardEvent};
+use web_sys::{DragEvent, Event, HtmlInputElement, KeyboardEvent};
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Todo {
     title: String,
     completed: bool,
     id: Uuid,
+    #[serde(default)]
+    created_at: Timestamp,
+    #[serde(default)]
+    priority: Priority,
+}
+
+// Lets todomvc_shared sort our todos exactly like the other implementations sort theirs
+impl Sortable for Todo {
+    fn with_description<R>(&self, f: impl FnOnce(&str) -> R) -> R {
+        f(&self.title)
+    }
+
+    fn created_at(&self) -> Timestamp {
+        self.created_at
+    }
+
+    fn is_completed(&self) -> bool {
+        self.completed
+    }
+
+    fn priority(&self) -> Priority {
+        self.priority
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -25,37 +49,141 @@ impl Default for Filter {
 }
 
 impl Filter {
-    fn url(self) -> &'static str {
+    fn fits(self, todo: &Todo) -> bool {
         match self {
-            Filter::All => "#",
-            Filter::Active => "#/active",
-            Filter::Completed => "#/completed",
+            Filter::All => true,
+            Filter::Active => !todo.completed,
+            Filter::Completed => todo.completed,
         }
     }
+}
 
-    fn get_filter_from_hash() -> Self {
-        let hash = web_sys::window().unwrap().location().hash().unwrap();
+impl From<todomvc_shared::Filter> for Filter {
+    fn from(filter: todomvc_shared::Filter) -> Self {
+        match filter {
+            todomvc_shared::Filter::All => Filter::All,
+            todomvc_shared::Filter::Active => Filter::Active,
+            todomvc_shared::Filter::Completed => Filter::Completed,
+            // This implementation has no due dates
+            todomvc_shared::Filter::Overdue
+            | todomvc_shared::Filter::DueToday
+            | todomvc_shared::Filter::Upcoming => Filter::All,
+            // Nor dependencies, so every active todo can be done next
+            todomvc_shared::Filter::Next => Filter::Active,
+            // Nor a board, the list shows every todo
+            todomvc_shared::Filter::Board | todomvc_shared::Filter::Archive => Filter::All,
+        }
+    }
+}
 
-        match hash.as_str() {
-            "#/active" => Filter::Active,
-            "#/completed" => Filter::Completed,
-            _ => Filter::All,
+impl From<Filter> for todomvc_shared::Filter {
+    fn from(filter: Filter) -> Self {
+        match filter {
+            Filter::All => todomvc_shared::Filter::All,
+            Filter::Active => todomvc_shared::Filter::Active,
+            Filter::Completed => todomvc_shared::Filter::Completed,
         }
     }
 }
 
-#[derive(Debug, Default, Clone)]
+fn get_route_from_hash() -> Route {
+    let hash = web_sys::window().unwrap().location().hash().unwrap();
+    Route::parse(&hash)
+}
+
+#[derive(Debug, Clone)]
 pub struct AppState {
+    /// The todos of the current list.
     pub todos: RcSignal<Vec<RcSignal<Todo>>>,
+    /// The todos of the other lists. The slot of the current list is only
+    /// up to date while another list is shown, `todos` is.
+    pub lists: RcSignal<TodoLists<Vec<Todo>>>,
     pub filter: RcSignal<Filter>,
+    pub sort: RcSignal<SortOrder>,
+    pub dragging: RcSignal<Option<Uuid>>,
 }
 
+
 impl AppState {
+    fn route(&self) -> Route {
+        Route {
+            filter: (*self.filter.get()).into(),
+            sort: *self.sort.get(),
+            list: self.lists.get().route_list(),
+            ..Route::default()
+        }
+    }
+
+    fn current_todos(&self) -> Vec<Todo> {
+        self.todos.get().iter().map(|todo| todo.get().as_ref().clone()).collect()
+    }
+
+    // `lists` is changed before `todos`, so that the todos are saved under
+    // the key of the list they belong to
+    fn show_list(&self, todos: &[Todo]) {
+        self.todos.set(todos.iter().cloned().map(create_rc_signal).collect());
+    }
+
+    fn switch_list(&self, id: Uuid) {
+        let todos = {
+            let mut lists = self.lists.modify();
+            if lists.current().id == id {
+                return;
+            }
+            lists.current_mut().todos = self.current_todos();
+            if !lists.switch(&id) {
+                return;
+            }
+            lists.current().todos.clone()
+        };
+        self.show_list(&todos);
+    }
+
+    fn create_list(&self, name: &str) -> bool {
+        let todos = self.current_todos();
+        {
+            let mut lists = self.lists.modify();
+            lists.current_mut().todos = todos;
+            if lists.create(name).is_none() {
+                return false;
+            }
+        }
+        self.show_list(&[]);
+        true
+    }
+
+    fn rename_list(&self, id: Uuid, name: &str) {
+        self.lists.modify().rename(&id, name);
+    }
+
+    fn delete_list(&self, id: Uuid) {
+        let todos = {
+            let mut lists = self.lists.modify();
+            let current = lists.current().id;
+            if !lists.delete(&id) || lists.current().id == current {
+                return;
+            }
+            lists.current().todos.clone()
+        };
+        self.show_list(&todos);
+    }
+
+    fn list_active_count(&self, id: Uuid) -> usize {
+        let lists = self.lists.get();
+        match lists.get(&id) {
+            Some(list) if list.id == lists.current().id => self.todos_left(),
+            Some(list) => list.todos.iter().filter(|todo| !todo.completed).count(),
+            None => 0,
+        }
+    }
+
     fn add_todo(&self, title: String) {
         self.todos.modify().push(create_rc_signal(Todo {
             title,
             completed: false,
             id: Uuid::new_v4(),
+            created_at: todomvc_shared::now(),
+            priority: Priority::None,
         }))
     }
 
@@ -63,6 +191,47 @@ impl AppState {
         self.todos.modify().retain(|todo| todo.get().id != id);
     }
 
+    /// Move the todo with `id` to the place of `target`: after `target` when moving down,
+    /// before it when moving up.
+    fn move_todo(&self, id: Uuid, target: Uuid) {
+        // The manual order is not visible when the list is sorted
+        if *self.sort.get() != SortOrder::Insertion {
+            return;
+        }
+        let (from, to) = {
+            let todos = self.todos.get();
+            let position = |x: Uuid| todos.iter().position(|todo| todo.get().id == x);
+            match (position(id), position(target)) {
+                (Some(from), Some(to)) if from != to => (from, to),
+                _ => return,
+            }
+        };
+        let mut todos = self.todos.modify();
+        let todo = todos.remove(from);
+        todos.insert(to, todo);
+    }
+
+    /// Swap a todo with its closest neighbor that is visible with the current filter.
+    fn move_todo_by_one(&self, id: Uuid, up: bool) {
+        let filter = *self.filter.get();
+        let target = {
+            let todos = self.todos.get();
+            let index = match todos.iter().position(|todo| todo.get().id == id) {
+                Some(index) => index,
+                None => return,
+            };
+            let visible = |todo: &&RcSignal<Todo>| filter.fits(&todo.get());
+            if up {
+                todos[..index].iter().rev().find(visible).map(|todo| todo.get().id)
+            } else {
+                todos[index + 1..].iter().find(visible).map(|todo| todo.get().id)
+            }
+        };
+        if let Some(target) = target {
+            self.move_todo(id, target);
+        }
+    }
+
     fn todos_left(&self) -> usize {
         self.todos.get().iter().fold(
             0,
@@ -118,14 +287,22 @@ fn App<G: Html>(cx: Scope) -> View<G> {
         .unwrap()
         .expect("user has not enabled localStorage");
 
-    let todos = if let Ok(Some(app_state)) = local_storage.get_item(KEY) {
-        serde_json::from_str(&app_state).unwrap_or_else(|_| create_rc_signal(Vec::new()))
-    } else {
-        create_rc_signal(Vec::new())
-    };
+    // The first list is the one saved before there were lists
+    let mut lists = TodoLists::<Vec<Todo>>::load_index(KEY);
+    for list in lists.lists.iter_mut() {
+        if let Ok(Some(todos)) = local_storage.get_item(&list.storage_key) {
+            list.todos = serde_json::from_str(&todos).unwrap_or_default();
+        }
+    }
+    let route = get_route_from_hash();
+    lists.switch_to_route(route.list);
+    let todos = lists.current().todos.iter().cloned().map(create_rc_signal).collect();
     let app_state = AppState {
-        todos,
-        filter: create_rc_signal(Filter::get_filter_from_hash()),
+        todos: create_rc_signal(todos),
+        lists: create_rc_signal(lists),
+        filter: create_rc_signal(route.filter.into()),
+        sort: create_rc_signal(route.sort),
+        dragging: create_rc_signal(None),
     };
     provide_context(cx, app_state);
     // Set up an effect that runs a function anytime app_state.todos changes
@@ -134,17 +311,25 @@ fn App<G: Html>(cx: Scope) -> View<G> {
         for todo in app_state.todos.get().iter() {
             todo.track();
         }
+        // Not tracked: switching lists changes `todos` too
+        let storage_key = app_state.lists.get_untracked().current().storage_key.clone();
         local_storage
             .set_item(
-                KEY,
+                &storage_key,
                 &serde_json::to_string(app_state.todos.get().as_ref()).unwrap(),
             )
             .unwrap();
     });
+    // And one that saves the names anytime the lists change
+    create_effect(cx, move || {
+        let app_state = use_context::<AppState>(cx);
+        app_state.lists.get().save_index().unwrap();
+    });
 
     view! { cx,
         div(class="todomvc-wrapper") {
             section(class="todoapp") {
+                ListSwitcher {}
                 Header {}
                 List {}
                 Footer {}
@@ -171,6 +356,113 @@ pub fn Copyright<G: Html>(cx: Scope) -> View<G> {
     }
 }
 
+#[component]
+pub fn ListSwitcher<G: Html>(cx: Scope) -> View<G> {
+    let app_state = use_context::<AppState>(cx);
+    let ids = create_memo(cx, || {
+        app_state.lists.get().lists.iter().map(|list| list.id).collect::<Vec<_>>()
+    });
+
+    let handle_submit = |event: Event| {
+        let event: KeyboardEvent = event.unchecked_into();
+        if event.key() == "Enter" {
+            let input: HtmlInputElement = event.target().unwrap().unchecked_into();
+            if app_state.create_list(&input.value()) {
+                input.set_value("");
+            }
+        }
+    };
+
+    view! { cx,
+        nav(class="lists") {
+            ul {
+                Keyed(
+                    iterable=ids,
+                    view=|cx, id| view! { cx,
+                        ListTab(id=id)
+                    },
+                    key=|id| *id,
+                )
+            }
+            input(class="new-list", placeholder="New list", on:keyup=handle_submit)
+        }
+    }
+}
+
+#[component(inline_props)]
+pub fn ListTab<G: Html>(cx: Scope, id: Uuid) -> View<G> {
+    let app_state = use_context::<AppState>(cx);
+    let name = || {
+        app_state.lists.get().get(&id).map(|list| list.name.clone()).unwrap_or_default()
+    };
+    let selected = create_selector(cx, move || app_state.lists.get().current().id == id);
+    let deletable = create_selector(cx, || app_state.lists.get().lists.len() > 1);
+    let href = move || app_state.lists.get().route_to(&id, &app_state.route()).to_hash();
+
+    let renaming = create_signal(cx, false);
+    let input_ref = create_node_ref(cx);
+
+    let handle_dblclick = move |_| {
+        renaming.set(true);
+        input_ref
+            .get::<DomNode>()
+            .unchecked_into::<HtmlInputElement>()
+            .focus()
+            .unwrap();
+    };
+
+    let handle_blur = move || {
+        if *renaming.get() {
+            renaming.set(false);
+            let input: HtmlInputElement = input_ref.get::<DomNode>().unchecked_into();
+            app_state.rename_list(id, &input.value());
+        }
+    };
+
+    let handle_submit = move |event: Event| {
+        let event: KeyboardEvent = event.unchecked_into();
+        match event.key().as_str() {
+            "Enter" => handle_blur(),
+            "Escape" => renaming.set(false),
+            _ => {}
+        }
+    };
+
+    view! { cx,
+        li(class=if *selected.get() { "selected" } else { "" }) {
+            (if *renaming.get() {
+                view! { cx,
+                    input(ref=input_ref,
+                        class="rename-list",
+                        prop:value=name(),
+                        on:blur=move |_| handle_blur(),
+                        on:keyup=handle_submit,
+                    )
+                }
+            } else {
+                view! { cx,
+                    a(
+                        href=href(),
+                        title="Double-click to rename",
+                        on:click=move |_| app_state.switch_list(id),
+                        on:dblclick=handle_dblclick,
+                    ) {
+                        (name())
+                        span(class="count") { (app_state.list_active_count(id)) }
+                    }
+                    button(
+                        class=if *deletable.get() { "delete-list" } else { "delete-list hidden" },
+                        title="Delete this list and its todos",
+                        on:click=move |_| app_state.delete_list(id),
+                    ) {
+                        "×"
+                    }
+                }
+            })
+        }
+    }
+}
+
 #[component]
 pub fn Header<G: Html>(cx: Scope) -> View<G> {
     let app_state = use_context::<AppState>(cx);
@@ -216,6 +508,7 @@ pub fn Item<G: Html>(cx: Scope, todo: RcSignal<Todo>) -> View<G> {
 
     let title = || todo.get().title.clone();
     let completed = create_selector(cx, || todo.get().completed);
+    let priority = create_selector(cx, || todo.get().priority);
     let id = todo.get().id;
 
     let editing = create_signal(cx, false);
@@ -275,10 +568,53 @@ pub fn Item<G: Html>(cx: Scope, todo: RcSignal<Todo>) -> View<G> {
         }
     };
 
+    let cycle_priority = |_| {
+        todo.set(Todo {
+            priority: todo.get().priority.next(),
+            ..todo.get().as_ref().clone()
+        });
+    };
+
     let handle_destroy = move |_| {
         app_state.remove_todo(id);
     };
 
+    let handle_dragstart = move |event: Event| {
+        let event: DragEvent = event.unchecked_into();
+        // Firefox does not start dragging without any data
+        if let Some(data) = event.data_transfer() {
+            data.set_data("text/plain", &id.to_string()).unwrap();
+        }
+        app_state.dragging.set(Some(id));
+    };
+
+    // The default action of dragover is to refuse the drop
+    let handle_dragover = |event: Event| event.prevent_default();
+
+    let handle_drop = move |event: Event| {
+        event.prevent_default();
+        if let Some(dragged) = *app_state.dragging.get() {
+            app_state.dragging.set(None);
+            app_state.move_todo(dragged, id);
+        }
+    };
+
+    let handle_dragend = move |_| app_state.dragging.set(None);
+
+    let handle_keydown = move |event: Event| {
+        let event: KeyboardEvent = event.unchecked_into();
+        if !event.alt_key() || *editing.get() {
+            return;
+        }
+        match event.key().as_str() {
+            "ArrowUp" => app_state.move_todo_by_one(id, true),
+            "ArrowDown" => app_state.move_todo_by_one(id, false),
+            _ => {}
+        }
+    };
+
+    let dragging = create_selector(cx, move || *app_state.dragging.get() == Some(id));
+
     // We need a separate signal for checked because clicking the checkbox will detach the binding
     // between the attribute and the view.
     let checked = create_signal(cx, false);
@@ -289,14 +625,23 @@ pub fn Item<G: Html>(cx: Scope, todo: RcSignal<Todo>) -> View<G> {
 
     let class = || {
         format!(
-            "{} {}",
+            "{} {} {} {}",
             if *completed.get() { "completed" } else { "" },
-            if *editing.get() { "editing" } else { "" }
+            if *editing.get() { "editing" } else { "" },
+            if *dragging.get() { "dragging" } else { "" },
+            priority.get().class_name().unwrap_or_default()
         )
     };
 
     view! { cx,
-        li(class=class()) {
+        li(class=class(),
+            draggable="true",
+            on:dragstart=handle_dragstart,
+            on:dragover=handle_dragover,
+            on:drop=handle_drop,
+            on:dragend=handle_dragend,
+            on:keydown=handle_keydown,
+        ) {
             div(class="view") {
                 input(
                     class="toggle",
@@ -307,6 +652,14 @@ pub fn Item<G: Html>(cx: Scope, todo: RcSignal<Todo>) -> View<G> {
                 label(on:dblclick=handle_dblclick) {
                     (title())
                 }
+                // A button, so that it can also be cycled with Enter or Space
+                button(
+                    class="priority",
+                    title=format!("Priority: {}", priority.get().label()),
+                    on:click=cycle_priority,
+                ) {
+                    (priority.get().marker())
+                }
                 button(class="destroy", on:click=handle_destroy)
             }
 
@@ -333,17 +686,19 @@ pub fn List<G: Html>(cx: Scope) -> View<G> {
     let todos_left = create_selector(cx, || app_state.todos_left());
 
     let filtered_todos = create_memo(cx, || {
-        app_state
+        let mut filtered = app_state
             .todos
             .get()
             .iter()
-            .filter(|todo| match *app_state.filter.get() {
-                Filter::All => true,
-                Filter::Active => !todo.get().completed,
-                Filter::Completed => todo.get().completed,
-            })
+            .filter(|todo| app_state.filter.get().fits(&todo.get()))
             .cloned()
-            .collect::<Vec<_>>()
+            .collect::<Vec<_>>();
+        // Sort the filtered copy, the order of `app_state.todos` is not changed
+        let sort = *app_state.sort.get();
+        if sort != SortOrder::Insertion {
+            filtered.sort_by(|a, b| sort.compare(&*a.get(), &*b.get()));
+        }
+        filtered
     });
 
     // We need a separate signal for checked because clicking the checkbox will detach the binding
@@ -389,7 +744,7 @@ pub fn TodoFilter<G: Html>(cx: Scope, filter: Filter) -> View<G> {
         li {
             a(
                 class=if selected() { "selected" } else { "" },
-                href=filter.url(),
+                href=app_state.route().with_filter(filter.into()).to_hash(),
                 on:click=move |_| set_filter(filter),
             ) {
                 (format!("{filter:?}"))
@@ -398,6 +753,25 @@ pub fn TodoFilter<G: Html>(cx: Scope, filter: Filter) -> View<G> {
     }
 }
 
+#[component(inline_props)]
+pub fn TodoSort<G: Html>(cx: Scope, sort: SortOrder) -> View<G> {
+    let app_state = use_context::<AppState>(cx);
+    let selected = move || sort == *app_state.sort.get();
+    let set_sort = |sort| app_state.sort.set(sort);
+
+    view! { cx,
+        li {
+            a(
+                class=if selected() { "selected" } else { "" },
+                href=app_state.route().with_sort(sort).to_hash(),
+                on:click=move |_| set_sort(sort),
+            ) {
+                (sort.label())
+            }
+        }
+    }
+}
+
 #[component]
 pub fn Footer<G: Html>(cx: Scope) -> View<G> {
     let app_state = use_context::<AppState>(cx);
@@ -423,6 +797,14 @@ pub fn Footer<G: Html>(cx: Scope) -> View<G> {
                 TodoFilter(filter=Filter::Active)
                 TodoFilter(filter=Filter::Completed)
             }
+            ul(class="sort-orders") {
+                TodoSort(sort=SortOrder::Insertion)
+                TodoSort(sort=SortOrder::Alphabetical)
+                TodoSort(sort=SortOrder::Created)
+                TodoSort(sort=SortOrder::CompletedLast)
+                TodoSort(sort=SortOrder::ActiveFirst)
+                TodoSort(sort=SortOrder::Priority)
+            }
 
             (if *has_completed_todos.get() {
                 view! { cx,
@@ -0,0 +1,141 @@
+use std::cell::Cell;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{now, subtask, TodoEntry, Timestamp, Todos, DAY};
+
+/// Retention of a new archive, see `Archive::retention_days`.
+pub const DEFAULT_RETENTION_DAYS: u32 = 30;
+
+/// Where cleared and removed entries go, see `Todos::archive`.
+///
+/// Saved under its own key, see `archive_key`: the main list is saved on
+/// every change and loaded on every start, and does not grow with the
+/// archive. The archive is only written when it changes.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Archive {
+    /// Most recently archived first.
+    pub entries: Vec<ArchivedEntry>,
+    /// Archived entries are purged after this many days, `None` keeps them
+    /// forever, see `purge`.
+    pub retention_days: Option<u32>,
+    #[serde(skip)]
+    changed: Cell<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ArchivedEntry {
+    /// With its subtasks.
+    pub entry: TodoEntry,
+    pub archived_at: Timestamp,
+    /// The entry it was a subtask of, it goes back there when restored if
+    /// that entry is still in the list.
+    pub parent: Option<uuid::Uuid>,
+}
+
+impl Default for Archive {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            retention_days: Some(DEFAULT_RETENTION_DAYS),
+            changed: Cell::new(false),
+        }
+    }
+}
+
+/// The storage key of the archive of the list saved under `key`.
+pub fn archive_key(key: &str) -> String {
+    format!("{key}.archive")
+}
+
+impl Archive {
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Whether the archive changed since this was last called, i.e. whether
+    /// it needs to be saved.
+    pub fn take_changed(&self) -> bool {
+        self.changed.replace(false)
+    }
+
+    fn push(&mut self, entry: TodoEntry, parent: Option<uuid::Uuid>, archived_at: Timestamp) {
+        self.entries.insert(0, ArchivedEntry { entry, archived_at, parent });
+        self.changed.set(true);
+    }
+
+    fn take(&mut self, id: &uuid::Uuid) -> Option<ArchivedEntry> {
+        let index = self.entries.iter().position(|e| e.entry.id == *id)?;
+        self.changed.set(true);
+        Some(self.entries.remove(index))
+    }
+
+    /// Remove the entries archived more than `retention_days` before `now`.
+    /// Returns how many were removed.
+    pub fn purge(&mut self, now: Timestamp) -> usize {
+        let days = match self.retention_days {
+            Some(days) => days as i64,
+            None => return 0,
+        };
+        let len = self.entries.len();
+        self.entries.retain(|e| now.0 - e.archived_at.0 < days * DAY);
+        let purged = len - self.entries.len();
+        if purged > 0 {
+            self.changed.set(true);
+        }
+        purged
+    }
+}
+
+impl Todos {
+    /// Move the entries that match `f` to the archive, at every depth, with
+    /// their subtasks. Returns how many top-level entries or subtrees were
+    /// archived.
+    pub(crate) fn archive_where(&mut self, f: &dyn Fn(&TodoEntry) -> bool) -> usize {
+        let mut archived = Vec::new();
+        subtask::drain_tree(&mut self.entries, None, f, &mut archived);
+        let count = archived.len();
+        let now = now();
+        // Keep the list order within one batch, most recent batch first
+        for (entry, parent) in archived.into_iter().rev() {
+            self.archive.push(entry, parent, now);
+        }
+        self.roll_up();
+        self.clean_up_dependencies();
+        count
+    }
+
+    /// Put an archived entry back where it was: under its parent if that is
+    /// still in the list, at the end of the list otherwise. Its dependencies
+    /// were removed when it was archived.
+    pub fn restore(&mut self, id: &uuid::Uuid) -> bool {
+        let archived = match self.archive.take(id) {
+            Some(archived) => archived,
+            None => return false,
+        };
+        let parent = archived.parent.and_then(|parent| self.get_entry_by_id_mut(&parent));
+        match parent {
+            Some(parent) => parent.children.push(archived.entry),
+            None => self.entries.push(archived.entry),
+        }
+        self.roll_up();
+        self.update_recurrences();
+        true
+    }
+
+    /// Delete an archived entry for good.
+    pub fn delete_archived(&mut self, id: &uuid::Uuid) -> bool {
+        self.archive.take(id).is_some()
+    }
+
+    /// Set how long archived entries are kept, and purge the ones that are
+    /// now too old.
+    pub fn set_retention_days(&mut self, days: Option<u32>) {
+        if self.archive.retention_days == days {
+            return;
+        }
+        self.archive.retention_days = days;
+        self.archive.changed.set(true);
+        self.archive.purge(now());
+    }
+}
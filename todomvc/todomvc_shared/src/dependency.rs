@@ -0,0 +1,315 @@
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{TodoEntry, Todos};
+
+/// Which entries block which: `blocked_by[a]` are the entries that must be
+/// completed before `a` can be.
+///
+/// An entry is completed through the entries without subtasks under it (its
+/// leaves), so "a is blocked by b" means every leaf of `a` waits for every
+/// leaf of `b`. Blocking a parent also blocks its subtasks.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Dependencies {
+    blocked_by: BTreeMap<Uuid, BTreeSet<Uuid>>,
+}
+
+impl Dependencies {
+    pub fn is_empty(&self) -> bool {
+        self.blocked_by.is_empty()
+    }
+
+    /// The entries that `id` is declared to be blocked by, completed or not.
+    pub fn blockers(&self, id: &Uuid) -> impl Iterator<Item = &Uuid> {
+        self.blocked_by.get(id).into_iter().flatten()
+    }
+
+    pub fn contains(&self, blocked: &Uuid, blocker: &Uuid) -> bool {
+        self.blocked_by.get(blocked).is_some_and(|blockers| blockers.contains(blocker))
+    }
+
+    fn insert(&mut self, blocked: Uuid, blocker: Uuid) {
+        self.blocked_by.entry(blocked).or_default().insert(blocker);
+    }
+
+    fn remove(&mut self, blocked: &Uuid, blocker: &Uuid) -> bool {
+        let blockers = match self.blocked_by.get_mut(blocked) {
+            Some(blockers) => blockers,
+            None => return false,
+        };
+        let removed = blockers.remove(blocker);
+        if blockers.is_empty() {
+            self.blocked_by.remove(blocked);
+        }
+        removed
+    }
+
+    /// Forget every link to or from an entry that `keep` rejects.
+    fn retain(&mut self, keep: impl Fn(&Uuid) -> bool) {
+        self.blocked_by.retain(|blocked, blockers| {
+            blockers.retain(&keep);
+            keep(blocked) && !blockers.is_empty()
+        });
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DependencyError {
+    UnknownEntry(Uuid),
+    SelfDependency,
+    /// A parent is completed through its subtasks, so neither can wait for
+    /// the other.
+    Nested { blocked: String, blocker: String },
+    /// The descriptions around the cycle: the entry that would be blocked,
+    /// the would-be blocker, then the existing blockers leading back.
+    Cycle(Vec<String>),
+    Blocked { description: String, blockers: Vec<String> },
+}
+
+impl fmt::Display for DependencyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DependencyError::UnknownEntry(id) => write!(f, "there is no todo with id {id}"),
+            DependencyError::SelfDependency => write!(f, "a todo cannot block itself"),
+            DependencyError::Nested { blocked, blocker } => {
+                write!(f, "\"{blocked}\" and \"{blocker}\" cannot block each other, one is a subtask of the other")
+            }
+            DependencyError::Cycle(descriptions) => {
+                write!(f, "this would create a cycle: \"{}\" would be blocked by \"{}\"", descriptions[0], descriptions[1])?;
+                for description in &descriptions[2..] {
+                    write!(f, ", which is blocked by \"{description}\"")?;
+                }
+                Ok(())
+            }
+            DependencyError::Blocked { description, blockers } => {
+                write!(f, "\"{description}\" is blocked by ")?;
+                for (index, blocker) in blockers.iter().enumerate() {
+                    let separator = if index == 0 { "" } else { ", " };
+                    write!(f, "{separator}\"{blocker}\"")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for DependencyError {}
+
+fn leaves(entry: &TodoEntry) -> impl Iterator<Item = &TodoEntry> {
+    entry.iter_tree().filter(|e| !e.has_subtasks())
+}
+
+impl Todos {
+    /// Make `blocked` wait for `blocker`. Rejected if it would make either
+    /// of them impossible to complete.
+    pub fn add_dependency(&mut self, blocked: &Uuid, blocker: &Uuid) -> Result<(), DependencyError> {
+        if blocked == blocker {
+            return Err(DependencyError::SelfDependency);
+        }
+        let blocked_path = self.path_to(blocked).ok_or(DependencyError::UnknownEntry(*blocked))?;
+        let blocker_path = self.path_to(blocker).ok_or(DependencyError::UnknownEntry(*blocker))?;
+        let blocked_entry = blocked_path[blocked_path.len() - 1];
+        let blocker_entry = blocker_path[blocker_path.len() - 1];
+        if blocked_path.iter().any(|e| e.id == *blocker) || blocker_path.iter().any(|e| e.id == *blocked) {
+            return Err(DependencyError::Nested {
+                blocked: blocked_entry.description.clone(),
+                blocker: blocker_entry.description.clone(),
+            });
+        }
+        if let Some(path) = self.blocking_path(blocker_entry, blocked_entry) {
+            let mut descriptions = vec![blocked_entry.description.clone(), blocker_entry.description.clone()];
+            descriptions.extend(path.into_iter().map(|e| e.description.clone()));
+            return Err(DependencyError::Cycle(descriptions));
+        }
+        self.dependencies.insert(*blocked, *blocker);
+        Ok(())
+    }
+
+    pub fn remove_dependency(&mut self, blocked: &Uuid, blocker: &Uuid) -> bool {
+        self.dependencies.remove(blocked, blocker)
+    }
+
+    /// The active entries that keep `id` from being completed: its own
+    /// blockers and those of its ancestors.
+    pub fn active_blockers(&self, id: &Uuid) -> Vec<&TodoEntry> {
+        if self.dependencies.is_empty() {
+            return Vec::new();
+        }
+        let path = self.path_to(id).unwrap_or_default();
+        let blocker_ids: BTreeSet<_> = path.iter().flat_map(|e| self.dependencies.blockers(&e.id)).collect();
+        self.iter_tree()
+            .filter(|e| !e.is_completed() && blocker_ids.contains(&e.id))
+            .collect()
+    }
+
+    pub fn is_blocked(&self, id: &Uuid) -> bool {
+        !self.active_blockers(id).is_empty()
+    }
+
+    /// Whether completing the entry, with its subtasks, is allowed now.
+    pub fn check_completion(&self, id: &Uuid) -> Result<(), DependencyError> {
+        let entry = self.get_entry_by_id(id).ok_or(DependencyError::UnknownEntry(*id))?;
+        let completing: BTreeSet<_> = leaves(entry).filter(|e| !e.is_completed()).map(|e| e.id).collect();
+        let blockers = self.unsatisfied_blockers(&completing);
+        if blockers.is_empty() {
+            return Ok(());
+        }
+        Err(DependencyError::Blocked {
+            description: entry.description.clone(),
+            blockers: blockers.into_iter().map(|e| e.description.clone()).collect(),
+        })
+    }
+
+    /// The top-level entries of `ids` that can be completed together:
+    /// those whose blockers are completed or among the others.
+    pub(crate) fn completable_together(&self, mut ids: Vec<Uuid>) -> Vec<Uuid> {
+        if self.dependencies.is_empty() {
+            return ids;
+        }
+        loop {
+            let entries: Vec<_> = ids.iter().filter_map(|id| self.get_entry_by_id(id)).collect();
+            let completing: BTreeSet<_> = entries
+                .iter()
+                .flat_map(|e| leaves(e))
+                .filter(|e| !e.is_completed())
+                .map(|e| e.id)
+                .collect();
+            let before = ids.len();
+            ids.retain(|id| {
+                let entry = self.get_entry_by_id(id);
+                let own: BTreeSet<_> = entry.into_iter().flat_map(leaves).map(|e| e.id).collect();
+                own.iter()
+                    .filter(|leaf| completing.contains(*leaf))
+                    .all(|leaf| self.leaf_blockers(leaf).iter().all(|b| self.is_satisfied(b, &completing)))
+            });
+            if ids.len() == before {
+                return ids;
+            }
+        }
+    }
+
+    /// The active entries without subtasks in an order in which they can be
+    /// done: every entry comes after what blocks it. Those that can be done
+    /// right now come first, in the order of the list.
+    pub fn next_entries(&self) -> Vec<&TodoEntry> {
+        let active: Vec<_> = self.iter_leaves().filter(|e| !e.is_completed()).collect();
+        let ids: BTreeSet<_> = active.iter().map(|e| e.id).collect();
+        let mut waiting_for: BTreeMap<Uuid, BTreeSet<Uuid>> = active
+            .iter()
+            .map(|e| {
+                let waits: BTreeSet<_> = self
+                    .leaf_dependencies(&e.id)
+                    .into_iter()
+                    .map(|(leaf, _)| leaf.id)
+                    .filter(|id| ids.contains(id))
+                    .collect();
+                (e.id, waits)
+            })
+            .collect();
+
+        // Kahn's algorithm, one layer at a time
+        let mut order = Vec::with_capacity(active.len());
+        while !waiting_for.is_empty() {
+            let ready: Vec<_> = active
+                .iter()
+                .filter(|e| waiting_for.get(&e.id).is_some_and(BTreeSet::is_empty))
+                .copied()
+                .collect();
+            if ready.is_empty() {
+                // Only possible with links added around `add_dependency`
+                order.extend(active.iter().filter(|e| waiting_for.contains_key(&e.id)).copied());
+                break;
+            }
+            for entry in &ready {
+                waiting_for.remove(&entry.id);
+            }
+            for waits in waiting_for.values_mut() {
+                for entry in &ready {
+                    waits.remove(&entry.id);
+                }
+            }
+            order.extend(ready);
+        }
+        order
+    }
+
+    /// Forget the links of removed entries.
+    pub(crate) fn clean_up_dependencies(&mut self) {
+        if self.dependencies.is_empty() {
+            return;
+        }
+        let ids: BTreeSet<_> = self.iter_tree().map(|e| e.id).collect();
+        self.dependencies.retain(|id| ids.contains(id));
+    }
+
+    // The declared blockers of a leaf, including those of its ancestors
+    fn leaf_blockers(&self, leaf: &Uuid) -> Vec<&TodoEntry> {
+        let path = self.path_to(leaf).unwrap_or_default();
+        path.iter()
+            .flat_map(|e| self.dependencies.blockers(&e.id))
+            .filter_map(|id| self.get_entry_by_id(id))
+            .collect()
+    }
+
+    // The leaves that a leaf waits for, each with the declared blocker it comes from
+    fn leaf_dependencies(&self, leaf: &Uuid) -> Vec<(&TodoEntry, &TodoEntry)> {
+        self.leaf_blockers(leaf)
+            .into_iter()
+            .flat_map(|blocker| leaves(blocker).map(move |e| (e, blocker)))
+            .collect()
+    }
+
+    // A blocker is satisfied when all of its leaves are completed or being completed
+    fn is_satisfied(&self, blocker: &TodoEntry, completing: &BTreeSet<Uuid>) -> bool {
+        leaves(blocker).all(|e| e.is_completed() || completing.contains(&e.id))
+    }
+
+    // In the order of the list
+    fn unsatisfied_blockers(&self, completing: &BTreeSet<Uuid>) -> Vec<&TodoEntry> {
+        let ids: BTreeSet<_> = completing
+            .iter()
+            .flat_map(|leaf| self.leaf_blockers(leaf))
+            .filter(|blocker| !self.is_satisfied(blocker, completing))
+            .map(|blocker| blocker.id)
+            .collect();
+        self.iter_tree().filter(|e| ids.contains(&e.id)).collect()
+    }
+
+    // The declared blockers leading from a leaf of `from` to a leaf of `to`,
+    // if `from` already waits for `to`
+    fn blocking_path<'a>(&'a self, from: &'a TodoEntry, to: &TodoEntry) -> Option<Vec<&'a TodoEntry>> {
+        if self.dependencies.is_empty() {
+            return None;
+        }
+        let targets: BTreeSet<_> = leaves(to).map(|e| e.id).collect();
+        let mut came_from: BTreeMap<Uuid, Option<(Uuid, &TodoEntry)>> = BTreeMap::new();
+        let mut queue = VecDeque::new();
+        for leaf in leaves(from) {
+            came_from.insert(leaf.id, None);
+            queue.push_back(leaf.id);
+        }
+        while let Some(leaf) = queue.pop_front() {
+            for (next, blocker) in self.leaf_dependencies(&leaf) {
+                if came_from.contains_key(&next.id) {
+                    continue;
+                }
+                came_from.insert(next.id, Some((leaf, blocker)));
+                if targets.contains(&next.id) {
+                    let mut path = Vec::new();
+                    let mut current = next.id;
+                    while let Some(Some((previous, blocker))) = came_from.get(&current) {
+                        path.push(*blocker);
+                        current = *previous;
+                    }
+                    path.reverse();
+                    return Some(path);
+                }
+                queue.push_back(next.id);
+            }
+        }
+        None
+    }
+}
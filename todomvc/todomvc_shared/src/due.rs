@@ -0,0 +1,53 @@
+use crate::{Date, TodoEntry};
+
+/// Where a due date is relative to the user's local today.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DueStatus {
+    Overdue,
+    Today,
+    Upcoming,
+}
+
+impl DueStatus {
+    pub fn of(due: Date, today: Date) -> Self {
+        match due.cmp(&today) {
+            std::cmp::Ordering::Less => DueStatus::Overdue,
+            std::cmp::Ordering::Equal => DueStatus::Today,
+            std::cmp::Ordering::Greater => DueStatus::Upcoming,
+        }
+    }
+}
+
+impl TodoEntry {
+    pub fn due_status(&self, today: Date) -> Option<DueStatus> {
+        self.due.map(|due| DueStatus::of(due, today))
+    }
+
+    /// A completed entry is never overdue.
+    pub fn is_overdue(&self, today: Date) -> bool {
+        !self.is_completed() && self.due_status(today) == Some(DueStatus::Overdue)
+    }
+
+    pub fn set_due(&mut self, due: Option<Date>) {
+        if self.due == due {
+            return;
+        }
+        self.due = due;
+        self.updated_at = crate::now();
+    }
+
+    /// E.g. "due tomorrow", "due in 3 days", "2 days overdue", "due 2026-01-31".
+    pub fn due_label(&self, today: Date) -> Option<String> {
+        let due = self.due?;
+        let days = due.days_since_epoch() - today.days_since_epoch();
+        let label = match days {
+            0 => "due today".to_string(),
+            1 => "due tomorrow".to_string(),
+            2..=6 => format!("due in {days} days"),
+            -1 if !self.is_completed() => "1 day overdue".to_string(),
+            -6..=-2 if !self.is_completed() => format!("{} days overdue", -days),
+            _ => format!("due {due}"),
+        };
+        Some(label)
+    }
+}
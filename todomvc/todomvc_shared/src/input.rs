@@ -0,0 +1,135 @@
+use std::collections::BTreeSet;
+
+use crate::{parse_recurrence, parse_tag, Date, Priority, Recurrence};
+
+/// What the user typed in the new-todo input, e.g.
+/// "pay rent tomorrow !high #home", split into its parts.
+///
+/// Recognised words are removed from the description:
+/// - a due date: `today`, `tomorrow`, `next monday` (the first Monday after
+///   today), `next week` (next Monday), `in 3 days`, `in a week`,
+///   `2026-01-31`, each optionally preceded by `due`
+/// - a priority marker, see `Priority::from_marker`
+/// - a recurrence, see `parse_recurrence`. A recurring todo without a due
+///   date is due on its first occurrence, see `Recurrence::first`
+/// - `#tags`, see `parse_tag`
+///
+/// Only the first date, recurrence and priority are taken, later ones stay in
+/// the description. An input made of recognised words only is kept as the
+/// description, so that the entry still has something to show.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParsedInput {
+    pub description: String,
+    pub tags: BTreeSet<String>,
+    pub due: Option<Date>,
+    pub priority: Priority,
+    pub recurrence: Option<Recurrence>,
+}
+
+impl ParsedInput {
+    pub fn parse(text: &str, today: Date) -> Self {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let mut kept = Vec::new();
+        let mut tags = BTreeSet::new();
+        let mut due = None;
+        let mut priority = None;
+        let mut recurrence = None;
+        let mut i = 0;
+        while i < words.len() {
+            if due.is_none() {
+                if let Some((date, len)) = parse_date(&words[i..], today) {
+                    due = Some(date);
+                    i += len;
+                    continue;
+                }
+            }
+            if recurrence.is_none() {
+                if let Some((rule, len)) = parse_recurrence(&words[i..]) {
+                    recurrence = Some(rule);
+                    i += len;
+                    continue;
+                }
+            }
+            if priority.is_none() {
+                if let Some(marker) = Priority::from_marker(words[i]) {
+                    priority = Some(marker);
+                    i += 1;
+                    continue;
+                }
+            }
+            match parse_tag(words[i]) {
+                Some(tag) => {
+                    tags.insert(tag);
+                }
+                None => kept.push(words[i]),
+            }
+            i += 1;
+        }
+        let description = match kept.is_empty() {
+            true => text.trim().to_string(),
+            false => kept.join(" "),
+        };
+        if let Some(rule) = recurrence {
+            let first = *due.get_or_insert_with(|| rule.first(today));
+            recurrence = Some(rule.anchored(first));
+        }
+        Self {
+            description,
+            tags,
+            due,
+            priority: priority.unwrap_or_default(),
+            recurrence,
+        }
+    }
+}
+
+/// A date expression at the start of `words`, and the number of words it
+/// takes. Matching is case-insensitive.
+pub fn parse_date(words: &[&str], today: Date) -> Option<(Date, usize)> {
+    let lower: Vec<String> = words.iter().take(3).map(|word| word.to_lowercase()).collect();
+    let word = |i: usize| lower.get(i).map(String::as_str);
+    match (word(0)?, word(1), word(2)) {
+        ("due", ..) => {
+            let (date, len) = parse_date(&words[1..], today)?;
+            Some((date, len + 1))
+        }
+        ("today", ..) => Some((today, 1)),
+        ("tomorrow", ..) => Some((today.add_days(1), 1)),
+        ("next", Some("week"), _) => Some((next_weekday(today, 0), 2)),
+        ("next", Some(day), _) => Some((next_weekday(today, parse_weekday(day)?), 2)),
+        ("in", Some(count), Some(unit)) => {
+            let count: i64 = match count {
+                "a" | "an" => 1,
+                // Anything longer than ten years is more likely not a date
+                count => count.parse().ok().filter(|count| (1..=3650).contains(count))?,
+            };
+            let days = match unit {
+                "day" | "days" => count,
+                "week" | "weeks" => count * 7,
+                _ => return None,
+            };
+            Some((today.add_days(days), 3))
+        }
+        (iso, ..) => Some((Date::parse_iso(iso)?, 1)),
+    }
+}
+
+/// 0 is Monday, like `Date::weekday`.
+pub(crate) fn parse_weekday(name: &str) -> Option<u8> {
+    match name {
+        "mon" | "monday" => Some(0),
+        "tue" | "tues" | "tuesday" => Some(1),
+        "wed" | "wednesday" => Some(2),
+        "thu" | "thur" | "thurs" | "thursday" => Some(3),
+        "fri" | "friday" => Some(4),
+        "sat" | "saturday" => Some(5),
+        "sun" | "sunday" => Some(6),
+        _ => None,
+    }
+}
+
+// The first `weekday` strictly after `today`.
+pub(crate) fn next_weekday(today: Date, weekday: u8) -> Date {
+    let days = (weekday as i64 - today.weekday() as i64 - 1).rem_euclid(7) + 1;
+    today.add_days(days)
+}
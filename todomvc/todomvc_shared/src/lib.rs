@@ -1,33 +1,144 @@
+use std::collections::BTreeSet;
+
 use serde::{Deserialize, Serialize};
 use strum::{Display, EnumIter};
 use gloo_storage::{LocalStorage, Storage, errors::StorageError};
 
+mod archive;
+mod date;
+mod dependency;
+mod due;
+mod input;
+mod lists;
+mod priority;
+mod query;
+mod recurrence;
+mod route;
+mod search;
+mod sort;
+mod status;
+mod subtask;
+mod tag;
+mod time;
+pub use archive::*;
+pub use date::*;
+pub use dependency::*;
+pub use due::*;
+pub use input::*;
+pub use lists::*;
+pub use priority::*;
+pub use query::*;
+pub use recurrence::*;
+pub use route::*;
+pub use search::*;
+pub use sort::*;
+pub use status::*;
+pub use subtask::*;
+pub use tag::*;
+pub use time::*;
+
+/// Version of the persisted `Todos` format, see `Todos::migrate`.
+pub const SCHEMA_VERSION: u32 = 7;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Todos {
     pub entries: Vec<TodoEntry>,
     pub filter: Filter,
+    #[serde(default)]
+    pub sort: SortOrder,
+    #[serde(default)]
+    pub query: String,
+    /// Only show the entries with this tag.
+    #[serde(default)]
+    pub tag: Option<String>,
+    #[serde(default)]
+    pub dependencies: Dependencies,
+    /// The columns of the board between `InProgress` and `Done`, see
+    /// `Todos::statuses`.
+    #[serde(default)]
+    pub custom_columns: Vec<String>,
+    #[serde(default)]
+    pub schema_version: u32,
+    /// Cleared and removed entries, saved separately, see `Archive`.
+    #[serde(skip)]
+    pub archive: Archive,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TodoEntry {
     pub id: uuid::Uuid,
     pub description: String,
-    pub completed: bool,
+    /// Data saved before version 6 has a `completed` bool instead, which
+    /// `status::deserialize` turns into `Todo` or `Done`.
+    #[serde(default, alias = "completed", deserialize_with = "status::deserialize")]
+    pub status: Status,
+    #[serde(default)]
+    pub created_at: Timestamp,
+    #[serde(default)]
+    pub updated_at: Timestamp,
+    #[serde(default)]
+    pub completed_at: Option<Timestamp>,
+    /// Lowercase, without the leading `#`.
+    #[serde(default)]
+    pub tags: BTreeSet<String>,
+    #[serde(default)]
+    pub due: Option<Date>,
+    #[serde(default)]
+    pub priority: Priority,
+    /// Subtasks, nested to any depth. An entry with subtasks is complete
+    /// when all of them are, see `Todos::set_completed`.
+    #[serde(default)]
+    pub children: Vec<TodoEntry>,
+    /// Hide the subtasks.
+    #[serde(default)]
+    pub collapsed: bool,
+    /// Only top-level entries recur, see `Todos::update_recurrences`.
+    #[serde(default)]
+    pub recurrence: Option<Recurrence>,
+    /// The entry added when this one was completed, which took the
+    /// recurrence over.
+    #[serde(default)]
+    pub next_occurrence: Option<uuid::Uuid>,
 }
 
-#[derive(Clone, Copy, Debug, EnumIter, Display, PartialEq, Serialize, Deserialize, Eq)]
+#[derive(Clone, Copy, Debug, Default, EnumIter, Display, PartialEq, Serialize, Deserialize, Eq)]
 pub enum Filter {
+    #[default]
     All,
     Active,
     Completed,
+    /// Active entries with a due date before today.
+    Overdue,
+    #[strum(to_string = "Due today")]
+    DueToday,
+    /// Entries due after today.
+    Upcoming,
+    /// Active entries in the order they can be done, see `Todos::next_entries`.
+    Next,
+    /// Every entry, in one column per status, see `Todos::board`.
+    Board,
+    /// The archived entries instead of the list, see `Todos::archive`.
+    Archive,
 }
 
 impl Filter {
     pub fn fits(&self, entry: &TodoEntry) -> bool {
         match *self {
-            Filter::All => true,
-            Filter::Active => !entry.completed,
-            Filter::Completed => entry.completed,
+            Filter::All | Filter::Board | Filter::Archive => true,
+            Filter::Active | Filter::Next => !entry.is_completed(),
+            Filter::Completed => entry.is_completed(),
+            _ => self.fits_on(entry, Date::today()),
+        }
+    }
+
+    /// Like `fits`, for when `today` is already known, e.g. when filtering
+    /// a whole list.
+    pub fn fits_on(&self, entry: &TodoEntry, today: Date) -> bool {
+        match *self {
+            Filter::Overdue => entry.is_overdue(today),
+            Filter::DueToday => entry.due_status(today) == Some(DueStatus::Today),
+            Filter::Upcoming => entry.due_status(today) == Some(DueStatus::Upcoming),
+            _ => self.fits(entry),
         }
     }
 
@@ -36,101 +147,377 @@ impl Filter {
             Filter::All => "#/",
             Filter::Active => "#/active",
             Filter::Completed => "#/completed",
+            Filter::Overdue => "#/overdue",
+            Filter::DueToday => "#/today",
+            Filter::Upcoming => "#/upcoming",
+            Filter::Next => "#/next",
+            Filter::Board => "#/board",
+            Filter::Archive => "#/archive",
         }
     }
 }
 
 impl TodoEntry {
-    pub fn toggle_completion(&mut self) {
-        self.completed = !self.completed;
+    /// An entry with `description` as it is, nothing is parsed out of it.
+    pub fn new(description: &str) -> Self {
+        let now = now();
+        Self {
+            id: uuid::Uuid::new_v4(),
+            description: description.to_string(),
+            status: Status::Todo,
+            created_at: now,
+            updated_at: now,
+            completed_at: None,
+            tags: BTreeSet::new(),
+            due: None,
+            priority: Priority::None,
+            children: Vec::new(),
+            collapsed: false,
+            recurrence: None,
+            next_occurrence: None,
+        }
+    }
+
+    /// An entry from what the user typed: tags, a due date, a recurrence and
+    /// a priority are taken out of `input`, see `ParsedInput`.
+    pub fn parsed(input: &str) -> Self {
+        let input = ParsedInput::parse(input, Date::today());
+        Self {
+            tags: input.tags,
+            due: input.due,
+            priority: input.priority,
+            recurrence: input.recurrence,
+            ..Self::new(&input.description)
+        }
+    }
+
+    /// Complete or reopen the entry and all of its subtasks. The parent is
+    /// not updated, see `Todos::set_completed`.
+    pub(crate) fn set_completed(&mut self, completed: bool) {
+        self.mark_completed(completed);
+        for child in self.children.iter_mut() {
+            child.set_completed(completed);
+        }
+    }
+
+    // Without touching the subtasks. Reopening leaves an active status as it is.
+    fn mark_completed(&mut self, completed: bool) {
+        if self.is_completed() != completed {
+            self.mark_status(if completed { Status::Done } else { Status::Todo });
+        }
+    }
+
+    // Without touching the subtasks
+    fn mark_status(&mut self, status: Status) {
+        if self.status == status {
+            return;
+        }
+        let now = now();
+        self.completed_at = status.is_done().then_some(now);
+        self.status = status;
+        self.updated_at = now;
+    }
+
+    pub fn set_description(&mut self, description: String) {
+        if self.description == description {
+            return;
+        }
+        self.description = description;
+        self.updated_at = now();
+    }
+
+    pub fn set_priority(&mut self, priority: Priority) {
+        if self.priority == priority {
+            return;
+        }
+        self.priority = priority;
+        self.updated_at = now();
+    }
+
+    pub fn cycle_priority(&mut self) {
+        self.set_priority(self.priority.next());
+    }
+
+    /// Describe the latest change to the entry, e.g. "completed 3 hours ago".
+    pub fn relative_time_label(&self, now: Timestamp) -> String {
+        match self.completed_at {
+            Some(completed_at) => format!("completed {}", relative_time(completed_at, now)),
+            None if self.updated_at > self.created_at => {
+                format!("updated {}", relative_time(self.updated_at, now))
+            }
+            None => format!("created {}", relative_time(self.created_at, now)),
+        }
+    }
+}
+
+/// An empty list, e.g. a new one, see `TodoLists::create`.
+impl Default for Todos {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            filter: Filter::All,
+            sort: SortOrder::Insertion,
+            query: String::new(),
+            tag: None,
+            dependencies: Dependencies::default(),
+            custom_columns: Vec::new(),
+            schema_version: SCHEMA_VERSION,
+            archive: Archive::default(),
+        }
     }
 }
 
 impl Todos {
+    /// Load the list saved under `key`, and its archive, purged of the
+    /// entries that are past their retention.
     pub fn load_todos(key: &str) -> Self {
-        match LocalStorage::get(key) {
-            Ok(todos) => todos,
-            Err(_) => Self {
-                entries: Vec::new(),
-                filter: Filter::All,
+        let mut todos = match LocalStorage::get(key) {
+            Ok(mut todos) => {
+                Self::migrate(&mut todos);
+                todos
             }
+            Err(_) => Self::default(),
+        };
+        if let Ok(archive) = LocalStorage::get(archive_key(key)) {
+            todos.archive = archive;
         }
+        todos.archive.purge(now());
+        todos
     }
 
+    /// Bring data saved by an older version of the app up to `SCHEMA_VERSION`.
+    pub fn migrate(&mut self) {
+        if self.schema_version < 1 {
+            // Timestamps were not recorded before version 1, the best we can do is "now"
+            let now = now();
+            for entry in self.entries.iter_mut() {
+                entry.created_at = now;
+                entry.updated_at = now;
+                entry.completed_at = entry.is_completed().then_some(now);
+            }
+        }
+        if self.schema_version < 2 {
+            // Tags were written in descriptions before they had their own field
+            for entry in self.entries.iter_mut() {
+                entry.tags = entry
+                    .description
+                    .split_whitespace()
+                    .filter_map(parse_tag)
+                    .collect();
+            }
+        }
+        // Version 3 added `TodoEntry::priority`, older entries get `Priority::None`
+        // from `#[serde(default)]`. Version 4 added `TodoEntry::children` and
+        // `collapsed` the same way, version 5 added `Todos::dependencies`. Version 6
+        // replaced `completed` with `status`, see `TodoEntry::status`. Version 7
+        // added `TodoEntry::recurrence` and `next_occurrence`.
+        self.schema_version = SCHEMA_VERSION;
+    }
+
+    /// The archive is only saved when it changed.
     pub fn save_todos(&self, key: &str) -> Result<(), StorageError> {
+        if self.archive.take_changed() {
+            LocalStorage::set(archive_key(key), &self.archive)?;
+        }
         LocalStorage::set(key, self)
     }
 
+    /// Add a top-level entry with `description` as it is.
     pub fn new_entry(&mut self, description: String) {
-        self.entries.push(TodoEntry {
-            id: uuid::Uuid::new_v4(),
-            description,
-            completed: false,
-        });
+        self.entries.push(TodoEntry::new(&description));
     }
 
+    /// Add a top-level entry from what the user typed, see `TodoEntry::parsed`.
+    pub fn new_entry_parsed(&mut self, input: String) {
+        self.entries.push(TodoEntry::parsed(&input));
+    }
+
+    /// Counts only entries without subtasks, see `iter_leaves`: a todo with
+    /// three subtasks counts as three.
     pub fn entry_count(&self) -> usize {
-        self.entries.len()
+        self.iter_leaves().count()
     }
 
     pub fn completed_count(&self) -> usize {
-        self.entries
-            .iter()
-            .filter(|e| e.completed)
+        self.iter_leaves()
+            .filter(|e| e.is_completed())
             .count()
     }
 
     pub fn is_all_completed(&self) -> bool {
         let has_active_todo = self
-            .entries
-            .iter()
-            .any(|e| !e.completed);
+            .iter_leaves()
+            .any(|e| !e.is_completed());
         !has_active_todo
     }
 
+    /// Move the completed entries to the archive, at every depth. A parent
+    /// is only complete when all of its subtasks are, so the remaining
+    /// parents keep at least one active subtask. Completed recurring entries
+    /// already handed their recurrence to their next occurrence, which stays.
     pub fn clear_completed(&mut self) {
-        self
-            .entries
-            .retain(|e| !e.completed);
+        self.archive_where(&|e| e.is_completed());
+    }
+
+    /// Entry counts of every tag, regardless of the current view.
+    pub fn tag_index(&self) -> TagIndex {
+        TagIndex::new(self.iter_tree().map(|e| &e.tags))
     }
 
+    /// Searches subtasks too.
     pub fn get_entry_by_id_mut(&mut self, id: &uuid::Uuid) -> Option<&mut TodoEntry> {
-        self.entries.iter_mut().find(|e| e.id == *id)
+        subtask::find_mut(&mut self.entries, id)
+    }
+
+    pub fn parse_query(&self) -> Result<Query, QueryError> {
+        Query::parse(&self.query)
+    }
+
+    /// The words of the query to highlight in the visible entries.
+    pub fn search(&self) -> Search {
+        self.parse_query().map(|query| query.highlight()).unwrap_or_default()
+    }
+
+    /// The status and tag filters combined with the query. A query that
+    /// does not parse filters nothing, the view shows the error instead.
+    pub fn predicate(&self) -> Predicate {
+        Predicate {
+            filter: self.filter,
+            today: Date::today(),
+            tag: self.tag.clone(),
+            query: self.parse_query().unwrap_or_default(),
+        }
     }
 
+    /// The top-level entries in the current view, see `Predicate::fits_tree`.
     pub fn get_filtered_entries(&self) -> impl Iterator<Item = &TodoEntry> {
-        let filter = self.filter;
-        self.entries.iter().filter(move |e| filter.fits(e))
+        let predicate = self.predicate();
+        self.entries.iter().filter(move |e| predicate.fits_tree(e))
+    }
+
+    /// The entries to render: filtered, then sorted by `self.sort`. The
+    /// underlying order of `entries` is left untouched.
+    ///
+    /// `Filter::Next` is a flat list of entries without subtasks, in the
+    /// order given by the dependencies instead of `self.sort`.
+    pub fn get_visible_entries(&self) -> Vec<&TodoEntry> {
+        if self.filter == Filter::Next {
+            let predicate = self.predicate();
+            return self.next_entries().into_iter().filter(|e| predicate.fits(e)).collect();
+        }
+        let mut entries: Vec<_> = self.get_filtered_entries().collect();
+        self.sort.sort(&mut entries);
+        entries
+    }
+
+    pub fn route(&self) -> Route {
+        Route {
+            list: None,
+            filter: self.filter,
+            sort: self.sort,
+            tag: self.tag.clone(),
+            query: self.query.clone(),
+        }
+    }
+
+    pub fn set_route(&mut self, route: Route) {
+        self.filter = route.filter;
+        self.sort = route.sort;
+        self.tag = route.tag;
+        self.query = route.query;
     }
 
     pub fn get_filtered_entries_mut(&mut self) -> impl Iterator<Item = &mut TodoEntry> {
-        let filter = self.filter;
-        self.entries.iter_mut().filter(move |e| filter.fits(e))
+        let predicate = self.predicate();
+        self.entries.iter_mut().filter(move |e| predicate.fits_tree(e))
     }
 
     pub fn get_filtered_entry_mut(&mut self, index: usize) -> Option<&mut TodoEntry> {
         self.get_filtered_entries_mut().nth(index)
     }
 
+    /// Position of the top-level entry in the current filtered view.
+    pub fn filtered_position(&self, id: &uuid::Uuid) -> Option<usize> {
+        self.get_filtered_entries().position(|e| e.id == *id)
+    }
+
+    /// Move an entry to `new_position` in the current filtered view. Entries
+    /// hidden by the filter keep their places relative to each other: the
+    /// moved entry lands just before the visible entry that currently holds
+    /// `new_position`, or just after the last visible entry. A position
+    /// outside of the view moves nothing.
+    ///
+    /// Manual order only makes sense in `SortOrder::Insertion`, in other
+    /// orders this does nothing. Only top-level entries can be moved, their
+    /// subtasks move with them. Nor can entries be moved in `Filter::Next`.
+    pub fn move_entry(&mut self, id: &uuid::Uuid, new_position: usize) -> bool {
+        if self.sort != SortOrder::Insertion
+            || self.filter == Filter::Next
+            || new_position >= self.get_filtered_entries().count()
+        {
+            return false;
+        }
+        let old_index = match self.entries.iter().position(|e| e.id == *id) {
+            Some(index) => index,
+            None => return false,
+        };
+        let entry = self.entries.remove(old_index);
+        let predicate = self.predicate();
+        let mut visible = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| predicate.fits_tree(e))
+            .map(|(index, _)| index);
+        let new_index = match visible.nth(new_position) {
+            Some(index) => index,
+            None => match self.entries.iter().rposition(|e| predicate.fits_tree(e)) {
+                Some(last_visible) => last_visible + 1,
+                None => old_index,
+            },
+        };
+        self.entries.insert(new_index, entry);
+        new_index != old_index
+    }
+
+    /// Entries that are blocked by entries outside of the view stay active.
+    /// Recurring entries get their next occurrences, which stay active, and
+    /// reopening all of them again removes those, see `update_recurrences`.
     pub fn set_completed_for_all(&mut self, completed: bool) {
-        for entry in &mut self.get_filtered_entries_mut() {
-            entry.completed = completed;
+        if !completed {
+            for entry in &mut self.get_filtered_entries_mut() {
+                entry.set_completed(false);
+            }
+        } else {
+            let ids = self.get_filtered_entries().map(|e| e.id).collect();
+            for id in self.completable_together(ids) {
+                if let Some(entry) = self.get_entry_by_id_mut(&id) {
+                    entry.set_completed(true);
+                }
+            }
         }
+        self.update_recurrences();
     }
 
+    /// Moves the entry to the archive with its subtasks, the parent may
+    /// become complete. Entries that were blocked by the removed ones are
+    /// not anymore.
     pub fn remove_by_id(&mut self, id: &uuid::Uuid) {
-        self.entries.retain(|e| e.id != *id);
+        self.archive_where(&|e| e.id == *id);
+        self.update_recurrences();
     }
 
     pub fn remove_by_index(&mut self, index: usize) {
+        let predicate = self.predicate();
         let actual_index = match self.entries
             .iter()
             .enumerate()
-            .filter(|e| self.filter.fits(e.1))
+            .filter(|e| predicate.fits_tree(e.1))
             .nth(index) {
                 None => return,
                 Some(ev) => ev.0,
         };
-        self.entries.remove(actual_index);
+        let id = self.entries[actual_index].id;
+        self.remove_by_id(&id);
     }
 }
@@ -0,0 +1,243 @@
+use std::cell::RefCell;
+use std::ops::{Deref, DerefMut};
+
+use gloo_storage::{errors::StorageError, LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{archive_key, Route, Todos};
+
+/// Name of the list made for data saved before there were lists.
+pub const DEFAULT_LIST_NAME: &str = "Todos";
+
+/// Named todo lists, e.g. Work, Home and Groceries, one of which is shown.
+///
+/// `T` is what a list holds, `Todos` for the implementations that use it.
+/// The others keep their own model and use this for the names, the
+/// current list, the routes and the storage keys.
+///
+/// Every list is saved under its own `TodoList::storage_key`, and the
+/// names under `lists_key`. Derefs to the current list.
+#[derive(Debug)]
+pub struct TodoLists<T = Todos> {
+    /// In switcher order, never empty.
+    pub lists: Vec<TodoList<T>>,
+    current: usize,
+    key: String,
+    // Storage keys of the deleted lists, removed by `save_index`
+    deleted: RefCell<Vec<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(bound(serialize = "", deserialize = "T: Default"))]
+pub struct TodoList<T> {
+    pub id: Uuid,
+    pub name: String,
+    /// The first list is saved under the key of the whole app, so that data
+    /// saved before there were lists is its data.
+    pub storage_key: String,
+    /// Saved under `storage_key`, not with the names.
+    #[serde(skip)]
+    pub todos: T,
+}
+
+/// The storage key of the names of the lists of the app saved under `key`.
+pub fn lists_key(key: &str) -> String {
+    format!("{key}.lists")
+}
+
+impl<T: Default> TodoLists<T> {
+    /// A single list named `DEFAULT_LIST_NAME` holding `todos`, saved under
+    /// `key`.
+    pub fn new(key: &str, todos: T) -> Self {
+        Self {
+            lists: vec![TodoList {
+                id: Uuid::new_v4(),
+                name: DEFAULT_LIST_NAME.to_string(),
+                storage_key: key.to_string(),
+                todos,
+            }],
+            current: 0,
+            key: key.to_string(),
+            deleted: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// The names of the lists of the app saved under `key`, with empty
+    /// lists: loading them is up to the implementation. The first list is
+    /// current.
+    pub fn load_index(key: &str) -> Self {
+        let mut lists = Self::new(key, T::default());
+        match LocalStorage::get::<Vec<TodoList<T>>>(lists_key(key)) {
+            Ok(saved) if !saved.is_empty() => lists.lists = saved,
+            _ => {}
+        }
+        lists
+    }
+
+    /// Save the names, and remove the data of the deleted lists.
+    pub fn save_index(&self) -> Result<(), StorageError> {
+        for storage_key in self.take_deleted() {
+            LocalStorage::delete(archive_key(&storage_key));
+            LocalStorage::delete(storage_key);
+        }
+        LocalStorage::set(lists_key(&self.key), &self.lists)
+    }
+
+    /// The storage keys of the lists deleted since this was last called.
+    pub fn take_deleted(&self) -> Vec<String> {
+        self.deleted.take()
+    }
+
+    pub fn current(&self) -> &TodoList<T> {
+        &self.lists[self.current]
+    }
+
+    pub fn current_mut(&mut self) -> &mut TodoList<T> {
+        &mut self.lists[self.current]
+    }
+
+    pub fn get(&self, id: &Uuid) -> Option<&TodoList<T>> {
+        self.lists.iter().find(|list| list.id == *id)
+    }
+
+    /// Names are trimmed, and must differ from the other lists regardless
+    /// of case.
+    fn check_name(&self, name: &str, renamed: Option<&Uuid>) -> Option<String> {
+        let name = name.trim();
+        let taken = self
+            .lists
+            .iter()
+            .filter(|list| Some(&list.id) != renamed)
+            .any(|list| list.name.to_lowercase() == name.to_lowercase());
+        (!name.is_empty() && !taken).then(|| name.to_string())
+    }
+
+    /// Add an empty list at the end and switch to it.
+    pub fn create(&mut self, name: &str) -> Option<Uuid> {
+        let name = self.check_name(name, None)?;
+        let id = Uuid::new_v4();
+        self.lists.push(TodoList {
+            id,
+            name,
+            storage_key: format!("{}.list.{id}", self.key),
+            todos: T::default(),
+        });
+        self.current = self.lists.len() - 1;
+        Some(id)
+    }
+
+    pub fn rename(&mut self, id: &Uuid, name: &str) -> bool {
+        let name = match self.check_name(name, Some(id)) {
+            Some(name) => name,
+            None => return false,
+        };
+        match self.lists.iter_mut().find(|list| list.id == *id) {
+            Some(list) => {
+                list.name = name;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Delete a list with its todos, the last one cannot be. Deleting the
+    /// current list switches to the first one.
+    pub fn delete(&mut self, id: &Uuid) -> bool {
+        let index = match self.lists.iter().position(|list| list.id == *id) {
+            Some(index) if self.lists.len() > 1 => index,
+            _ => return false,
+        };
+        let current_id = self.current().id;
+        let list = self.lists.remove(index);
+        self.deleted.borrow_mut().push(list.storage_key);
+        self.current = self.lists.iter().position(|list| list.id == current_id).unwrap_or(0);
+        true
+    }
+
+    pub fn switch(&mut self, id: &Uuid) -> bool {
+        match self.lists.iter().position(|list| list.id == *id) {
+            Some(index) => {
+                self.current = index;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The list of a route: `None` is the first list, see `Route::list`.
+    pub fn route_list(&self) -> Option<Uuid> {
+        match self.current {
+            0 => None,
+            _ => Some(self.current().id),
+        }
+    }
+
+    /// Switch to the list of a route, the first one for `None` or a list
+    /// that does not exist.
+    pub fn switch_to_route(&mut self, list: Option<Uuid>) {
+        if !list.is_some_and(|id| self.switch(&id)) {
+            self.current = 0;
+        }
+    }
+
+    /// The route to show a list, keeping the rest of `route`.
+    pub fn route_to(&self, id: &Uuid, route: &Route) -> Route {
+        let list = self.lists.iter().position(|list| list.id == *id).filter(|index| *index > 0);
+        route.with_list(list.map(|_| *id))
+    }
+}
+
+impl TodoList<Todos> {
+    pub fn entry_count(&self) -> usize {
+        self.todos.entry_count()
+    }
+
+    pub fn active_count(&self) -> usize {
+        self.todos.entry_count() - self.todos.completed_count()
+    }
+}
+
+impl TodoLists<Todos> {
+    /// Every list saved under `key`, see `Todos::load_todos`. Data saved
+    /// before there were lists is the first list.
+    pub fn load_todos(key: &str) -> Self {
+        let mut lists = Self::load_index(key);
+        for list in lists.lists.iter_mut() {
+            list.todos = Todos::load_todos(&list.storage_key);
+        }
+        lists
+    }
+
+    /// Save the names and the current list, the only one that changes.
+    pub fn save(&self) -> Result<(), StorageError> {
+        self.save_index()?;
+        let current = self.current();
+        current.todos.save_todos(&current.storage_key)
+    }
+
+    /// The route of the current list.
+    pub fn route(&self) -> Route {
+        self.current().todos.route().with_list(self.route_list())
+    }
+
+    /// Switch to the list of `route`, then show it like `Todos::set_route`.
+    pub fn set_route(&mut self, route: Route) {
+        self.switch_to_route(route.list);
+        self.current_mut().todos.set_route(route);
+    }
+}
+
+impl<T> Deref for TodoLists<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.lists[self.current].todos
+    }
+}
+
+impl<T> DerefMut for TodoLists<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.lists[self.current].todos
+    }
+}
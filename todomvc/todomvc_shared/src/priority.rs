@@ -0,0 +1,86 @@
+use serde::{Deserialize, Serialize};
+use strum::EnumIter;
+
+#[derive(
+    Clone, Copy, Debug, Default, EnumIter, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub enum Priority {
+    #[default]
+    None,
+    Low,
+    Medium,
+    High,
+}
+
+impl Priority {
+    /// The priority after a click on the priority button: None, Low, Medium,
+    /// High, then None again.
+    pub fn next(&self) -> Self {
+        match self {
+            Priority::None => Priority::Low,
+            Priority::Low => Priority::Medium,
+            Priority::Medium => Priority::High,
+            Priority::High => Priority::None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Priority::None => "None",
+            Priority::Low => "Low",
+            Priority::Medium => "Medium",
+            Priority::High => "High",
+        }
+    }
+
+    /// Shown on the priority button.
+    pub fn marker(&self) -> &'static str {
+        match self {
+            Priority::None => "-",
+            Priority::Low => "!",
+            Priority::Medium => "!!",
+            Priority::High => "!!!",
+        }
+    }
+
+    /// Class of the `li` of an entry with this priority.
+    pub fn class_name(&self) -> Option<&'static str> {
+        match self {
+            Priority::None => None,
+            Priority::Low => Some("priority-low"),
+            Priority::Medium => Some("priority-medium"),
+            Priority::High => Some("priority-high"),
+        }
+    }
+
+    /// Value of `priority:` in a query.
+    pub fn as_query_value(&self) -> &'static str {
+        match self {
+            Priority::None => "none",
+            Priority::Low => "low",
+            Priority::Medium => "medium",
+            Priority::High => "high",
+        }
+    }
+
+    pub fn from_query_value(value: &str) -> Option<Self> {
+        match value {
+            "none" => Some(Priority::None),
+            "low" => Some(Priority::Low),
+            "medium" => Some(Priority::Medium),
+            "high" => Some(Priority::High),
+            _ => None,
+        }
+    }
+
+    /// Parse a priority marker typed in the new-todo input: `!high` (or `!h`,
+    /// `!!!`), `!medium` (or `!med`, `!m`, `!!`) and `!low` (or `!l`).
+    pub fn from_marker(word: &str) -> Option<Self> {
+        match word.to_lowercase().as_str() {
+            "!high" | "!h" | "!!!" => Some(Priority::High),
+            "!medium" | "!med" | "!m" | "!!" => Some(Priority::Medium),
+            "!low" | "!l" => Some(Priority::Low),
+            _ => None,
+        }
+    }
+}
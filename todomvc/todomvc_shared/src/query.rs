@@ -0,0 +1,397 @@
+use std::fmt;
+use std::ops::Range;
+
+use crate::{Date, Filter, Priority, Search, Timestamp, TodoEntry};
+
+/// A parsed search query, e.g.
+/// `is:active "buy milk" -is:completed tag:work created:>2026-01-01`.
+///
+/// A query is a list of terms separated by whitespace, an entry matches when
+/// it matches every term:
+/// - `milk`, `"buy milk"`: the description contains the word or phrase,
+///   ignoring case and diacritics
+/// - `is:active`, `is:completed` (or `is:done`)
+/// - `tag:work`: the entry has the tag `work`
+/// - `priority:high` (or `low`, `medium`, `none`)
+/// - `created:`, `updated:`, `completed:` followed by an optional `<`, `<=`,
+///   `>`, `>=` or `=` and a date like `2026-01-31`, in the user's local time
+///
+/// A `-` in front of a term negates it. `Display` writes the query back in a
+/// canonical form that parses to the same terms. The URL hash keeps the text
+/// as typed instead, see `Route::query`, so that the search box is not
+/// rewritten while the user types.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Query {
+    pub terms: Vec<Term>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Term {
+    pub negated: bool,
+    pub condition: Condition,
+    /// Byte range of the term in the parsed text.
+    pub span: Range<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Condition {
+    Text { phrase: String, search: Search },
+    /// Only `Filter::Active` and `Filter::Completed` are produced by the parser.
+    Is(Filter),
+    Tag(String),
+    Priority(Priority),
+    Date {
+        field: DateField,
+        op: DateOp,
+        date: Date,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DateField {
+    Created,
+    Updated,
+    Completed,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DateOp {
+    Eq,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QueryError {
+    pub message: String,
+    /// Byte range of the offending text in the parsed query.
+    pub span: Range<usize>,
+}
+
+impl Query {
+    pub fn parse(text: &str) -> Result<Self, QueryError> {
+        let mut parser = Parser { text, position: 0 };
+        let mut terms = Vec::new();
+        while let Some(term) = parser.term()? {
+            terms.push(term);
+        }
+        Ok(Self { terms })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    pub fn matches(&self, entry: &TodoEntry) -> bool {
+        self.terms.iter().all(|term| term.matches(entry))
+    }
+
+    /// The text terms to highlight in the descriptions of matching entries.
+    pub fn highlight(&self) -> Search {
+        Search::all_of(self.terms.iter().filter_map(|term| match &term.condition {
+            Condition::Text { phrase, .. } if !term.negated => Some(phrase.as_str()),
+            _ => None,
+        }))
+    }
+}
+
+impl Term {
+    pub fn matches(&self, entry: &TodoEntry) -> bool {
+        self.condition.matches(entry) != self.negated
+    }
+}
+
+impl Condition {
+    pub fn matches(&self, entry: &TodoEntry) -> bool {
+        match self {
+            Condition::Text { search, .. } => search.matches(&entry.description),
+            Condition::Is(filter) => filter.fits(entry),
+            Condition::Tag(tag) => entry.tags.contains(tag),
+            Condition::Priority(priority) => entry.priority == *priority,
+            Condition::Date { field, op, date } => {
+                let at = match field {
+                    DateField::Created => entry.created_at,
+                    DateField::Updated => entry.updated_at,
+                    DateField::Completed => match entry.completed_at {
+                        Some(at) => at,
+                        None => return false,
+                    },
+                };
+                op.compare(at, date)
+            }
+        }
+    }
+}
+
+impl DateOp {
+    /// Whether the instant `at` is `self` the local day `date`.
+    pub fn compare(&self, at: Timestamp, date: &Date) -> bool {
+        match self {
+            DateOp::Eq => date.start_timestamp_local() <= at && at < date.end_timestamp_local(),
+            DateOp::Lt => at < date.start_timestamp_local(),
+            DateOp::Le => at < date.end_timestamp_local(),
+            DateOp::Gt => at >= date.end_timestamp_local(),
+            DateOp::Ge => at >= date.start_timestamp_local(),
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            DateOp::Eq => "",
+            DateOp::Lt => "<",
+            DateOp::Le => "<=",
+            DateOp::Gt => ">",
+            DateOp::Ge => ">=",
+        }
+    }
+}
+
+impl DateField {
+    fn as_str(&self) -> &'static str {
+        match self {
+            DateField::Created => "created",
+            DateField::Updated => "updated",
+            DateField::Completed => "completed",
+        }
+    }
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<char> {
+        self.text[self.position..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += c.len_utf8();
+        Some(c)
+    }
+
+    fn at_term_end(&self) -> bool {
+        match self.peek() {
+            Some(c) => c.is_whitespace(),
+            None => true,
+        }
+    }
+
+    fn error<T>(&self, message: impl Into<String>, span: Range<usize>) -> Result<T, QueryError> {
+        Err(QueryError {
+            message: message.into(),
+            span,
+        })
+    }
+
+    fn term(&mut self) -> Result<Option<Term>, QueryError> {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+        let start = self.position;
+        if self.peek().is_none() {
+            return Ok(None);
+        }
+        let negated = self.peek() == Some('-');
+        if negated {
+            self.bump();
+            if self.at_term_end() {
+                return self.error("expected a term after `-`", start..self.position);
+            }
+        }
+        let condition = if self.peek() == Some('"') {
+            let phrase = self.quoted()?;
+            text_condition(phrase)
+        } else {
+            self.field_or_word()?
+        };
+        if !self.at_term_end() {
+            let rest = self.position;
+            while !self.at_term_end() {
+                self.bump();
+            }
+            return self.error("expected whitespace after the term", rest..self.position);
+        }
+        Ok(Some(Term {
+            negated,
+            condition,
+            span: start..self.position,
+        }))
+    }
+
+    // A phrase in double quotes, `\"` and `\\` are escapes.
+    fn quoted(&mut self) -> Result<String, QueryError> {
+        let start = self.position;
+        self.bump();
+        let mut phrase = String::new();
+        loop {
+            match self.bump() {
+                Some('"') => return Ok(phrase),
+                Some('\\') => match self.bump() {
+                    Some(c) => phrase.push(c),
+                    None => break,
+                },
+                Some(c) => phrase.push(c),
+                None => break,
+            }
+        }
+        self.error("unterminated quote", start..self.position)
+    }
+
+    fn word(&mut self) -> &'a str {
+        let start = self.position;
+        while !self.at_term_end() {
+            self.bump();
+        }
+        &self.text[start..self.position]
+    }
+
+    fn field_or_word(&mut self) -> Result<Condition, QueryError> {
+        let start = self.position;
+        let key_len = self.text[start..]
+            .find(|c: char| !c.is_alphabetic())
+            .unwrap_or(self.text.len() - start);
+        // Only `letters:` starts a field, so that e.g. `12:30` is plain text
+        if key_len == 0 || !self.text[start + key_len..].starts_with(':') {
+            return Ok(text_condition(self.word().to_string()));
+        }
+        let key = &self.text[start..start + key_len];
+        self.position += key_len + 1;
+
+        let value_start = self.position;
+        let value = if self.peek() == Some('"') {
+            self.quoted()?
+        } else {
+            self.word().to_string()
+        };
+        let value_span = value_start..self.position;
+        if value.is_empty() {
+            return self.error(format!("missing value for `{key}:`"), start..self.position);
+        }
+
+        let date_field = match key {
+            "is" => {
+                return match value.as_str() {
+                    "active" => Ok(Condition::Is(Filter::Active)),
+                    "completed" | "done" => Ok(Condition::Is(Filter::Completed)),
+                    _ => self.error(
+                        format!("unknown status `{value}`, expected `active` or `completed`"),
+                        value_span,
+                    ),
+                };
+            }
+            "tag" => {
+                let tag = value.strip_prefix('#').unwrap_or(&value);
+                return Ok(Condition::Tag(tag.to_lowercase()));
+            }
+            "priority" => {
+                return match Priority::from_query_value(&value) {
+                    Some(priority) => Ok(Condition::Priority(priority)),
+                    None => self.error(
+                        format!("unknown priority `{value}`, expected `high`, `medium`, `low` or `none`"),
+                        value_span,
+                    ),
+                };
+            }
+            "created" => DateField::Created,
+            "updated" => DateField::Updated,
+            "completed" => DateField::Completed,
+            _ => {
+                return self.error(
+                    format!("unknown field `{key}`, expected one of `is`, `tag`, `priority`, `created`, `updated`, `completed`"),
+                    start..start + key_len,
+                )
+            }
+        };
+        let (op, date) = [
+            (">=", DateOp::Ge),
+            ("<=", DateOp::Le),
+            (">", DateOp::Gt),
+            ("<", DateOp::Lt),
+            ("=", DateOp::Eq),
+        ]
+        .into_iter()
+        .find_map(|(prefix, op)| value.strip_prefix(prefix).map(|date| (op, date)))
+        .unwrap_or((DateOp::Eq, &value));
+        match Date::parse_iso(date) {
+            Some(date) => Ok(Condition::Date {
+                field: date_field,
+                op,
+                date,
+            }),
+            None => self.error(
+                format!("invalid date `{date}`, expected a date like `2026-01-31`"),
+                value_span,
+            ),
+        }
+    }
+}
+
+fn text_condition(phrase: String) -> Condition {
+    Condition::Text {
+        search: Search::new(&phrase),
+        phrase,
+    }
+}
+
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (index, term) in self.terms.iter().enumerate() {
+            if index > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{term}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Term {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.negated {
+            f.write_str("-")?;
+        }
+        match &self.condition {
+            Condition::Text { phrase, .. } => write_text(f, phrase),
+            Condition::Is(filter) => write!(f, "is:{}", filter.to_string().to_lowercase()),
+            Condition::Tag(tag) => {
+                f.write_str("tag:")?;
+                write_text(f, tag)
+            }
+            Condition::Priority(priority) => write!(f, "priority:{}", priority.as_query_value()),
+            Condition::Date { field, op, date } => {
+                write!(f, "{}:{}{}", field.as_str(), op.as_str(), date)
+            }
+        }
+    }
+}
+
+// Quote the text unless it would parse back as the same single word.
+fn write_text(f: &mut fmt::Formatter, text: &str) -> fmt::Result {
+    let needs_quotes = text.is_empty()
+        || text.starts_with('-')
+        || text.contains(|c: char| c.is_whitespace() || c == '"' || c == '\\' || c == ':');
+    if !needs_quotes {
+        return f.write_str(text);
+    }
+    f.write_str("\"")?;
+    for c in text.chars() {
+        if c == '"' || c == '\\' {
+            f.write_str("\\")?;
+        }
+        write!(f, "{c}")?;
+    }
+    f.write_str("\"")
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (at {}..{})", self.message, self.span.start, self.span.end)
+    }
+}
+
+impl std::error::Error for QueryError {}
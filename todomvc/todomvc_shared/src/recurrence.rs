@@ -0,0 +1,218 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{days_in_month, next_weekday, now, parse_weekday, Date, Status, Timestamp, TodoEntry, Todos};
+
+/// How a todo repeats: once completed, the next occurrence is added with
+/// the next due date, see `Todos::update_recurrences`.
+///
+/// Only calendar arithmetic on `Date`, there is no time of day, so
+/// daylight saving time changes cannot move an occurrence.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Recurrence {
+    /// Every `n` days.
+    Days(u32),
+    /// Every `n` weeks.
+    Weeks(u32),
+    /// Every week on a weekday, 0 is Monday like `Date::weekday`.
+    Weekday(u8),
+    /// Every `every` months on `day`, or on the last day of the months that
+    /// are too short: a todo due on January 31 comes back on February 28,
+    /// then on March 31.
+    Months { every: u32, day: u8 },
+}
+
+impl Recurrence {
+    /// The occurrence that follows the one on `date`.
+    pub fn after(&self, date: Date) -> Date {
+        match *self {
+            Recurrence::Days(n) => date.add_days(n.max(1) as i64),
+            Recurrence::Weeks(n) => date.add_days(7 * n.max(1) as i64),
+            Recurrence::Weekday(weekday) => next_weekday(date, weekday % 7),
+            Recurrence::Months { every, day } => {
+                let months = date.year() as i64 * 12 + date.month() as i64 - 1 + every.max(1) as i64;
+                let year = months.div_euclid(12) as i32;
+                let month = (months.rem_euclid(12) + 1) as u8;
+                let day = day.clamp(1, days_in_month(year, month));
+                Date::new(year, month, day).expect("the day is clamped to the month")
+            }
+        }
+    }
+
+    /// The first occurrence on or after `today`, the due date of a new
+    /// recurring todo that was not given one.
+    pub fn first(&self, today: Date) -> Date {
+        match *self {
+            Recurrence::Weekday(weekday) if today.weekday() != weekday % 7 => next_weekday(today, weekday % 7),
+            _ => today,
+        }
+    }
+
+    /// The due date of the occurrence that follows one due on `due` and
+    /// completed on `today`: the first one after `today` on the schedule.
+    /// The occurrences missed while the todo was overdue are skipped, and a
+    /// todo without a due date is rescheduled from `today`.
+    pub fn next_due(&self, due: Option<Date>, today: Date) -> Date {
+        let mut next = self.after(due.unwrap_or(today));
+        while next <= today {
+            next = self.after(next);
+        }
+        next
+    }
+
+    /// Month based recurrences come back on the day of `due`, the others
+    /// are returned as they are.
+    pub fn anchored(self, due: Date) -> Self {
+        match self {
+            Recurrence::Months { every, .. } => Recurrence::Months { every, day: due.day() },
+            other => other,
+        }
+    }
+}
+
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        const WEEKDAYS: [&str; 7] = ["Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday", "Sunday"];
+        match *self {
+            Recurrence::Days(1) => write!(f, "every day"),
+            Recurrence::Days(n) => write!(f, "every {n} days"),
+            Recurrence::Weeks(1) => write!(f, "every week"),
+            Recurrence::Weeks(n) => write!(f, "every {n} weeks"),
+            Recurrence::Weekday(weekday) => write!(f, "every {}", WEEKDAYS[weekday as usize % 7]),
+            Recurrence::Months { every: 1, .. } => write!(f, "every month"),
+            Recurrence::Months { every: 12, .. } => write!(f, "every year"),
+            Recurrence::Months { every, .. } if every % 12 == 0 => write!(f, "every {} years", every / 12),
+            Recurrence::Months { every, .. } => write!(f, "every {every} months"),
+        }
+    }
+}
+
+/// A recurrence at the start of `words`, and the number of words it takes:
+/// `every day`, `every 3 days`, `every week`, `every 2 weeks`,
+/// `every monday`, `every month`, `every 6 months`, `every year`,
+/// `every 2 years`. Matching is case-insensitive.
+///
+/// Month based recurrences are on the 1st, see `anchored`.
+pub fn parse_recurrence(words: &[&str]) -> Option<(Recurrence, usize)> {
+    let lower: Vec<String> = words.iter().take(3).map(|word| word.to_lowercase()).collect();
+    let word = |i: usize| lower.get(i).map(String::as_str);
+    if word(0)? != "every" {
+        return None;
+    }
+    let unit = |count: u32, unit: &str| match unit {
+        "day" | "days" => Some(Recurrence::Days(count)),
+        "week" | "weeks" => Some(Recurrence::Weeks(count)),
+        "month" | "months" => Some(Recurrence::Months { every: count, day: 1 }),
+        "year" | "years" => Some(Recurrence::Months { every: count * 12, day: 1 }),
+        _ => None,
+    };
+    if let Some(weekday) = parse_weekday(word(1)?) {
+        return Some((Recurrence::Weekday(weekday), 2));
+    }
+    match (word(1)?, word(2)) {
+        (count, Some(name)) if count.bytes().all(|b| b.is_ascii_digit()) => {
+            // Anything longer than ten years is more likely not a recurrence
+            let count = count.parse().ok().filter(|count| (1..=3650).contains(count))?;
+            Some((unit(count, name)?, 3))
+        }
+        (name, _) => Some((unit(1, name)?, 2)),
+    }
+}
+
+impl TodoEntry {
+    pub fn set_recurrence(&mut self, recurrence: Option<Recurrence>) {
+        if self.recurrence == recurrence {
+            return;
+        }
+        self.recurrence = recurrence;
+        self.updated_at = now();
+    }
+
+    // Neither changed nor completed since it was created, at every depth
+    fn is_untouched(&self) -> bool {
+        self.iter_tree().all(|e| e.status == Status::Todo && e.updated_at == e.created_at)
+    }
+
+    // A new active entry like this one and its subtasks, with new ids and
+    // every due date moved by `days`
+    fn occurrence(&self, days: i64, now: Timestamp) -> TodoEntry {
+        TodoEntry {
+            id: uuid::Uuid::new_v4(),
+            description: self.description.clone(),
+            status: Status::Todo,
+            created_at: now,
+            updated_at: now,
+            completed_at: None,
+            tags: self.tags.clone(),
+            due: self.due.map(|due| due.add_days(days)),
+            priority: self.priority,
+            children: self.children.iter().map(|child| child.occurrence(days, now)).collect(),
+            collapsed: self.collapsed,
+            recurrence: None,
+            next_occurrence: None,
+        }
+    }
+}
+
+impl Todos {
+    /// Set or remove the recurrence of a top-level entry, subtasks do not
+    /// recur. Setting it on a completed entry adds its next occurrence.
+    pub fn set_recurrence(&mut self, id: &uuid::Uuid, recurrence: Option<Recurrence>) -> bool {
+        let entry = match self.entries.iter_mut().find(|e| e.id == *id) {
+            Some(entry) => entry,
+            None => return false,
+        };
+        let recurrence = recurrence.map(|r| r.anchored(entry.due.unwrap_or_else(Date::today)));
+        entry.set_recurrence(recurrence);
+        self.update_recurrences();
+        true
+    }
+
+    /// Keep recurring entries in step with their completion, after anything
+    /// that completes or reopens entries:
+    /// - a completed entry with a `recurrence` gets its next occurrence,
+    ///   inserted just after it, see `Recurrence::next_due`. The recurrence
+    ///   moves to the new entry, so that clearing the completed one keeps it
+    /// - a reopened entry takes its recurrence back, and its next occurrence
+    ///   is removed, unless it was changed in the meantime: then the
+    ///   reopened entry is just a todo that does not recur
+    pub(crate) fn update_recurrences(&mut self) {
+        let mut removed = false;
+        let reopened: Vec<_> = self
+            .entries
+            .iter()
+            .filter(|e| !e.is_completed())
+            .filter_map(|e| Some((e.id, e.next_occurrence?)))
+            .collect();
+        for (id, next_id) in reopened {
+            let next_index = self.entries.iter().position(|e| e.id == next_id && e.is_untouched());
+            let recurrence = next_index.and_then(|index| self.entries.remove(index).recurrence);
+            removed |= next_index.is_some();
+            if let Some(entry) = self.entries.iter_mut().find(|e| e.id == id) {
+                entry.next_occurrence = None;
+                entry.recurrence = entry.recurrence.or(recurrence);
+            }
+        }
+
+        let today = Date::today();
+        let now = now();
+        let mut index = 0;
+        while index < self.entries.len() {
+            let entry = &mut self.entries[index];
+            if let (true, Some(recurrence)) = (entry.is_completed(), entry.recurrence) {
+                let from = entry.due.unwrap_or(today);
+                let due = recurrence.next_due(entry.due, today);
+                let mut next = entry.occurrence(due.days_since_epoch() - from.days_since_epoch(), now);
+                next.due = Some(due);
+                next.recurrence = entry.recurrence.take();
+                entry.next_occurrence = Some(next.id);
+                self.entries.insert(index + 1, next);
+            }
+            index += 1;
+        }
+        if removed {
+            self.clean_up_dependencies();
+        }
+    }
+}
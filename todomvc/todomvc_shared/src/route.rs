@@ -0,0 +1,183 @@
+use crate::{Filter, SortOrder};
+
+/// The view state that is reflected in the URL hash, e.g. `#/active?q=milk&sort=alpha`,
+/// `#/tag/work/completed` or `#/list/<id>/active`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Route {
+    /// The list to show, `None` is the first one, see `TodoLists`.
+    pub list: Option<uuid::Uuid>,
+    pub filter: Filter,
+    pub sort: SortOrder,
+    pub tag: Option<String>,
+    /// The search query, as typed by the user.
+    pub query: String,
+}
+
+impl Route {
+    /// Parse the hash part of a URL. The leading `#` and `/` are optional,
+    /// unknown paths and parameters are ignored.
+    pub fn parse(hash: &str) -> Self {
+        let hash = hash.trim_start_matches('#').trim_start_matches('/');
+        let (path, query) = match hash.split_once('?') {
+            Some((path, query)) => (path, query),
+            None => (hash, ""),
+        };
+        let path = path.trim_end_matches('/');
+        let (list, path) = match path.strip_prefix("list/") {
+            Some(rest) => match rest.split_once('/') {
+                Some((list, path)) => (uuid::Uuid::parse_str(list).ok(), path),
+                None => (uuid::Uuid::parse_str(rest).ok(), ""),
+            },
+            None => (None, path),
+        };
+        let (tag, path) = match path.strip_prefix("tag/") {
+            Some(rest) => match rest.split_once('/') {
+                Some((tag, path)) => (Some(percent_decode(tag)), path),
+                None => (Some(percent_decode(rest)), ""),
+            },
+            None => (None, path),
+        };
+        let filter = match path {
+            "active" => Filter::Active,
+            "completed" => Filter::Completed,
+            "overdue" => Filter::Overdue,
+            "today" => Filter::DueToday,
+            "upcoming" => Filter::Upcoming,
+            "next" => Filter::Next,
+            "board" => Filter::Board,
+            "archive" => Filter::Archive,
+            _ => Filter::All,
+        };
+        let mut route = Self {
+            list,
+            filter,
+            tag: tag.filter(|tag| !tag.is_empty()),
+            ..Self::default()
+        };
+        for (name, value) in query.split('&').filter_map(|pair| pair.split_once('=')) {
+            match name {
+                "sort" => route.sort = SortOrder::from_query_value(value).unwrap_or_default(),
+                "q" => route.query = percent_decode(value),
+                _ => {}
+            }
+        }
+        route
+    }
+
+    pub fn to_hash(&self) -> String {
+        let mut hash = match &self.tag {
+            Some(tag) => {
+                let filter = self.filter.as_href().trim_start_matches("#/");
+                let separator = if filter.is_empty() { "" } else { "/" };
+                format!("#/tag/{}{separator}{filter}", percent_encode(tag))
+            }
+            None => self.filter.as_href().to_string(),
+        };
+        if let Some(list) = self.list {
+            hash = format!("#/list/{list}{}", hash.trim_start_matches('#').trim_end_matches('/'));
+        }
+        let mut separator = '?';
+        let mut push_param = |name: &str, value: &str| {
+            hash.push(separator);
+            hash.push_str(name);
+            hash.push('=');
+            hash.push_str(value);
+            separator = '&';
+        };
+        if !self.query.is_empty() {
+            push_param("q", &percent_encode(&self.query));
+        }
+        if let Some(sort) = self.sort.as_query_value() {
+            push_param("sort", sort);
+        }
+        hash
+    }
+
+    pub fn with_list(&self, list: Option<uuid::Uuid>) -> Self {
+        Self {
+            list,
+            ..self.clone()
+        }
+    }
+
+    pub fn with_filter(&self, filter: Filter) -> Self {
+        Self {
+            filter,
+            ..self.clone()
+        }
+    }
+
+    pub fn with_sort(&self, sort: SortOrder) -> Self {
+        Self {
+            sort,
+            ..self.clone()
+        }
+    }
+
+    pub fn with_tag(&self, tag: Option<&str>) -> Self {
+        Self {
+            tag: tag.map(str::to_string),
+            ..self.clone()
+        }
+    }
+
+    pub fn with_query(&self, query: &str) -> Self {
+        Self {
+            query: query.to_string(),
+            ..self.clone()
+        }
+    }
+
+    /// Show this route in the address bar without adding a history entry and
+    /// without a `hashchange` event, e.g. while the user types a search query.
+    pub fn replace_location_hash(&self) {
+        if let Some(window) = web_sys::window() {
+            if let Ok(history) = window.history() {
+                let _ = history.replace_state_with_url(
+                    &wasm_bindgen::JsValue::NULL,
+                    "",
+                    Some(&self.to_hash()),
+                );
+            }
+        }
+    }
+}
+
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+// Invalid escapes are kept as they are, `+` is a space as in form encoding.
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' if i + 2 < bytes.len() => {
+                let hex = |byte: u8| (byte as char).to_digit(16);
+                match (hex(bytes[i + 1]), hex(bytes[i + 2])) {
+                    (Some(high), Some(low)) => {
+                        decoded.push((high * 16 + low) as u8);
+                        i += 3;
+                        continue;
+                    }
+                    _ => decoded.push(b'%'),
+                }
+            }
+            b'+' => decoded.push(b' '),
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
@@ -0,0 +1,196 @@
+use std::ops::Range;
+
+use crate::{Date, Filter, Query, TodoEntry};
+
+/// A case-insensitive, diacritic-insensitive substring search: "cafe"
+/// finds "Café", "tiếng" finds "TIENG". A search for several needles
+/// matches a text that contains all of them.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Search {
+    needles: Vec<Vec<char>>,
+}
+
+/// A piece of a text split by `Search::split_matches`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Segment<'a> {
+    pub text: &'a str,
+    pub matched: bool,
+}
+
+impl Search {
+    /// Search for `phrase` as a whole.
+    pub fn new(phrase: &str) -> Self {
+        Self::all_of([phrase])
+    }
+
+    pub fn all_of<'a>(phrases: impl IntoIterator<Item = &'a str>) -> Self {
+        Self {
+            needles: phrases
+                .into_iter()
+                .map(|phrase| fold(phrase.trim()).map(|(c, _)| c).collect::<Vec<_>>())
+                .filter(|needle| !needle.is_empty())
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.needles.is_empty()
+    }
+
+    /// An empty search matches everything.
+    pub fn matches(&self, text: &str) -> bool {
+        if self.is_empty() {
+            return true;
+        }
+        let folded: Vec<(char, Range<usize>)> = fold(text).collect();
+        self.needles
+            .iter()
+            .all(|needle| find(&folded, needle, 0).is_some())
+    }
+
+    /// Byte ranges of `text` that match any needle, in order and not overlapping.
+    pub fn find_all(&self, text: &str) -> Vec<Range<usize>> {
+        let folded: Vec<(char, Range<usize>)> = fold(text).collect();
+        let mut found = Vec::new();
+        for needle in self.needles.iter() {
+            let mut from = 0;
+            while let Some(i) = find(&folded, needle, from) {
+                found.push(folded[i].1.start..folded[i + needle.len() - 1].1.end);
+                from = i + needle.len();
+            }
+        }
+        found.sort_by_key(|range| range.start);
+
+        let mut ranges: Vec<Range<usize>> = Vec::with_capacity(found.len());
+        for range in found {
+            match ranges.last_mut() {
+                // Matches of different needles can overlap, and a char that folds
+                // to several chars ("æ" to "ae") can be shared by two matches
+                Some(last) if last.end >= range.start => last.end = last.end.max(range.end),
+                _ => ranges.push(range),
+            }
+        }
+        ranges
+    }
+
+    /// Split `text` into matched and unmatched segments for highlighting.
+    /// Concatenating the segments gives back `text`.
+    pub fn split_matches<'a>(&self, text: &'a str) -> Vec<Segment<'a>> {
+        let mut segments = Vec::new();
+        let mut position = 0;
+        for range in self.find_all(text) {
+            if range.start > position {
+                segments.push(Segment {
+                    text: &text[position..range.start],
+                    matched: false,
+                });
+            }
+            position = range.end;
+            segments.push(Segment {
+                text: &text[range],
+                matched: true,
+            });
+        }
+        if position < text.len() || segments.is_empty() {
+            segments.push(Segment {
+                text: &text[position..],
+                matched: false,
+            });
+        }
+        segments
+    }
+}
+
+/// The status and tag filters composed with the query: decides whether an
+/// entry is in the current view.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Predicate {
+    pub filter: Filter,
+    /// The day that due dates are compared with.
+    pub today: Date,
+    pub tag: Option<String>,
+    pub query: Query,
+}
+
+impl Predicate {
+    pub fn fits(&self, entry: &TodoEntry) -> bool {
+        self.filter.fits_on(entry, self.today)
+            && self.tag.as_ref().is_none_or(|tag| entry.tags.contains(tag))
+            && self.query.matches(entry)
+    }
+}
+
+// Index of the first occurrence of `needle` in `folded` at or after `from`.
+fn find(folded: &[(char, Range<usize>)], needle: &[char], from: usize) -> Option<usize> {
+    if folded.len() < needle.len() {
+        return None;
+    }
+    (from..=folded.len() - needle.len())
+        .find(|&i| folded[i..i + needle.len()].iter().map(|(c, _)| *c).eq(needle.iter().copied()))
+}
+
+/// Lowercase and strip diacritics, keeping track of the byte range in `text`
+/// that each folded char comes from.
+fn fold(text: &str) -> impl Iterator<Item = (char, Range<usize>)> + '_ {
+    text.char_indices().flat_map(|(start, c)| {
+        let range = start..start + c.len_utf8();
+        c.to_lowercase()
+            .flat_map(strip_diacritic)
+            .map(move |c| (c, range.clone()))
+    })
+}
+
+// Covers Latin-1, Latin Extended-A, Vietnamese and combining marks, which is
+// what a todo list meets in practice. A full solution would need Unicode
+// decomposition tables, which are too big for this comparison.
+fn strip_diacritic(c: char) -> impl Iterator<Item = char> {
+    let folded: &[char] = match c {
+        '\u{300}'..='\u{36f}' => &[],
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => &['a'],
+        '\u{1ea0}'..='\u{1eb7}' => &['a'],
+        'æ' => &['a', 'e'],
+        'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => &['c'],
+        'ď' | 'đ' => &['d'],
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => &['e'],
+        '\u{1eb8}'..='\u{1ec7}' => &['e'],
+        'ĝ' | 'ğ' | 'ġ' | 'ģ' => &['g'],
+        'ĥ' | 'ħ' => &['h'],
+        'ì' | 'í' | 'î' | 'ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => &['i'],
+        '\u{1ec8}'..='\u{1ecb}' => &['i'],
+        'ĵ' => &['j'],
+        'ķ' => &['k'],
+        'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => &['l'],
+        'ñ' | 'ń' | 'ņ' | 'ň' => &['n'],
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ŏ' | 'ő' | 'ơ' => &['o'],
+        '\u{1ecc}'..='\u{1ee3}' => &['o'],
+        'œ' => &['o', 'e'],
+        'ŕ' | 'ŗ' | 'ř' => &['r'],
+        'ś' | 'ŝ' | 'ş' | 'š' => &['s'],
+        'ß' => &['s', 's'],
+        'ţ' | 'ť' | 'ŧ' => &['t'],
+        'ù' | 'ú' | 'û' | 'ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' | 'ư' => &['u'],
+        '\u{1ee4}'..='\u{1ef1}' => &['u'],
+        'ŵ' => &['w'],
+        'ý' | 'ÿ' | 'ŷ' => &['y'],
+        '\u{1ef2}'..='\u{1ef9}' => &['y'],
+        'ź' | 'ż' | 'ž' => &['z'],
+        c => return StripDiacritic::One(Some(c)),
+    };
+    StripDiacritic::Many(folded.iter())
+}
+
+enum StripDiacritic {
+    One(Option<char>),
+    Many(std::slice::Iter<'static, char>),
+}
+
+impl Iterator for StripDiacritic {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        match self {
+            StripDiacritic::One(c) => c.take(),
+            StripDiacritic::Many(chars) => chars.next().copied(),
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+use strum::EnumIter;
+
+use crate::{Priority, TodoEntry, Timestamp};
+
+#[derive(Clone, Copy, Debug, Default, EnumIter, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SortOrder {
+    /// The order of `Todos::entries`, which is also the manual order.
+    #[default]
+    Insertion,
+    /// Case-insensitive, by description.
+    Alphabetical,
+    /// Oldest first.
+    Created,
+    /// Insertion order, with completed entries moved to the bottom.
+    CompletedLast,
+    /// Active entries first, newest first in each group.
+    ActiveFirst,
+    /// Highest priority first, insertion order for the same priority.
+    Priority,
+}
+
+/// What `SortOrder` needs to know about an item. Implementations that keep
+/// their own todo type (e.g. with a signal per field) implement this so that
+/// every implementation sorts in exactly the same way.
+pub trait Sortable {
+    fn with_description<R>(&self, f: impl FnOnce(&str) -> R) -> R;
+    fn created_at(&self) -> Timestamp;
+    fn is_completed(&self) -> bool;
+    fn priority(&self) -> Priority;
+}
+
+impl Sortable for TodoEntry {
+    fn with_description<R>(&self, f: impl FnOnce(&str) -> R) -> R {
+        f(&self.description)
+    }
+
+    fn created_at(&self) -> Timestamp {
+        self.created_at
+    }
+
+    fn is_completed(&self) -> bool {
+        self.status.is_done()
+    }
+
+    fn priority(&self) -> Priority {
+        self.priority
+    }
+}
+
+impl<T: Sortable + ?Sized> Sortable for &T {
+    fn with_description<R>(&self, f: impl FnOnce(&str) -> R) -> R {
+        (**self).with_description(f)
+    }
+
+    fn created_at(&self) -> Timestamp {
+        (**self).created_at()
+    }
+
+    fn is_completed(&self) -> bool {
+        (**self).is_completed()
+    }
+
+    fn priority(&self) -> Priority {
+        (**self).priority()
+    }
+}
+
+impl<T: Sortable + ?Sized> Sortable for std::sync::Arc<T> {
+    fn with_description<R>(&self, f: impl FnOnce(&str) -> R) -> R {
+        (**self).with_description(f)
+    }
+
+    fn created_at(&self) -> Timestamp {
+        (**self).created_at()
+    }
+
+    fn is_completed(&self) -> bool {
+        (**self).is_completed()
+    }
+
+    fn priority(&self) -> Priority {
+        (**self).priority()
+    }
+}
+
+impl<T: Sortable + ?Sized> Sortable for std::rc::Rc<T> {
+    fn with_description<R>(&self, f: impl FnOnce(&str) -> R) -> R {
+        (**self).with_description(f)
+    }
+
+    fn created_at(&self) -> Timestamp {
+        (**self).created_at()
+    }
+
+    fn is_completed(&self) -> bool {
+        (**self).is_completed()
+    }
+
+    fn priority(&self) -> Priority {
+        (**self).priority()
+    }
+}
+
+impl SortOrder {
+    pub fn compare<T: Sortable>(&self, a: &T, b: &T) -> Ordering {
+        match self {
+            SortOrder::Insertion => Ordering::Equal,
+            SortOrder::Alphabetical => a.with_description(|a| {
+                b.with_description(|b| compare_case_insensitive(a, b))
+            }),
+            SortOrder::Created => a.created_at().cmp(&b.created_at()),
+            SortOrder::CompletedLast => a.is_completed().cmp(&b.is_completed()),
+            SortOrder::ActiveFirst => a
+                .is_completed()
+                .cmp(&b.is_completed())
+                .then_with(|| b.created_at().cmp(&a.created_at())),
+            SortOrder::Priority => b.priority().cmp(&a.priority()),
+        }
+    }
+
+    /// Sort a view of the entries. The sort is stable, so entries that
+    /// compare equal stay in insertion order.
+    pub fn sort<T: Sortable>(&self, items: &mut [T]) {
+        if *self != SortOrder::Insertion {
+            items.sort_by(|a, b| self.compare(a, b));
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SortOrder::Insertion => "Manual",
+            SortOrder::Alphabetical => "A-Z",
+            SortOrder::Created => "Oldest",
+            SortOrder::CompletedLast => "Completed last",
+            SortOrder::ActiveFirst => "Active first",
+            SortOrder::Priority => "Priority",
+        }
+    }
+
+    /// Value of the `sort` parameter in the URL, `None` for the default order.
+    pub fn as_query_value(&self) -> Option<&'static str> {
+        match self {
+            SortOrder::Insertion => None,
+            SortOrder::Alphabetical => Some("alpha"),
+            SortOrder::Created => Some("created"),
+            SortOrder::CompletedLast => Some("completed-last"),
+            SortOrder::ActiveFirst => Some("active-first"),
+            SortOrder::Priority => Some("priority"),
+        }
+    }
+
+    pub fn from_query_value(value: &str) -> Option<Self> {
+        match value {
+            "alpha" => Some(SortOrder::Alphabetical),
+            "created" => Some(SortOrder::Created),
+            "completed-last" => Some(SortOrder::CompletedLast),
+            "active-first" => Some(SortOrder::ActiveFirst),
+            "priority" => Some(SortOrder::Priority),
+            _ => None,
+        }
+    }
+}
+
+// Compare char by char instead of allocating lowercased copies: the comparator
+// runs O(n log n) times when sorting.
+fn compare_case_insensitive(a: &str, b: &str) -> Ordering {
+    a.chars()
+        .flat_map(char::to_lowercase)
+        .cmp(b.chars().flat_map(char::to_lowercase))
+}
@@ -0,0 +1,170 @@
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::{DependencyError, TodoEntry, Todos};
+
+/// Where an entry is in the workflow, one column of the board.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Status {
+    #[default]
+    Todo,
+    InProgress,
+    /// A column added by the user, e.g. "Review", see `Todos::add_column`.
+    /// Still active, like `Todo` and `InProgress`.
+    Custom(String),
+    Done,
+}
+
+impl Status {
+    pub fn is_done(&self) -> bool {
+        *self == Status::Done
+    }
+
+    pub fn label(&self) -> &str {
+        match self {
+            Status::Todo => "To do",
+            Status::InProgress => "In progress",
+            Status::Custom(name) => name,
+            Status::Done => "Done",
+        }
+    }
+
+    /// Class of the `li` of an entry with this status, `None` for `Todo`
+    /// and `Done` which already have their TodoMVC looks.
+    pub fn class_name(&self) -> Option<&'static str> {
+        match self {
+            Status::Todo | Status::Done => None,
+            Status::InProgress => Some("status-in-progress"),
+            Status::Custom(_) => Some("status-custom"),
+        }
+    }
+
+    /// The status of an entry with subtasks: the one they all share, or
+    /// `InProgress` when they differ.
+    pub fn of_subtasks<'a>(mut statuses: impl Iterator<Item = &'a Status>) -> Status {
+        let first = match statuses.next() {
+            Some(first) => first,
+            None => return Status::Todo,
+        };
+        match statuses.all(|status| status == first) {
+            true => first.clone(),
+            false => Status::InProgress,
+        }
+    }
+}
+
+/// Reads a `Status`, or the `completed` bool that entries had before it.
+pub(crate) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Status, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Saved {
+        Completed(bool),
+        Status(Status),
+    }
+    Ok(match Saved::deserialize(deserializer)? {
+        Saved::Completed(true) => Status::Done,
+        Saved::Completed(false) => Status::Todo,
+        Saved::Status(status) => status,
+    })
+}
+
+/// A column of the board: the entries without subtasks that have `status`.
+pub struct Column<'a> {
+    pub status: Status,
+    pub entries: Vec<&'a TodoEntry>,
+}
+
+impl TodoEntry {
+    pub fn is_completed(&self) -> bool {
+        self.status.is_done()
+    }
+
+    /// Move the entry and all of its subtasks to `status`.
+    pub fn set_status(&mut self, status: Status) {
+        for child in self.children.iter_mut() {
+            child.set_status(status.clone());
+        }
+        self.mark_status(status);
+    }
+}
+
+impl Todos {
+    /// Every status in board order: `Todo`, `InProgress`, the custom
+    /// columns, then `Done`.
+    pub fn statuses(&self) -> Vec<Status> {
+        let mut statuses = vec![Status::Todo, Status::InProgress];
+        statuses.extend(self.custom_columns.iter().cloned().map(Status::Custom));
+        statuses.push(Status::Done);
+        statuses
+    }
+
+    /// Add a custom column before `Done`. Names are trimmed, and must differ
+    /// from the other columns regardless of case.
+    pub fn add_column(&mut self, name: &str) -> bool {
+        let name = name.trim();
+        let taken = self.statuses().iter().any(|s| s.label().to_lowercase() == name.to_lowercase());
+        if name.is_empty() || taken {
+            return false;
+        }
+        self.custom_columns.push(name.to_string());
+        true
+    }
+
+    /// Remove a custom column, its entries go back to `Todo`.
+    pub fn remove_column(&mut self, name: &str) -> bool {
+        let len = self.custom_columns.len();
+        self.custom_columns.retain(|column| column != name);
+        if self.custom_columns.len() == len {
+            return false;
+        }
+        let removed = Status::Custom(name.to_string());
+        fn reset(entries: &mut [TodoEntry], removed: &Status) {
+            for entry in entries {
+                if entry.status == *removed {
+                    entry.mark_status(Status::Todo);
+                }
+                reset(&mut entry.children, removed);
+            }
+        }
+        reset(&mut self.entries, &removed);
+        self.roll_up();
+        true
+    }
+
+    /// Move an entry, with its subtasks, to another column. Moving to `Done`
+    /// is completing, which fails for a blocked entry.
+    pub fn set_status(&mut self, id: &uuid::Uuid, status: Status) -> Result<(), DependencyError> {
+        if status.is_done() {
+            self.check_completion(id)?;
+        }
+        if let Some(entry) = self.get_entry_by_id_mut(id) {
+            entry.set_status(status);
+            self.roll_up();
+            self.update_recurrences();
+        }
+        Ok(())
+    }
+
+    /// The columns of the board view, one per status. Entries with subtasks
+    /// are not cards, their status is derived from their subtasks. The tag
+    /// and the query apply, the cards are sorted by `self.sort`.
+    pub fn board(&self) -> Vec<Column<'_>> {
+        let predicate = self.predicate();
+        let mut columns: Vec<_> = self
+            .statuses()
+            .into_iter()
+            .map(|status| Column {
+                status,
+                entries: Vec::new(),
+            })
+            .collect();
+        for entry in self.iter_leaves().filter(|e| predicate.fits(e)) {
+            // Entries of a column that does not exist anymore show in the first one
+            let index = columns.iter().position(|c| c.status == entry.status).unwrap_or(0);
+            columns[index].entries.push(entry);
+        }
+        for column in columns.iter_mut() {
+            self.sort.sort(&mut column.entries);
+        }
+        columns
+    }
+}
@@ -0,0 +1,207 @@
+use crate::{DependencyError, Predicate, Status, TodoEntry, Todos};
+
+/// The entries of a tree, depth first, each parent before its subtasks.
+pub struct TreeIter<'a> {
+    stack: Vec<&'a TodoEntry>,
+}
+
+impl<'a> TreeIter<'a> {
+    fn new(entries: &'a [TodoEntry]) -> Self {
+        Self {
+            stack: entries.iter().rev().collect(),
+        }
+    }
+}
+
+impl<'a> Iterator for TreeIter<'a> {
+    type Item = &'a TodoEntry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = self.stack.pop()?;
+        self.stack.extend(entry.children.iter().rev());
+        Some(entry)
+    }
+}
+
+impl TodoEntry {
+    pub fn has_subtasks(&self) -> bool {
+        !self.children.is_empty()
+    }
+
+    /// The entry itself, then all of its subtasks at any depth.
+    pub fn iter_tree(&self) -> TreeIter<'_> {
+        TreeIter::new(std::slice::from_ref(self))
+    }
+
+    /// Completed and total subtasks without subtasks of their own, e.g.
+    /// `(1, 3)`. `None` for an entry without subtasks.
+    pub fn subtask_progress(&self) -> Option<(usize, usize)> {
+        if !self.has_subtasks() {
+            return None;
+        }
+        let leaves = self.iter_tree().skip(1).filter(|e| !e.has_subtasks());
+        let (completed, total) = leaves.fold((0, 0), |(completed, total), e| {
+            (completed + e.is_completed() as usize, total + 1)
+        });
+        Some((completed, total))
+    }
+
+    pub fn toggle_collapsed(&mut self) {
+        self.collapsed = !self.collapsed;
+    }
+}
+
+impl Predicate {
+    /// An entry fits if it does or if any of its subtasks does, so that the
+    /// path to a matching subtask stays visible.
+    pub fn fits_tree(&self, entry: &TodoEntry) -> bool {
+        self.fits(entry) || entry.children.iter().any(|e| self.fits_tree(e))
+    }
+}
+
+impl Todos {
+    /// Every entry at every depth, see `TreeIter`.
+    pub fn iter_tree(&self) -> TreeIter<'_> {
+        TreeIter::new(&self.entries)
+    }
+
+    /// The entries that are counted: those without subtasks. The completion
+    /// of an entry with subtasks is derived from them, counting it as well
+    /// would count the same work twice.
+    pub fn iter_leaves(&self) -> impl Iterator<Item = &TodoEntry> {
+        self.iter_tree().filter(|e| !e.has_subtasks())
+    }
+
+    pub fn get_entry_by_id(&self, id: &uuid::Uuid) -> Option<&TodoEntry> {
+        self.iter_tree().find(|e| e.id == *id)
+    }
+
+    /// The top-level ancestor of the entry, then each level of subtasks down
+    /// to the entry itself.
+    pub fn path_to(&self, id: &uuid::Uuid) -> Option<Vec<&TodoEntry>> {
+        fn search<'a>(entries: &'a [TodoEntry], id: &uuid::Uuid, path: &mut Vec<&'a TodoEntry>) -> bool {
+            for entry in entries {
+                path.push(entry);
+                if entry.id == *id || search(&entry.children, id, path) {
+                    return true;
+                }
+                path.pop();
+            }
+            false
+        }
+        let mut path = Vec::new();
+        search(&self.entries, id, &mut path).then_some(path)
+    }
+
+    /// Add a subtask with `description` as it is at the end of the subtasks
+    /// of `parent_id`. The parent is expanded so that the new subtask is
+    /// visible, and is no longer complete.
+    pub fn add_subtask(&mut self, parent_id: &uuid::Uuid, description: String) -> bool {
+        self.push_subtask(parent_id, TodoEntry::new(&description))
+    }
+
+    /// Like `add_subtask`, parsing `input` like `new_entry_parsed` does.
+    pub fn add_subtask_parsed(&mut self, parent_id: &uuid::Uuid, input: String) -> bool {
+        let mut entry = TodoEntry::parsed(&input);
+        // Only top-level entries recur
+        entry.recurrence = None;
+        self.push_subtask(parent_id, entry)
+    }
+
+    fn push_subtask(&mut self, parent_id: &uuid::Uuid, entry: TodoEntry) -> bool {
+        let parent = match self.get_entry_by_id_mut(parent_id) {
+            Some(parent) => parent,
+            None => return false,
+        };
+        parent.children.push(entry);
+        parent.collapsed = false;
+        self.roll_up();
+        self.update_recurrences();
+        true
+    }
+
+    /// Complete or reopen an entry with all of its subtasks, then update
+    /// the completion of its ancestors. Completing a blocked entry fails,
+    /// see `check_completion`.
+    pub fn set_completed(&mut self, id: &uuid::Uuid, completed: bool) -> Result<(), DependencyError> {
+        if completed {
+            self.check_completion(id)?;
+        }
+        if let Some(entry) = self.get_entry_by_id_mut(id) {
+            entry.set_completed(completed);
+            self.roll_up();
+            self.update_recurrences();
+        }
+        Ok(())
+    }
+
+    pub fn toggle_completion(&mut self, id: &uuid::Uuid) -> Result<(), DependencyError> {
+        match self.get_entry_by_id(id).map(|e| e.is_completed()) {
+            Some(completed) => self.set_completed(id, !completed),
+            None => Ok(()),
+        }
+    }
+
+    pub fn toggle_collapsed(&mut self, id: &uuid::Uuid) {
+        if let Some(entry) = self.get_entry_by_id_mut(id) {
+            entry.toggle_collapsed();
+        }
+    }
+
+    /// The subtasks of `entry` to render, filtered and sorted like the
+    /// top-level entries. Nothing for a collapsed entry.
+    pub fn get_visible_children<'a>(&self, entry: &'a TodoEntry) -> Vec<&'a TodoEntry> {
+        if entry.collapsed {
+            return Vec::new();
+        }
+        let predicate = self.predicate();
+        let mut children: Vec<_> = entry.children.iter().filter(|e| predicate.fits_tree(e)).collect();
+        self.sort.sort(&mut children);
+        children
+    }
+
+    /// Derive the status of every entry with subtasks, see
+    /// `Status::of_subtasks`: complete when all of its subtasks are.
+    pub(crate) fn roll_up(&mut self) {
+        roll_up(&mut self.entries);
+    }
+}
+
+fn roll_up(entries: &mut [TodoEntry]) {
+    for entry in entries.iter_mut().filter(|e| e.has_subtasks()) {
+        roll_up(&mut entry.children);
+        entry.mark_status(Status::of_subtasks(entry.children.iter().map(|e| &e.status)));
+    }
+}
+
+pub(crate) fn find_mut<'a>(entries: &'a mut [TodoEntry], id: &uuid::Uuid) -> Option<&'a mut TodoEntry> {
+    for entry in entries {
+        if entry.id == *id {
+            return Some(entry);
+        }
+        if let Some(found) = find_mut(&mut entry.children, id) {
+            return Some(found);
+        }
+    }
+    None
+}
+
+/// Remove the entries that match `f` at every depth, into `drained` with
+/// the id of their parent. The subtasks of a removed entry go with it.
+pub(crate) fn drain_tree(
+    entries: &mut Vec<TodoEntry>,
+    parent: Option<uuid::Uuid>,
+    f: &dyn Fn(&TodoEntry) -> bool,
+    drained: &mut Vec<(TodoEntry, Option<uuid::Uuid>)>,
+) {
+    let mut index = 0;
+    while index < entries.len() {
+        if f(&entries[index]) {
+            drained.push((entries.remove(index), parent));
+            continue;
+        }
+        let entry = &mut entries[index];
+        drain_tree(&mut entry.children, Some(entry.id), f, drained);
+        index += 1;
+    }
+}
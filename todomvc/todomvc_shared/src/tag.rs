@@ -0,0 +1,66 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Split the `#tags` out of a description typed by the user:
+/// "Call Bob #work #Phone" gives "Call Bob" and `{"phone", "work"}`.
+///
+/// A description made of tags only is kept as it is, so that the entry still
+/// has something to show.
+pub fn extract_tags(text: &str) -> (String, BTreeSet<String>) {
+    let mut tags = BTreeSet::new();
+    let mut words = Vec::new();
+    for word in text.split_whitespace() {
+        match parse_tag(word) {
+            Some(tag) => {
+                tags.insert(tag);
+            }
+            None => words.push(word),
+        }
+    }
+    if words.is_empty() {
+        return (text.trim().to_string(), tags);
+    }
+    (words.join(" "), tags)
+}
+
+/// The tag written by `word`, e.g. "work" for "#Work", normalized to
+/// lowercase. A tag starts with a letter and may contain letters, digits,
+/// `-` and `_`, so that "#12" (an issue number) is not a tag.
+pub fn parse_tag(word: &str) -> Option<String> {
+    let tag = word.strip_prefix('#')?;
+    let mut chars = tag.chars();
+    let first_is_letter = chars.next().is_some_and(char::is_alphabetic);
+    if first_is_letter && chars.all(|c| c.is_alphanumeric() || c == '-' || c == '_') {
+        Some(tag.to_lowercase())
+    } else {
+        None
+    }
+}
+
+/// Number of entries per tag, in alphabetical order of the tags.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TagIndex {
+    counts: BTreeMap<String, usize>,
+}
+
+impl TagIndex {
+    /// Build the index from the tag sets of all entries.
+    pub fn new<'a>(tag_sets: impl IntoIterator<Item = &'a BTreeSet<String>>) -> Self {
+        let mut counts = BTreeMap::new();
+        for tag in tag_sets.into_iter().flatten() {
+            *counts.entry(tag.clone()).or_insert(0) += 1;
+        }
+        Self { counts }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    pub fn count(&self, tag: &str) -> usize {
+        self.counts.get(tag).copied().unwrap_or(0)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.counts.iter().map(|(tag, count)| (tag.as_str(), *count))
+    }
+}
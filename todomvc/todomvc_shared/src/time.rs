@@ -0,0 +1,133 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+
+/// Milliseconds since the Unix epoch (UTC).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Timestamp(pub i64);
+
+pub const SECOND: i64 = 1000;
+pub const MINUTE: i64 = 60 * SECOND;
+pub const HOUR: i64 = 60 * MINUTE;
+pub const DAY: i64 = 24 * HOUR;
+
+/// Source of the current time. The mutation methods of `Todos` read the
+/// time through the clock installed with `set_clock`, so tests can run
+/// natively with a `ManualClock` instead of the browser's clock.
+pub trait Clock {
+    fn now(&self) -> Timestamp;
+
+    /// Offset of the user's local time from UTC at the given instant,
+    /// in minutes (e.g. `120` for UTC+2).
+    fn utc_offset_minutes(&self, _at: Timestamp) -> i32 {
+        0
+    }
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    #[cfg(target_arch = "wasm32")]
+    fn now(&self) -> Timestamp {
+        Timestamp(js_sys::Date::now() as i64)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn now(&self) -> Timestamp {
+        let since_epoch = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default();
+        Timestamp(since_epoch.as_millis() as i64)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn utc_offset_minutes(&self, at: Timestamp) -> i32 {
+        let date = js_sys::Date::new(&(at.0 as f64).into());
+        // `getTimezoneOffset` is UTC minus local time
+        -(date.get_timezone_offset() as i32)
+    }
+}
+
+/// A clock that only moves when told to.
+#[derive(Debug, Default)]
+pub struct ManualClock {
+    now: std::cell::Cell<i64>,
+    utc_offset_minutes: std::cell::Cell<i32>,
+}
+
+impl ManualClock {
+    pub fn new(now: Timestamp) -> Self {
+        Self {
+            now: std::cell::Cell::new(now.0),
+            utc_offset_minutes: std::cell::Cell::new(0),
+        }
+    }
+
+    pub fn set(&self, now: Timestamp) {
+        self.now.set(now.0);
+    }
+
+    pub fn advance(&self, millis: i64) {
+        self.now.set(self.now.get() + millis);
+    }
+
+    pub fn set_utc_offset_minutes(&self, offset: i32) {
+        self.utc_offset_minutes.set(offset);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Timestamp {
+        Timestamp(self.now.get())
+    }
+
+    fn utc_offset_minutes(&self, _at: Timestamp) -> i32 {
+        self.utc_offset_minutes.get()
+    }
+}
+
+thread_local! {
+    static CLOCK: RefCell<Rc<dyn Clock>> = RefCell::new(Rc::new(SystemClock));
+}
+
+/// Replace the clock used by this thread.
+pub fn set_clock(clock: Rc<dyn Clock>) {
+    CLOCK.with(|c| *c.borrow_mut() = clock);
+}
+
+pub fn now() -> Timestamp {
+    CLOCK.with(|c| c.borrow().now())
+}
+
+pub fn utc_offset_minutes(at: Timestamp) -> i32 {
+    CLOCK.with(|c| c.borrow().utc_offset_minutes(at))
+}
+
+/// Format `then` relative to `now`, e.g. "just now", "3 hours ago",
+/// "in 2 days".
+pub fn relative_time(then: Timestamp, now: Timestamp) -> String {
+    let delta = now.0 - then.0;
+    let abs = delta.abs();
+    if abs < MINUTE {
+        return "just now".to_string();
+    }
+    let (count, unit) = if abs < HOUR {
+        (abs / MINUTE, "minute")
+    } else if abs < DAY {
+        (abs / HOUR, "hour")
+    } else if abs < 30 * DAY {
+        (abs / DAY, "day")
+    } else if abs < 365 * DAY {
+        (abs / (30 * DAY), "month")
+    } else {
+        (abs / (365 * DAY), "year")
+    };
+    let plural = if count == 1 { "" } else { "s" };
+    if delta > 0 {
+        format!("{count} {unit}{plural} ago")
+    } else {
+        format!("in {count} {unit}{plural}")
+    }
+}
@@ -0,0 +1,108 @@
+use std::rc::Rc;
+
+use todomvc_shared::*;
+
+mod common;
+use common::{id_of, todos};
+
+fn listed(todos: &Todos) -> Vec<&str> {
+    todos.iter_tree().map(|e| e.description.as_str()).collect()
+}
+
+fn archived(todos: &Todos) -> Vec<&str> {
+    todos.archive.entries.iter().map(|e| e.entry.description.as_str()).collect()
+}
+
+#[test]
+fn clearing_and_removing_archive_entries() {
+    let mut todos = todos(&["a", "b", "c", "d"]);
+    for description in ["a", "c"] {
+        todos.toggle_completion(&id_of(&todos, description)).unwrap();
+    }
+    todos.clear_completed();
+    assert_eq!(listed(&todos), ["b", "d"]);
+    assert_eq!(archived(&todos), ["a", "c"]);
+
+    // The most recently archived come first
+    todos.remove_by_id(&id_of(&todos, "d"));
+    assert_eq!(archived(&todos), ["d", "a", "c"]);
+    todos.remove_by_index(0);
+    assert_eq!(listed(&todos), Vec::<&str>::new());
+    assert_eq!(archived(&todos), ["b", "d", "a", "c"]);
+    assert!(todos.archive.take_changed());
+    assert!(!todos.archive.take_changed());
+}
+
+#[test]
+fn restoring_puts_entries_back() {
+    let mut todos = todos(&["Trip", "Groceries"]);
+    let trip = id_of(&todos, "Trip");
+    todos.add_subtask(&trip, "Pack".to_string());
+    todos.add_subtask(&trip, "Visa".to_string());
+    todos.toggle_completion(&id_of(&todos, "Visa")).unwrap();
+    todos.clear_completed();
+    todos.remove_by_id(&id_of(&todos, "Groceries"));
+    assert_eq!(listed(&todos), ["Trip", "Pack"]);
+
+    // Back under its parent, which is now in progress
+    let visa = todos.archive.entries[1].entry.id;
+    assert!(todos.restore(&visa));
+    assert_eq!(listed(&todos), ["Trip", "Pack", "Visa"]);
+    assert_eq!(todos.get_entry_by_id(&trip).unwrap().status, Status::InProgress);
+
+    // At the end of the list when the parent is gone
+    todos.remove_by_id(&id_of(&todos, "Visa"));
+    todos.remove_by_id(&trip);
+    let visa = todos.archive.entries.iter().find(|e| e.entry.description == "Visa").unwrap();
+    assert_eq!(visa.parent, Some(trip));
+    let visa = visa.entry.id;
+    assert!(todos.restore(&visa));
+    assert!(!todos.restore(&visa));
+    assert_eq!(listed(&todos), ["Visa"]);
+
+    // A removed parent comes back with its subtasks
+    assert!(todos.restore(&trip));
+    assert_eq!(listed(&todos), ["Visa", "Trip", "Pack"]);
+
+    let groceries = todos.archive.entries[0].entry.id;
+    assert!(todos.delete_archived(&groceries));
+    assert!(!todos.restore(&groceries));
+    assert!(todos.archive.is_empty());
+}
+
+#[test]
+fn archived_entries_are_purged_after_the_retention() {
+    let clock = Rc::new(ManualClock::new(Timestamp(0)));
+    set_clock(clock.clone());
+    let mut todos = todos(&["old", "recent"]);
+    todos.remove_by_id(&id_of(&todos, "old"));
+    clock.advance(20 * DAY);
+    todos.remove_by_id(&id_of(&todos, "recent"));
+
+    clock.advance(10 * DAY);
+    assert_eq!(todos.archive.purge(now()), 1);
+    assert_eq!(archived(&todos), ["recent"]);
+
+    todos.set_retention_days(None);
+    clock.advance(365 * DAY);
+    assert_eq!(todos.archive.purge(now()), 0);
+
+    todos.set_retention_days(Some(7));
+    assert!(todos.archive.is_empty());
+}
+
+#[test]
+fn the_archive_is_saved_apart_from_the_list() {
+    let mut todos = todos(&["kept", "removed"]);
+    todos.remove_by_id(&id_of(&todos, "removed"));
+    let json = serde_json::to_string(&todos).unwrap();
+    assert!(!json.contains("removed"));
+
+    let archive: Archive = serde_json::from_str(&serde_json::to_string(&todos.archive).unwrap()).unwrap();
+    assert_eq!(archive.entries[0].entry.description, "removed");
+    assert_eq!(archive.retention_days, Some(DEFAULT_RETENTION_DAYS));
+    assert_eq!(archive_key("todos-spair-keyed"), "todos-spair-keyed.archive");
+
+    todos.set_route(Route::parse("#/archive"));
+    assert_eq!(todos.filter, Filter::Archive);
+}
@@ -0,0 +1,17 @@
+// Every test crate only uses some of the helpers
+#![allow(dead_code)]
+
+use todomvc_shared::*;
+
+/// An entry for each of `inputs`, parsed like the new-todo input.
+pub fn todos(inputs: &[&str]) -> Todos {
+    let mut todos = Todos::default();
+    for input in inputs {
+        todos.new_entry_parsed(input.to_string());
+    }
+    todos
+}
+
+pub fn id_of(todos: &Todos, description: &str) -> uuid::Uuid {
+    todos.iter_tree().find(|e| e.description == description).unwrap().id
+}
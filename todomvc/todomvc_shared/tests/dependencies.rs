@@ -0,0 +1,156 @@
+use todomvc_shared::*;
+
+mod common;
+use common::{id_of, todos};
+
+fn block(todos: &mut Todos, blocked: &str, blocker: &str) -> Result<(), DependencyError> {
+    let (blocked, blocker) = (id_of(todos, blocked), id_of(todos, blocker));
+    todos.add_dependency(&blocked, &blocker)
+}
+
+fn complete(todos: &mut Todos, description: &str) -> Result<(), DependencyError> {
+    let id = id_of(todos, description);
+    todos.set_completed(&id, true)
+}
+
+fn next(todos: &Todos) -> Vec<&str> {
+    todos.next_entries().into_iter().map(|e| e.description.as_str()).collect()
+}
+
+#[test]
+fn blocked_entries_cannot_be_completed() {
+    let mut todos = todos(&["Deploy", "Review", "Test"]);
+    block(&mut todos, "Deploy", "Review").unwrap();
+    block(&mut todos, "Deploy", "Test").unwrap();
+    assert!(todos.is_blocked(&id_of(&todos, "Deploy")));
+
+    let error = complete(&mut todos, "Deploy").unwrap_err();
+    assert_eq!(error.to_string(), r#""Deploy" is blocked by "Review", "Test""#);
+    assert!(!todos.get_entry_by_id(&id_of(&todos, "Deploy")).unwrap().is_completed());
+
+    complete(&mut todos, "Review").unwrap();
+    let error = complete(&mut todos, "Deploy").unwrap_err();
+    assert_eq!(error.to_string(), r#""Deploy" is blocked by "Test""#);
+
+    complete(&mut todos, "Test").unwrap();
+    assert!(!todos.is_blocked(&id_of(&todos, "Deploy")));
+    complete(&mut todos, "Deploy").unwrap();
+}
+
+#[test]
+fn rejects_cycles_with_the_whole_chain() {
+    let mut todos = todos(&["Get paid", "Pay rent", "Move in"]);
+    block(&mut todos, "Pay rent", "Get paid").unwrap();
+    block(&mut todos, "Move in", "Pay rent").unwrap();
+
+    let error = block(&mut todos, "Get paid", "Move in").unwrap_err();
+    assert_eq!(
+        error,
+        DependencyError::Cycle(vec![
+            "Get paid".to_string(),
+            "Move in".to_string(),
+            "Pay rent".to_string(),
+            "Get paid".to_string(),
+        ])
+    );
+    assert_eq!(
+        error.to_string(),
+        r#"this would create a cycle: "Get paid" would be blocked by "Move in", which is blocked by "Pay rent", which is blocked by "Get paid""#
+    );
+
+    let id = id_of(&todos, "Get paid");
+    assert_eq!(todos.add_dependency(&id, &id), Err(DependencyError::SelfDependency));
+    let unknown = uuid::Uuid::new_v4();
+    assert_eq!(todos.add_dependency(&id, &unknown), Err(DependencyError::UnknownEntry(unknown)));
+    // Adding an existing link again is fine
+    block(&mut todos, "Pay rent", "Get paid").unwrap();
+}
+
+#[test]
+fn blocking_a_parent_blocks_its_subtasks() {
+    let mut todos = todos(&["Trip", "Passport"]);
+    todos.add_subtask(&id_of(&todos, "Trip"), "Book flights".to_string());
+    todos.add_subtask(&id_of(&todos, "Trip"), "Pack".to_string());
+    block(&mut todos, "Trip", "Passport").unwrap();
+
+    assert!(todos.is_blocked(&id_of(&todos, "Pack")));
+    assert!(complete(&mut todos, "Pack").is_err());
+    assert_eq!(next(&todos), ["Passport", "Book flights", "Pack"]);
+
+    // A parent and its subtask cannot wait for each other
+    assert!(matches!(block(&mut todos, "Trip", "Pack"), Err(DependencyError::Nested { .. })));
+    assert!(matches!(block(&mut todos, "Pack", "Trip"), Err(DependencyError::Nested { .. })));
+
+    // A cycle through a subtask: "Passport" waits for "Pack", which waits
+    // for "Passport" through its parent
+    assert!(matches!(block(&mut todos, "Passport", "Pack"), Err(DependencyError::Cycle(_))));
+}
+
+#[test]
+fn completing_a_subtask_cannot_complete_a_blocked_parent() {
+    let mut todos = todos(&["Trip", "Visa"]);
+    todos.add_subtask(&id_of(&todos, "Trip"), "Pack".to_string());
+    todos.add_subtask(&id_of(&todos, "Visa"), "Fill the form".to_string());
+    block(&mut todos, "Trip", "Visa").unwrap();
+
+    let error = complete(&mut todos, "Pack").unwrap_err();
+    assert_eq!(error.to_string(), r#""Pack" is blocked by "Visa""#);
+    complete(&mut todos, "Fill the form").unwrap();
+    complete(&mut todos, "Pack").unwrap();
+    assert!(todos.get_entry_by_id(&id_of(&todos, "Trip")).unwrap().is_completed());
+}
+
+#[test]
+fn next_entries_are_in_topological_order() {
+    let mut todos = todos(&["Deploy", "Review", "Write code", "Coffee", "Test"]);
+    block(&mut todos, "Deploy", "Review").unwrap();
+    block(&mut todos, "Deploy", "Test").unwrap();
+    block(&mut todos, "Review", "Write code").unwrap();
+    block(&mut todos, "Test", "Write code").unwrap();
+    assert_eq!(next(&todos), ["Write code", "Coffee", "Review", "Test", "Deploy"]);
+
+    complete(&mut todos, "Write code").unwrap();
+    assert_eq!(next(&todos), ["Review", "Coffee", "Test", "Deploy"]);
+
+    todos.set_route(Route::parse("#/next"));
+    let visible: Vec<_> = todos.get_visible_entries().iter().map(|e| e.description.clone()).collect();
+    assert_eq!(visible, ["Review", "Coffee", "Test", "Deploy"]);
+    assert!(!todos.move_entry(&id_of(&todos, "Deploy"), 0));
+}
+
+#[test]
+fn toggle_all_skips_entries_blocked_from_outside_the_view() {
+    let mut todos = todos(&["Deploy", "Review", "Release notes #docs"]);
+    block(&mut todos, "Deploy", "Review").unwrap();
+    block(&mut todos, "Release notes", "Deploy").unwrap();
+
+    todos.set_route(Route::parse("#/tag/docs"));
+    todos.set_completed_for_all(true);
+    assert_eq!(todos.completed_count(), 0);
+
+    // Blockers completed at the same time do not count
+    todos.set_route(Route::default());
+    todos.set_completed_for_all(true);
+    assert!(todos.is_all_completed());
+}
+
+#[test]
+fn removing_entries_removes_their_links() {
+    let mut todos = todos(&["Deploy", "Review", "Test"]);
+    block(&mut todos, "Deploy", "Review").unwrap();
+    block(&mut todos, "Deploy", "Test").unwrap();
+
+    let review = id_of(&todos, "Review");
+    todos.remove_by_id(&review);
+    let deploy = id_of(&todos, "Deploy");
+    assert_eq!(todos.dependencies.blockers(&deploy).count(), 1);
+
+    complete(&mut todos, "Test").unwrap();
+    todos.clear_completed();
+    assert!(todos.dependencies.is_empty());
+    assert!(!todos.is_blocked(&deploy));
+
+    let json = serde_json::to_string(&todos).unwrap();
+    let loaded: Todos = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded.dependencies, todos.dependencies);
+}
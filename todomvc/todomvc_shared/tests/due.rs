@@ -0,0 +1,121 @@
+use std::rc::Rc;
+
+use todomvc_shared::*;
+
+fn date(text: &str) -> Date {
+    Date::parse_iso(text).unwrap()
+}
+
+fn todos_due(dues: &[(Option<&str>, bool)]) -> Todos {
+    let mut todos = Todos::default();
+    for (index, (due, completed)) in dues.iter().enumerate() {
+        todos.new_entry(format!("todo {index}"));
+        let entry = todos.entries.last_mut().unwrap();
+        entry.set_due(due.map(date));
+        let id = entry.id;
+        todos.set_completed(&id, *completed).unwrap();
+    }
+    todos
+}
+
+fn visible(todos: &mut Todos, filter: Filter) -> Vec<String> {
+    todos.filter = filter;
+    todos.get_visible_entries().iter().map(|e| e.description.clone()).collect()
+}
+
+#[test]
+fn today_follows_the_local_day() {
+    // 2026-03-01 23:30 UTC
+    let clock = Rc::new(ManualClock::new(Timestamp(
+        date("2026-03-01").days_since_epoch() * DAY + 23 * HOUR + 30 * MINUTE,
+    )));
+    set_clock(clock.clone());
+    assert_eq!(Date::today(), date("2026-03-01"));
+
+    // Already the next day in UTC+1, still the previous day in UTC-1
+    clock.set_utc_offset_minutes(60);
+    assert_eq!(Date::today(), date("2026-03-02"));
+    clock.set_utc_offset_minutes(-60);
+    assert_eq!(Date::today(), date("2026-03-01"));
+    clock.advance(2 * HOUR);
+    assert_eq!(Date::today(), date("2026-03-02"));
+}
+
+#[test]
+fn filters_by_due_date() {
+    set_clock(Rc::new(ManualClock::new(date("2026-01-31").start_timestamp_local())));
+    let mut todos = todos_due(&[
+        (None, false),
+        (Some("2026-01-30"), false),
+        (Some("2026-01-30"), true),
+        (Some("2026-01-31"), false),
+        (Some("2026-02-01"), false),
+    ]);
+
+    assert_eq!(visible(&mut todos, Filter::Overdue), ["todo 1"]);
+    assert_eq!(visible(&mut todos, Filter::DueToday), ["todo 3"]);
+    assert_eq!(visible(&mut todos, Filter::Upcoming), ["todo 4"]);
+    assert_eq!(visible(&mut todos, Filter::All).len(), 5);
+
+    // Tomorrow, today's entry is overdue
+    set_clock(Rc::new(ManualClock::new(date("2026-02-01").start_timestamp_local())));
+    assert_eq!(visible(&mut todos, Filter::Overdue), ["todo 1", "todo 3"]);
+    assert_eq!(visible(&mut todos, Filter::DueToday), ["todo 4"]);
+    assert!(visible(&mut todos, Filter::Upcoming).is_empty());
+}
+
+#[test]
+fn due_labels() {
+    let today = date("2026-12-30");
+    let todos = todos_due(&[
+        (None, false),
+        (Some("2026-12-30"), false),
+        (Some("2026-12-31"), false),
+        (Some("2027-01-03"), false),
+        (Some("2027-02-01"), false),
+        (Some("2026-12-29"), false),
+        (Some("2026-12-25"), false),
+        (Some("2026-12-25"), true),
+    ]);
+    let labels: Vec<_> = todos.entries.iter().map(|e| e.due_label(today)).collect();
+    let expected = [
+        None,
+        Some("due today"),
+        Some("due tomorrow"),
+        Some("due in 4 days"),
+        Some("due 2027-02-01"),
+        Some("1 day overdue"),
+        Some("5 days overdue"),
+        Some("due 2026-12-25"),
+    ];
+    assert_eq!(labels, expected.map(|label| label.map(str::to_string)));
+}
+
+#[test]
+fn due_routes() {
+    for filter in [Filter::Overdue, Filter::DueToday, Filter::Upcoming] {
+        let route = Route::default().with_filter(filter).with_tag(Some("work"));
+        assert_eq!(Route::parse(&route.to_hash()), route);
+    }
+    assert_eq!(Route::parse("#/today").filter, Filter::DueToday);
+    assert_eq!(Filter::DueToday.to_string(), "Due today");
+}
+
+#[test]
+fn due_dates_are_serialized_as_iso_dates() {
+    let mut todos = todos_due(&[(Some("2026-02-28"), false), (None, false)]);
+    let json = serde_json::to_string(&todos.entries[0]).unwrap();
+    assert!(json.contains(r#""due":"2026-02-28""#), "{json}");
+
+    // Entries saved before due dates existed have none
+    let json = json.replace(r#","due":"2026-02-28""#, "");
+    let entry: TodoEntry = serde_json::from_str(&json).unwrap();
+    assert_eq!(entry.due, None);
+
+    let json = serde_json::to_string(&todos.entries[1]).unwrap();
+    todos.entries[1] = serde_json::from_str(&json).unwrap();
+    assert_eq!(todos.entries[1].due, None);
+
+    let invalid = r#"{"id":"67e55044-10b1-426f-9247-bb680e5fe0c8","description":"x","completed":false,"due":"2026-02-30"}"#;
+    assert!(serde_json::from_str::<TodoEntry>(invalid).is_err());
+}
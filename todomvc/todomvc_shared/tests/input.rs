@@ -0,0 +1,182 @@
+use std::rc::Rc;
+
+use todomvc_shared::*;
+
+// A Wednesday
+const TODAY: &str = "2026-01-28";
+
+fn date(text: &str) -> Date {
+    Date::parse_iso(text).unwrap()
+}
+
+struct Case {
+    input: &'static str,
+    description: &'static str,
+    due: Option<&'static str>,
+    priority: Priority,
+    tags: &'static [&'static str],
+}
+
+const fn case(
+    input: &'static str,
+    description: &'static str,
+    due: Option<&'static str>,
+    priority: Priority,
+) -> Case {
+    Case {
+        input,
+        description,
+        due,
+        priority,
+        tags: &[],
+    }
+}
+
+use Priority::{High, Low, Medium, None as NoPriority};
+
+const CASES: &[Case] = &[
+    // Nothing to recognise
+    case("buy milk", "buy milk", None, NoPriority),
+    case("  buy   milk  ", "buy milk", None, NoPriority),
+    case("tomorrow's news", "tomorrow's news", None, NoPriority),
+    case("next", "next", None, NoPriority),
+    case("next time", "next time", None, NoPriority),
+    case("in the garden", "in the garden", None, NoPriority),
+    case("in 3 hours", "in 3 hours", None, NoPriority),
+    case("in 0 days", "in 0 days", None, NoPriority),
+    case("in -2 days", "in -2 days", None, NoPriority),
+    case("in 99999 days", "in 99999 days", None, NoPriority),
+    case("bill due", "bill due", None, NoPriority),
+    case("due diligence", "due diligence", None, NoPriority),
+    case("wow!", "wow!", None, NoPriority),
+    case("! alone", "! alone", None, NoPriority),
+    case("!urgent", "!urgent", None, NoPriority),
+    case("2026-02-30 is not a date", "2026-02-30 is not a date", None, NoPriority),
+    case("2026-1-5", "2026-1-5", None, NoPriority),
+    // Relative dates
+    case("pay rent today", "pay rent", Some("2026-01-28"), NoPriority),
+    case("pay rent TODAY", "pay rent", Some("2026-01-28"), NoPriority),
+    case("pay rent tomorrow", "pay rent", Some("2026-01-29"), NoPriority),
+    case("Tomorrow pay rent", "pay rent", Some("2026-01-29"), NoPriority),
+    case("pay rent due tomorrow", "pay rent", Some("2026-01-29"), NoPriority),
+    case("in 3 days call mom", "call mom", Some("2026-01-31"), NoPriority),
+    case("call mom in 4 days", "call mom", Some("2026-02-01"), NoPriority),
+    case("call mom in 1 day", "call mom", Some("2026-01-29"), NoPriority),
+    case("call mom in a day", "call mom", Some("2026-01-29"), NoPriority),
+    case("call mom in a week", "call mom", Some("2026-02-04"), NoPriority),
+    case("call mom in 2 weeks", "call mom", Some("2026-02-11"), NoPriority),
+    case("call mom in 365 days", "call mom", Some("2027-01-28"), NoPriority),
+    case("call mom due in 3 days", "call mom", Some("2026-01-31"), NoPriority),
+    // `next <weekday>` is the first such day after today
+    case("standup next monday", "standup", Some("2026-02-02"), NoPriority),
+    case("standup next mon", "standup", Some("2026-02-02"), NoPriority),
+    case("standup next tuesday", "standup", Some("2026-02-03"), NoPriority),
+    case("standup next tue", "standup", Some("2026-02-03"), NoPriority),
+    case("standup next wednesday", "standup", Some("2026-02-04"), NoPriority),
+    case("standup next thursday", "standup", Some("2026-01-29"), NoPriority),
+    case("standup next thurs", "standup", Some("2026-01-29"), NoPriority),
+    case("standup next friday", "standup", Some("2026-01-30"), NoPriority),
+    case("standup next Saturday", "standup", Some("2026-01-31"), NoPriority),
+    case("standup next sun", "standup", Some("2026-02-01"), NoPriority),
+    case("standup next week", "standup", Some("2026-02-02"), NoPriority),
+    case("standup next month", "standup next month", None, NoPriority),
+    // Absolute dates
+    case("taxes 2026-04-15", "taxes", Some("2026-04-15"), NoPriority),
+    case("taxes due 2026-04-15", "taxes", Some("2026-04-15"), NoPriority),
+    case("leap day 2028-02-29", "leap day", Some("2028-02-29"), NoPriority),
+    case("history 1999-12-31", "history", Some("1999-12-31"), NoPriority),
+    // Only the first date counts
+    case("today or tomorrow", "or tomorrow", Some("2026-01-28"), NoPriority),
+    case("2026-03-01 then 2026-03-02", "then 2026-03-02", Some("2026-03-01"), NoPriority),
+    // Priorities
+    case("taxes !high", "taxes", None, High),
+    case("taxes !HIGH", "taxes", None, High),
+    case("taxes !h", "taxes", None, High),
+    case("taxes !!!", "taxes", None, High),
+    case("taxes !medium", "taxes", None, Medium),
+    case("taxes !med", "taxes", None, Medium),
+    case("taxes !m", "taxes", None, Medium),
+    case("taxes !!", "taxes", None, Medium),
+    case("taxes !low", "taxes", None, Low),
+    case("taxes !l", "taxes", None, Low),
+    case("!high taxes", "taxes", None, High),
+    case("taxes !low !high", "taxes !high", None, Low),
+    // Everything together
+    case("pay rent tomorrow !high", "pay rent", Some("2026-01-29"), High),
+    case("!!! pay rent in 2 days", "pay rent", Some("2026-01-30"), High),
+    case("pay rent next friday !l", "pay rent", Some("2026-01-30"), Low),
+    // An input with nothing left is kept as it is
+    case("tomorrow", "tomorrow", Some("2026-01-29"), NoPriority),
+    case("!high", "!high", None, High),
+    case("today !!", "today !!", Some("2026-01-28"), Medium),
+];
+
+#[test]
+fn parses_dates_and_priorities() {
+    let today = date(TODAY);
+    for case in CASES {
+        let parsed = ParsedInput::parse(case.input, today);
+        assert_eq!(parsed.description, case.description, "{:?}", case.input);
+        assert_eq!(parsed.due, case.due.map(date), "{:?}", case.input);
+        assert_eq!(parsed.priority, case.priority, "{:?}", case.input);
+        let tags: Vec<_> = parsed.tags.iter().map(String::as_str).collect();
+        assert_eq!(tags, case.tags, "{:?}", case.input);
+    }
+}
+
+#[test]
+fn parses_tags_along_with_the_rest() {
+    let parsed = ParsedInput::parse("pay rent #home tomorrow !high #Money", date(TODAY));
+    assert_eq!(parsed.description, "pay rent");
+    assert_eq!(parsed.due, Some(date("2026-01-29")));
+    assert_eq!(parsed.priority, High);
+    assert_eq!(parsed.tags.into_iter().collect::<Vec<_>>(), ["home", "money"]);
+}
+
+#[test]
+fn relative_dates_cross_month_and_year_ends() {
+    let cases = [
+        ("2026-01-31", "tomorrow", "2026-02-01"),
+        ("2026-02-28", "tomorrow", "2026-03-01"),
+        ("2028-02-28", "tomorrow", "2028-02-29"),
+        ("2026-12-31", "tomorrow", "2027-01-01"),
+        ("2026-12-30", "in 3 days", "2027-01-02"),
+        ("2026-12-28", "next sunday", "2027-01-03"),
+        // 2026-02-02 is a Monday, so "next monday" is a week later
+        ("2026-02-02", "next monday", "2026-02-09"),
+        ("2026-02-01", "next monday", "2026-02-02"),
+    ];
+    for (today, input, due) in cases {
+        let (parsed, _) = parse_date(&input.split(' ').collect::<Vec<_>>(), date(today)).unwrap();
+        assert_eq!(parsed, date(due), "{input} on {today}");
+    }
+}
+
+#[test]
+fn new_entry_uses_the_local_day() {
+    // 23:30 UTC on 2026-01-28 is already the 29th in UTC+1
+    let clock = Rc::new(ManualClock::new(Timestamp(
+        date(TODAY).days_since_epoch() * DAY + 23 * HOUR + 30 * MINUTE,
+    )));
+    clock.set_utc_offset_minutes(60);
+    set_clock(clock);
+
+    let mut todos = Todos::default();
+    todos.new_entry_parsed("pay rent tomorrow !high #home".to_string());
+    let entry = &todos.entries[0];
+    assert_eq!(entry.description, "pay rent");
+    assert_eq!(entry.due, Some(date("2026-01-30")));
+    assert_eq!(entry.priority, High);
+    assert!(entry.tags.contains("home"));
+}
+
+#[test]
+fn new_entry_keeps_the_input_as_it_is() {
+    let mut todos = Todos::default();
+    todos.new_entry("watch today tomorrow next mon !high #home".to_string());
+    let entry = &todos.entries[0];
+    assert_eq!(entry.description, "watch today tomorrow next mon !high #home");
+    assert_eq!(entry.due, None);
+    assert_eq!(entry.priority, Priority::None);
+    assert!(entry.tags.is_empty());
+}
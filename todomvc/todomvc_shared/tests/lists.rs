@@ -0,0 +1,114 @@
+use todomvc_shared::*;
+
+const KEY: &str = "todos-test";
+
+fn names(lists: &TodoLists) -> Vec<&str> {
+    lists.lists.iter().map(|list| list.name.as_str()).collect()
+}
+
+#[test]
+fn creates_renames_and_deletes_lists() {
+    let mut lists = TodoLists::new(KEY, Todos::default());
+    assert_eq!(names(&lists), [DEFAULT_LIST_NAME]);
+    assert_eq!(lists.current().storage_key, KEY);
+
+    let work = lists.create(" Work ").unwrap();
+    assert_eq!(lists.current().id, work);
+    assert_eq!(lists.current().storage_key, format!("{KEY}.list.{work}"));
+    assert_eq!(lists.create("work"), None);
+    assert_eq!(lists.create(""), None);
+    let home = lists.create("Home").unwrap();
+    assert_eq!(names(&lists), ["Todos", "Work", "Home"]);
+
+    assert!(lists.rename(&home, "House"));
+    assert!(lists.rename(&home, "house"));
+    assert!(!lists.rename(&home, "WORK"));
+    assert!(!lists.rename(&uuid::Uuid::new_v4(), "Garden"));
+    assert_eq!(names(&lists), ["Todos", "Work", "house"]);
+
+    // Deleting another list keeps the current one, deleting the current
+    // one switches to the first
+    lists.switch(&home);
+    assert!(lists.delete(&work));
+    assert_eq!(lists.current().id, home);
+    assert!(lists.delete(&home));
+    assert_eq!(names(&lists), ["Todos"]);
+    assert_eq!(lists.current().name, "Todos");
+    let first = lists.current().id;
+    assert!(!lists.delete(&first));
+    assert_eq!(
+        lists.take_deleted(),
+        [format!("{KEY}.list.{work}"), format!("{KEY}.list.{home}")]
+    );
+    assert!(lists.take_deleted().is_empty());
+}
+
+#[test]
+fn every_list_has_its_own_todos_and_counts() {
+    let mut lists = TodoLists::new(KEY, Todos::default());
+    lists.new_entry("report".to_string());
+    let work = lists.create("Work").unwrap();
+    lists.new_entry("slides".to_string());
+    lists.new_entry("email".to_string());
+    let id = lists.entries[0].id;
+    lists.toggle_completion(&id).unwrap();
+
+    let counts: Vec<_> = lists.lists.iter().map(|list| (list.active_count(), list.entry_count())).collect();
+    assert_eq!(counts, [(1, 1), (1, 2)]);
+
+    let first = lists.lists[0].id;
+    lists.switch(&first);
+    assert_eq!(lists.entries[0].description, "report");
+    assert!(!lists.switch(&uuid::Uuid::new_v4()));
+    assert_eq!(lists.current().id, first);
+    lists.switch(&work);
+    assert_eq!(lists.entry_count(), 2);
+}
+
+#[test]
+fn routes_select_the_list() {
+    let mut lists = TodoLists::new(KEY, Todos::default());
+    let work = lists.create("Work").unwrap();
+
+    let route = Route::parse(&format!("#/list/{work}/active?q=milk"));
+    assert_eq!(route.list, Some(work));
+    assert_eq!(route.filter, Filter::Active);
+    assert_eq!(route.to_hash(), format!("#/list/{work}/active?q=milk"));
+    let route = Route::parse(&format!("#/list/{work}/tag/home/completed"));
+    assert_eq!((route.list, route.tag.as_deref(), route.filter), (Some(work), Some("home"), Filter::Completed));
+    assert_eq!(route.to_hash(), format!("#/list/{work}/tag/home/completed"));
+    assert_eq!(Route::parse(&format!("#/list/{work}")).to_hash(), format!("#/list/{work}"));
+    assert_eq!(Route::parse("#/list/nonsense/active").list, None);
+
+    // The first list has the routes it had before there were lists
+    lists.set_route(Route::parse("#/completed"));
+    assert_eq!(lists.current().name, "Todos");
+    assert_eq!(lists.route().to_hash(), "#/completed");
+    lists.set_route(Route::parse(&format!("#/list/{work}/active")));
+    assert_eq!(lists.current().id, work);
+    assert_eq!(lists.filter, Filter::Active);
+    assert_eq!(lists.route().to_hash(), format!("#/list/{work}/active"));
+
+    let first = lists.lists[0].id;
+    assert_eq!(lists.route_to(&first, &lists.route()).to_hash(), "#/active");
+
+    // A list that does not exist anymore shows the first one
+    lists.delete(&work);
+    lists.set_route(Route::parse(&format!("#/list/{work}/active")));
+    assert_eq!(lists.current().id, first);
+}
+
+#[test]
+fn names_are_saved_without_the_todos() {
+    let mut lists = TodoLists::new(KEY, Todos::default());
+    lists.new_entry("report".to_string());
+    lists.create("Work");
+    let json = serde_json::to_string(&lists.lists).unwrap();
+    assert!(json.contains("Work"));
+    assert!(!json.contains("report"));
+
+    let loaded: Vec<TodoList<Todos>> = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded.len(), 2);
+    assert!(loaded[0].todos.entries.is_empty());
+    assert_eq!(lists_key(KEY), "todos-test.lists");
+}
@@ -0,0 +1,66 @@
+use todomvc_shared::*;
+
+mod common;
+use common::todos;
+
+fn visible(todos: &Todos) -> Vec<&str> {
+    todos.get_visible_entries().iter().map(|e| e.description.as_str()).collect()
+}
+
+#[test]
+fn cycles_through_every_priority() {
+    let mut todos = todos(&["a"]);
+    let entry = &mut todos.entries[0];
+    let mut seen = Vec::new();
+    for _ in 0..5 {
+        seen.push(entry.priority);
+        entry.cycle_priority();
+    }
+    use Priority::*;
+    assert_eq!(seen, [None, Low, Medium, High, None]);
+}
+
+#[test]
+fn sorts_by_priority_keeping_insertion_order() {
+    let mut todos = todos(&["a !low", "b", "c !high", "d !medium", "e !high"]);
+    todos.sort = SortOrder::Priority;
+    assert_eq!(visible(&todos), ["c", "e", "d", "a", "b"]);
+    assert_eq!(Route::parse("#/?sort=priority").sort, SortOrder::Priority);
+}
+
+#[test]
+fn filters_by_priority() {
+    let mut todos = todos(&["a !low", "b", "c !high"]);
+    todos.query = "priority:high".to_string();
+    assert_eq!(visible(&todos), ["c"]);
+    todos.query = "-priority:none".to_string();
+    assert_eq!(visible(&todos), ["a", "c"]);
+    todos.query = "priority:urgent".to_string();
+    let error = todos.parse_query().unwrap_err();
+    assert_eq!(error.span, 9..15);
+
+    let query = Query::parse("priority:medium").unwrap();
+    assert_eq!(query.to_string(), "priority:medium");
+}
+
+#[test]
+fn priority_is_persisted() {
+    let mut todos = todos(&["a"]);
+    todos.entries[0].set_priority(Priority::Medium);
+    let json = serde_json::to_string(&todos).unwrap();
+    let loaded: Todos = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded.entries[0].priority, Priority::Medium);
+
+    // Saved before priorities existed
+    let json = json.replace(r#","priority":"Medium""#, "").replace(r#""schema_version":3"#, r#""schema_version":2"#);
+    let mut loaded: Todos = serde_json::from_str(&json).unwrap();
+    loaded.migrate();
+    assert_eq!(loaded.entries[0].priority, Priority::None);
+    assert_eq!(loaded.schema_version, SCHEMA_VERSION);
+}
+
+#[test]
+fn classes() {
+    assert_eq!(Priority::None.class_name(), None);
+    assert_eq!(Priority::High.class_name(), Some("priority-high"));
+}
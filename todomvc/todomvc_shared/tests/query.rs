@@ -0,0 +1,143 @@
+use std::rc::Rc;
+
+use todomvc_shared::*;
+
+fn entry(description: &str, completed: bool, created_at: Timestamp) -> TodoEntry {
+    TodoEntry {
+        status: if completed { Status::Done } else { Status::Todo },
+        created_at,
+        updated_at: created_at,
+        completed_at: completed.then_some(created_at),
+        tags: description.split_whitespace().filter_map(parse_tag).collect(),
+        ..TodoEntry::new(description)
+    }
+}
+
+fn at(date: &str) -> Timestamp {
+    Date::parse_iso(date).unwrap().start_timestamp_local()
+}
+
+#[test]
+fn parses_every_kind_of_term() {
+    let query =
+        Query::parse(r#"is:active "buy milk" -is:completed tag:work created:>2026-01-01"#).unwrap();
+    let conditions: Vec<_> = query.terms.iter().map(|t| (t.negated, &t.condition)).collect();
+    assert_eq!(conditions.len(), 5);
+    assert_eq!(conditions[0], (false, &Condition::Is(Filter::Active)));
+    assert!(matches!(conditions[1], (false, Condition::Text { phrase, .. }) if phrase == "buy milk"));
+    assert_eq!(conditions[2], (true, &Condition::Is(Filter::Completed)));
+    assert_eq!(conditions[3], (false, &Condition::Tag("work".to_string())));
+    assert_eq!(
+        conditions[4],
+        (
+            false,
+            &Condition::Date {
+                field: DateField::Created,
+                op: DateOp::Gt,
+                date: Date::new(2026, 1, 1).unwrap(),
+            }
+        )
+    );
+    assert_eq!(query.terms[1].span, 10..20);
+}
+
+#[test]
+fn plain_words_with_colons_are_text() {
+    let query = Query::parse("meet at 12:30").unwrap();
+    assert_eq!(query.terms.len(), 3);
+    assert!(matches!(&query.terms[2].condition, Condition::Text { phrase, .. } if phrase == "12:30"));
+}
+
+#[test]
+fn reports_errors_with_spans() {
+    let cases = [
+        ("milk \"buy", 5..9, "unterminated quote"),
+        ("is:", 0..3, "missing value"),
+        ("milk is:later", 8..13, "unknown status"),
+        ("tga:work", 0..3, "unknown field"),
+        ("created:>2026-13-01", 8..19, "invalid date"),
+        ("a - b", 2..3, "expected a term"),
+        ("\"a\"b", 3..4, "expected whitespace"),
+    ];
+    for (text, span, message) in cases {
+        let error = Query::parse(text).unwrap_err();
+        assert_eq!(error.span, span, "{text}");
+        assert!(error.message.starts_with(message), "{text}: {error}");
+    }
+}
+
+#[test]
+fn round_trips_through_text_and_url_hash() {
+    let texts = [
+        r#"is:active "buy milk" -is:completed tag:work created:>2026-01-01"#,
+        r#""say \"hi\"" -"a b" updated:<=2025-12-31 completed:2026-02-03"#,
+        r#""12:30" "-x" tag:"two words" is:done"#,
+        "café",
+    ];
+    for text in texts {
+        let query = Query::parse(text).unwrap();
+        let canonical = query.to_string();
+        let reparsed = Query::parse(&canonical).unwrap();
+        assert_eq!(reparsed.to_string(), canonical);
+        assert_eq!(
+            reparsed.terms.iter().map(|t| &t.condition).collect::<Vec<_>>(),
+            query.terms.iter().map(|t| &t.condition).collect::<Vec<_>>(),
+        );
+
+        let route = Route::default().with_filter(Filter::Active).with_query(&canonical);
+        let parsed_route = Route::parse(&route.to_hash());
+        assert_eq!(parsed_route, route);
+        assert_eq!(Query::parse(&parsed_route.query).unwrap().to_string(), canonical);
+    }
+}
+
+#[test]
+fn matches_entries() {
+    let clock = Rc::new(ManualClock::new(at("2026-03-01")));
+    clock.set_utc_offset_minutes(120);
+    set_clock(clock);
+
+    let milk = entry("Buy milk #errands", false, at("2026-01-15"));
+    let report = entry("Write report #work", true, at("2025-12-31"));
+    let review = entry("Review PR #Work", false, at("2026-01-01"));
+
+    let check = |text: &str, expected: [bool; 3]| {
+        let query = Query::parse(text).unwrap();
+        let actual = [&milk, &report, &review].map(|e| query.matches(e));
+        assert_eq!(actual, expected, "{text}");
+    };
+    check("", [true, true, true]);
+    check("milk", [true, false, false]);
+    check("-milk", [false, true, true]);
+    check("\"buy milk\"", [true, false, false]);
+    check("is:active", [true, false, true]);
+    check("-is:completed", [true, false, true]);
+    check("tag:work", [false, true, true]);
+    check("tag:#WORK is:active", [false, false, true]);
+    check("created:2026-01-01", [false, false, true]);
+    check("created:>2026-01-01", [true, false, false]);
+    check("created:>=2026-01-01", [true, false, true]);
+    check("created:<2026-01-01", [false, true, false]);
+    check("created:<=2026-01-01", [false, true, true]);
+    check("completed:<2027-01-01", [false, true, false]);
+    check("-completed:<2027-01-01", [true, false, true]);
+}
+
+#[test]
+fn todos_use_the_query() {
+    let mut todos = Todos {
+        entries: vec![
+            entry("Buy milk #errands", false, Timestamp(0)),
+            entry("Write report #work", true, Timestamp(0)),
+        ],
+        query: "is:completed report".to_string(),
+        ..Todos::default()
+    };
+    let visible: Vec<_> = todos.get_visible_entries().iter().map(|e| e.description.clone()).collect();
+    assert_eq!(visible, ["Write report #work"]);
+    assert!(!todos.search().is_empty());
+
+    todos.query = "is:unknown".to_string();
+    assert!(todos.parse_query().is_err());
+    assert_eq!(todos.get_visible_entries().len(), 2);
+}
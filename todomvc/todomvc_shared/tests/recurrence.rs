@@ -0,0 +1,229 @@
+use std::rc::Rc;
+
+use todomvc_shared::*;
+
+mod common;
+use common::todos;
+
+fn date(text: &str) -> Date {
+    Date::parse_iso(text).unwrap()
+}
+
+fn clock_on(text: &str) -> Rc<ManualClock> {
+    let clock = Rc::new(ManualClock::new(date(text).start_timestamp_local()));
+    set_clock(clock.clone());
+    clock
+}
+
+// Description, due date and whether it is completed, in list order
+fn entries(todos: &Todos) -> Vec<(String, String, bool)> {
+    todos
+        .entries
+        .iter()
+        .map(|e| (e.description.clone(), e.due.map(|d| d.to_string()).unwrap_or_default(), e.is_completed()))
+        .collect()
+}
+
+fn entry(description: &str, due: &str, completed: bool) -> (String, String, bool) {
+    (description.to_string(), due.to_string(), completed)
+}
+
+fn occurrences(recurrence: Recurrence, from: &str, count: usize) -> Vec<String> {
+    let mut date = date(from);
+    (0..count)
+        .map(|_| {
+            date = recurrence.after(date);
+            date.to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn months_keep_their_day_across_month_ends() {
+    let monthly = Recurrence::Months { every: 1, day: 31 };
+    assert_eq!(
+        occurrences(monthly, "2026-01-31", 4),
+        ["2026-02-28", "2026-03-31", "2026-04-30", "2026-05-31"]
+    );
+    let monthly = Recurrence::Months { every: 1, day: 30 };
+    assert_eq!(occurrences(monthly, "2028-01-30", 2), ["2028-02-29", "2028-03-30"]);
+
+    let quarterly = Recurrence::Months { every: 3, day: 31 };
+    assert_eq!(occurrences(quarterly, "2026-10-31", 2), ["2027-01-31", "2027-04-30"]);
+
+    let yearly = Recurrence::Months { every: 12, day: 29 };
+    assert_eq!(
+        occurrences(yearly, "2024-02-29", 4),
+        ["2025-02-28", "2026-02-28", "2027-02-28", "2028-02-29"]
+    );
+}
+
+#[test]
+fn days_and_weeks_cross_month_and_year_ends() {
+    assert_eq!(
+        occurrences(Recurrence::Days(3), "2026-02-26", 3),
+        ["2026-03-01", "2026-03-04", "2026-03-07"]
+    );
+    assert_eq!(occurrences(Recurrence::Days(1), "2028-02-28", 2), ["2028-02-29", "2028-03-01"]);
+    assert_eq!(occurrences(Recurrence::Weeks(2), "2026-12-24", 2), ["2027-01-07", "2027-01-21"]);
+    // 2026-03-28 is a Saturday
+    assert_eq!(
+        occurrences(Recurrence::Weekday(0), "2026-03-28", 3),
+        ["2026-03-30", "2026-04-06", "2026-04-13"]
+    );
+    // Every occurrence of a year of days, daylight saving time changes included
+    let days = occurrences(Recurrence::Days(1), "2025-12-31", 365);
+    assert_eq!(days.first().unwrap(), "2026-01-01");
+    assert_eq!(days.last().unwrap(), "2026-12-31");
+}
+
+#[test]
+fn next_due_skips_missed_occurrences() {
+    let every_3_days = Recurrence::Days(3);
+    // Completed early or on time: the next one on the schedule
+    assert_eq!(every_3_days.next_due(Some(date("2026-01-30")), date("2026-01-29")), date("2026-02-02"));
+    assert_eq!(every_3_days.next_due(Some(date("2026-01-30")), date("2026-01-30")), date("2026-02-02"));
+    // Completed late: the first one after today
+    assert_eq!(every_3_days.next_due(Some(date("2026-01-20")), date("2026-01-30")), date("2026-02-01"));
+    // Without a due date
+    assert_eq!(every_3_days.next_due(None, date("2026-01-30")), date("2026-02-02"));
+
+    let monthly = Recurrence::Months { every: 1, day: 31 };
+    assert_eq!(monthly.next_due(Some(date("2026-01-31")), date("2026-03-15")), date("2026-03-31"));
+}
+
+#[test]
+fn parses_recurrences_from_the_input() {
+    // A Wednesday
+    let today = date("2026-01-28");
+    let parse = |text| {
+        let input = ParsedInput::parse(text, today);
+        (input.description, input.recurrence, input.due.map(|d| d.to_string()))
+    };
+    assert_eq!(
+        parse("water plants every 3 days"),
+        ("water plants".to_string(), Some(Recurrence::Days(3)), Some("2026-01-28".to_string()))
+    );
+    assert_eq!(
+        parse("report every Monday"),
+        ("report".to_string(), Some(Recurrence::Weekday(0)), Some("2026-02-02".to_string()))
+    );
+    assert_eq!(
+        parse("pay rent every month 2026-01-31"),
+        ("pay rent".to_string(), Some(Recurrence::Months { every: 1, day: 31 }), Some("2026-01-31".to_string()))
+    );
+    assert_eq!(parse("stretch every day").1, Some(Recurrence::Days(1)));
+    assert_eq!(parse("review every 2 weeks").1, Some(Recurrence::Weeks(2)));
+    assert_eq!(parse("renew every year").1, Some(Recurrence::Months { every: 12, day: 28 }));
+    assert_eq!(parse("every now and then"), ("every now and then".to_string(), None, None));
+    assert_eq!(parse("every 0 days").1, None);
+
+    assert_eq!(Recurrence::Days(3).to_string(), "every 3 days");
+    assert_eq!(Recurrence::Weekday(0).to_string(), "every Monday");
+    assert_eq!(Recurrence::Months { every: 24, day: 1 }.to_string(), "every 2 years");
+}
+
+#[test]
+fn completing_adds_the_next_occurrence() {
+    let clock = clock_on("2026-01-28");
+    let mut todos = todos(&["water plants every 3 days #home", "coffee"]);
+    let id = todos.entries[0].id;
+    clock.advance(HOUR);
+    todos.toggle_completion(&id).unwrap();
+    assert_eq!(
+        entries(&todos),
+        [
+            entry("water plants", "2026-01-28", true),
+            entry("water plants", "2026-01-31", false),
+            entry("coffee", "", false),
+        ]
+    );
+    let next = &todos.entries[1];
+    assert!(next.tags.contains("home"));
+    assert_eq!(next.recurrence, Some(Recurrence::Days(3)));
+    assert_eq!(todos.entries[0].recurrence, None);
+    assert_eq!(todos.entries[0].next_occurrence, Some(next.id));
+
+    // Clearing keeps the next occurrence and its recurrence
+    todos.clear_completed();
+    assert_eq!(entries(&todos), [entry("water plants", "2026-01-31", false), entry("coffee", "", false)]);
+    assert_eq!(todos.entries[0].recurrence, Some(Recurrence::Days(3)));
+
+    // The series goes on, completing on the board too
+    let id = todos.entries[0].id;
+    todos.set_status(&id, Status::Done).unwrap();
+    assert_eq!(todos.entries[1].due, Some(date("2026-02-03")));
+}
+
+#[test]
+fn reopening_undoes_the_next_occurrence() {
+    let clock = clock_on("2026-01-28");
+    let mut todos = todos(&["report every Monday"]);
+    let id = todos.entries[0].id;
+    clock.advance(HOUR);
+    todos.toggle_completion(&id).unwrap();
+    assert_eq!(todos.entries.len(), 2);
+
+    // Reopened before anything happened to the next occurrence
+    clock.advance(HOUR);
+    todos.toggle_completion(&id).unwrap();
+    assert_eq!(entries(&todos), [entry("report", "2026-02-02", false)]);
+    assert_eq!(todos.entries[0].recurrence, Some(Recurrence::Weekday(0)));
+    assert_eq!(todos.entries[0].next_occurrence, None);
+
+    // Once the next occurrence was changed, reopening keeps both
+    todos.toggle_completion(&id).unwrap();
+    clock.advance(HOUR);
+    todos.entries[1].set_description("weekly report".to_string());
+    todos.toggle_completion(&id).unwrap();
+    assert_eq!(
+        entries(&todos),
+        [entry("report", "2026-02-02", false), entry("weekly report", "2026-02-09", false)]
+    );
+    assert_eq!(todos.entries[0].recurrence, None);
+    assert_eq!(todos.entries[1].recurrence, Some(Recurrence::Weekday(0)));
+}
+
+#[test]
+fn toggle_all_completes_the_current_occurrences() {
+    let clock = clock_on("2026-01-31");
+    let mut todos = todos(&["pay rent every month", "coffee"]);
+    clock.advance(HOUR);
+    todos.set_completed_for_all(true);
+    assert_eq!(
+        entries(&todos),
+        [
+            entry("pay rent", "2026-01-31", true),
+            entry("pay rent", "2026-02-28", false),
+            entry("coffee", "", true),
+        ]
+    );
+    assert!(!todos.is_all_completed());
+
+    // Reopening everything brings the list back to where it was
+    clock.advance(HOUR);
+    todos.set_completed_for_all(false);
+    assert_eq!(entries(&todos), [entry("pay rent", "2026-01-31", false), entry("coffee", "", false)]);
+    assert_eq!(todos.entries[0].recurrence, Some(Recurrence::Months { every: 1, day: 31 }));
+}
+
+#[test]
+fn subtasks_are_copied_but_do_not_recur() {
+    let clock = clock_on("2026-01-28");
+    let mut todos = todos(&["clean up every week"]);
+    let id = todos.entries[0].id;
+    todos.add_subtask_parsed(&id, "kitchen tomorrow".to_string());
+    todos.add_subtask_parsed(&id, "garden every day".to_string());
+    assert_eq!(todos.entries[0].children[1].recurrence, None);
+
+    clock.advance(HOUR);
+    for child in todos.entries[0].children.iter().map(|e| e.id).collect::<Vec<_>>() {
+        todos.set_completed(&child, true).unwrap();
+    }
+    assert_eq!(todos.entries.len(), 2);
+    let next = &todos.entries[1];
+    assert_eq!(next.due, Some(date("2026-02-04")));
+    assert_eq!(next.children.len(), 2);
+    assert!(next.children.iter().all(|e| !e.is_completed()));
+    assert_eq!(next.children[0].due, Some(date("2026-02-05")));
+}
@@ -0,0 +1,50 @@
+use todomvc_shared::*;
+
+mod common;
+use common::{id_of, todos};
+
+fn listed(todos: &Todos) -> Vec<&str> {
+    todos.entries.iter().map(|e| e.description.as_str()).collect()
+}
+
+// `a`, `b` and `c` are active, with completed entries in between
+fn active_view() -> Todos {
+    let mut todos = todos(&["a", "done 1", "b", "done 2", "c"]);
+    todos.toggle_completion(&id_of(&todos, "done 1")).unwrap();
+    todos.toggle_completion(&id_of(&todos, "done 2")).unwrap();
+    todos.filter = Filter::Active;
+    todos
+}
+
+#[test]
+fn moves_around_hidden_entries() {
+    let mut todos = active_view();
+    // Just before `b`, so after the completed entry that precedes it
+    assert!(todos.move_entry(&id_of(&todos, "c"), 1));
+    assert_eq!(listed(&todos), ["a", "done 1", "c", "b", "done 2"]);
+    assert_eq!(todos.filtered_position(&id_of(&todos, "c")), Some(1));
+
+    // Back to where it already is
+    assert!(!todos.move_entry(&id_of(&todos, "c"), 1));
+    assert_eq!(listed(&todos), ["a", "done 1", "c", "b", "done 2"]);
+}
+
+#[test]
+fn moves_to_the_first_and_last_position() {
+    let mut todos = active_view();
+    assert!(todos.move_entry(&id_of(&todos, "c"), 0));
+    assert_eq!(listed(&todos), ["c", "a", "done 1", "b", "done 2"]);
+
+    // Just after the last visible entry, before the hidden ones that follow it
+    assert!(todos.move_entry(&id_of(&todos, "c"), 2));
+    assert_eq!(listed(&todos), ["a", "done 1", "b", "c", "done 2"]);
+}
+
+#[test]
+fn rejects_positions_outside_of_the_view() {
+    let mut todos = active_view();
+    assert!(!todos.move_entry(&id_of(&todos, "a"), 3));
+    assert!(!todos.move_entry(&id_of(&todos, "a"), usize::MAX));
+    assert!(!todos.move_entry(&uuid::Uuid::new_v4(), 0));
+    assert_eq!(listed(&todos), ["a", "done 1", "b", "done 2", "c"]);
+}
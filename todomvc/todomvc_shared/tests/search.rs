@@ -0,0 +1,102 @@
+use todomvc_shared::*;
+
+fn segments<'a>(search: &Search, text: &'a str) -> Vec<(&'a str, bool)> {
+    search.split_matches(text).iter().map(|s| (s.text, s.matched)).collect()
+}
+
+#[test]
+fn folds_case_and_diacritics() {
+    let cases = [
+        ("cafe", "Un CAFÉ noir", true),
+        ("café", "cafe", true),
+        ("strasse", "Hauptstraße", true),
+        ("STRASSE", "HAUPTSTRASSE", true),
+        ("ore", "Øresund", true),
+        ("oresund", "Øresund", true),
+        ("aesir", "Æsir", true),
+        ("tieng viet", "Học TIẾNG VIỆT", true),
+        ("lodz", "Łódź", true),
+        ("cafe", "cofe", false),
+        ("ss", "s", false),
+    ];
+    for (search, text, expected) in cases {
+        assert_eq!(Search::new(search).matches(text), expected, "{search} in {text}");
+    }
+}
+
+#[test]
+fn combining_marks_are_ignored() {
+    // "é" as "e" and a combining acute accent
+    let decomposed = "Cafe\u{301} noir";
+    assert!(Search::new("café").matches(decomposed));
+    assert!(Search::new("cafe\u{301}").matches("Café"));
+    // The mark folds to nothing, it is left out of the match
+    assert_eq!(segments(&Search::new("cafe"), decomposed), [("Cafe", true), ("\u{301} noir", false)]);
+}
+
+#[test]
+fn splits_on_the_original_text() {
+    let search = Search::new("cafe");
+    assert_eq!(
+        segments(&search, "Un CAFÉ noir, café"),
+        [("Un ", false), ("CAFÉ", true), (" noir, ", false), ("café", true)]
+    );
+    // A char that folds to two is matched whole
+    assert_eq!(segments(&Search::new("s"), "ß"), [("ß", true)]);
+    assert_eq!(segments(&Search::all_of(["strasse", "haupt"]), "Hauptstraße"), [("Hauptstraße", true)]);
+    for text in ["", "Øresund Æsir", "Học TIẾNG VIỆT"] {
+        let joined: String = Search::new("e").split_matches(text).iter().map(|s| s.text).collect();
+        assert_eq!(joined, text);
+    }
+}
+
+#[test]
+fn search_combines_with_the_status_filter() {
+    let mut todos = Todos::default();
+    for description in ["Café au lait", "Cafe noir", "Thé vert"] {
+        todos.new_entry(description.to_string());
+    }
+    let id = todos.entries[0].id;
+    todos.toggle_completion(&id).unwrap();
+    todos.query = "CAFE".to_string();
+
+    let visible = |todos: &Todos| -> Vec<String> {
+        todos.get_visible_entries().iter().map(|e| e.description.clone()).collect()
+    };
+    assert_eq!(visible(&todos), ["Café au lait", "Cafe noir"]);
+    todos.filter = Filter::Active;
+    assert_eq!(visible(&todos), ["Cafe noir"]);
+    todos.filter = Filter::Completed;
+    assert_eq!(visible(&todos), ["Café au lait"]);
+    todos.query = "the".to_string();
+    assert!(visible(&todos).is_empty());
+    todos.filter = Filter::All;
+    assert_eq!(visible(&todos), ["Thé vert"]);
+}
+
+#[test]
+fn queries_and_tags_are_percent_encoded() {
+    let route = Route::default()
+        .with_filter(Filter::Active)
+        .with_tag(Some("été"))
+        .with_query("crème brûlée & tea=1 #x 50%+?/日本");
+    let hash = route.to_hash();
+    assert!(hash.is_ascii(), "{hash}");
+    assert_eq!(hash.matches('?').count(), 1, "{hash}");
+    assert_eq!(hash.matches('&').count(), 0, "{hash}");
+    assert!(hash.starts_with("#/tag/%C3%A9t%C3%A9/active?q=cr%C3%A8me%20"), "{hash}");
+    assert_eq!(Route::parse(&hash), route);
+
+    let reserved = Route::default().with_query("a/b?c=d&e#f%g+h");
+    assert_eq!(Route::parse(&reserved.to_hash()), reserved);
+}
+
+#[test]
+fn decodes_what_browsers_write() {
+    assert_eq!(Route::parse("#/?q=buy+milk").query, "buy milk");
+    assert_eq!(Route::parse("#/?q=caf%c3%a9").query, "café");
+    // Invalid or cut escapes are kept as they are
+    assert_eq!(Route::parse("#/?q=100%zz").query, "100%zz");
+    assert_eq!(Route::parse("#/?q=50%2").query, "50%2");
+    assert_eq!(Route::parse("#/?q=50%").query, "50%");
+}
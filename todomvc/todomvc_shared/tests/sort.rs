@@ -0,0 +1,88 @@
+use std::rc::Rc;
+
+use strum::IntoEnumIterator;
+use todomvc_shared::*;
+
+// One entry a minute, in the order of `descriptions`
+fn todos(descriptions: &[&str]) -> Todos {
+    let clock = Rc::new(ManualClock::new(Timestamp(1_700_000_000_000)));
+    set_clock(clock.clone());
+    let mut todos = Todos::default();
+    for description in descriptions {
+        todos.new_entry(description.to_string());
+        clock.advance(MINUTE);
+    }
+    todos
+}
+
+fn sorted(todos: &mut Todos, sort: SortOrder) -> Vec<String> {
+    todos.sort = sort;
+    todos.get_visible_entries().iter().map(|e| e.description.clone()).collect()
+}
+
+#[test]
+fn sorts_a_view_without_touching_the_entries() {
+    let mut todos = todos(&["b", "C", "a", "d"]);
+    for id in [todos.entries[1].id, todos.entries[3].id] {
+        todos.toggle_completion(&id).unwrap();
+    }
+
+    assert_eq!(sorted(&mut todos, SortOrder::Insertion), ["b", "C", "a", "d"]);
+    assert_eq!(sorted(&mut todos, SortOrder::Alphabetical), ["a", "b", "C", "d"]);
+    assert_eq!(sorted(&mut todos, SortOrder::Created), ["b", "C", "a", "d"]);
+    assert_eq!(sorted(&mut todos, SortOrder::CompletedLast), ["b", "a", "C", "d"]);
+    assert_eq!(sorted(&mut todos, SortOrder::ActiveFirst), ["a", "b", "d", "C"]);
+
+    let entries: Vec<_> = todos.entries.iter().map(|e| e.description.as_str()).collect();
+    assert_eq!(entries, ["b", "C", "a", "d"]);
+}
+
+#[test]
+fn equal_entries_keep_their_insertion_order() {
+    // Same description but for the case, same time, same completion
+    let mut todos = todos(&["x1", "x2", "x3", "x4"]);
+    for (i, entry) in todos.entries.iter_mut().enumerate() {
+        entry.description = if i % 2 == 0 { "same".to_string() } else { "SAME".to_string() };
+        entry.created_at = Timestamp(0);
+    }
+    let ids: Vec<_> = todos.entries.iter().map(|e| e.id).collect();
+    for sort in SortOrder::iter() {
+        todos.sort = sort;
+        let visible: Vec<_> = todos.get_visible_entries().iter().map(|e| e.id).collect();
+        assert_eq!(visible, ids, "{sort:?}");
+    }
+}
+
+#[test]
+fn sorts_10k_entries() {
+    let descriptions: Vec<String> = (0..10_000).map(|i| format!("Todo {}", (i * 7919) % 10_000)).collect();
+    let descriptions: Vec<&str> = descriptions.iter().map(String::as_str).collect();
+    let mut todos = todos(&descriptions);
+    for entry in todos.entries.iter_mut().step_by(3) {
+        entry.set_status(Status::Done);
+    }
+
+    for sort in SortOrder::iter() {
+        todos.sort = sort;
+        let visible = todos.get_visible_entries();
+        assert_eq!(visible.len(), 10_000);
+        for pair in visible.windows(2) {
+            assert_ne!(sort.compare(&pair[0], &pair[1]), std::cmp::Ordering::Greater, "{sort:?}");
+        }
+    }
+    // Stable: completed entries stay in insertion order
+    todos.sort = SortOrder::CompletedLast;
+    let completed: Vec<_> = todos.get_visible_entries().into_iter().filter(|e| e.is_completed()).map(|e| e.id).collect();
+    let expected: Vec<_> = todos.entries.iter().step_by(3).map(|e| e.id).collect();
+    assert_eq!(completed, expected);
+}
+
+#[test]
+fn sort_is_in_the_route() {
+    for sort in SortOrder::iter() {
+        let route = Route::default().with_filter(Filter::Active).with_sort(sort);
+        assert_eq!(Route::parse(&route.to_hash()).sort, sort);
+    }
+    assert_eq!(Route::parse("#/active?sort=alpha").sort, SortOrder::Alphabetical);
+    assert_eq!(Route::parse("#/active?sort=unknown").sort, SortOrder::Insertion);
+}
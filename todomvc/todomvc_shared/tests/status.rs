@@ -0,0 +1,135 @@
+use todomvc_shared::*;
+
+mod common;
+use common::{id_of, todos};
+
+fn status_of(todos: &Todos, description: &str) -> Status {
+    todos.get_entry_by_id(&id_of(todos, description)).unwrap().status.clone()
+}
+
+fn board(todos: &Todos) -> Vec<(String, Vec<String>)> {
+    todos
+        .board()
+        .into_iter()
+        .map(|column| {
+            let entries = column.entries.iter().map(|e| e.description.clone()).collect();
+            (column.status.label().to_string(), entries)
+        })
+        .collect()
+}
+
+#[test]
+fn migrates_the_completed_bool() {
+    let saved = r#"{
+        "entries": [
+            {"id": "67e55044-10b1-426f-9247-bb680e5fe0c8", "description": "Done", "completed": true},
+            {"id": "67e55044-10b1-426f-9247-bb680e5fe0c9", "description": "Not done", "completed": false,
+             "children": [{"id": "67e55044-10b1-426f-9247-bb680e5fe0ca", "description": "Sub", "completed": true}]}
+        ],
+        "filter": "Completed",
+        "schema_version": 5
+    }"#;
+    let mut todos: Todos = serde_json::from_str(saved).unwrap();
+    todos.migrate();
+    assert_eq!(status_of(&todos, "Done"), Status::Done);
+    assert_eq!(status_of(&todos, "Not done"), Status::Todo);
+    assert_eq!(status_of(&todos, "Sub"), Status::Done);
+    assert_eq!(todos.schema_version, SCHEMA_VERSION);
+
+    // Saved again, only the status is written
+    let json = serde_json::to_string(&todos).unwrap();
+    assert!(!json.contains("\"completed\":"));
+    assert!(json.contains("\"status\":\"Done\""));
+    let loaded: Todos = serde_json::from_str(&json).unwrap();
+    assert_eq!(status_of(&loaded, "Done"), Status::Done);
+}
+
+#[test]
+fn filters_map_onto_statuses() {
+    let mut todos = todos(&["a", "b", "c"]);
+    todos.set_status(&id_of(&todos, "b"), Status::InProgress).unwrap();
+    todos.set_status(&id_of(&todos, "c"), Status::Done).unwrap();
+
+    let visible = |todos: &mut Todos, filter| {
+        todos.filter = filter;
+        todos.get_visible_entries().iter().map(|e| e.description.clone()).collect::<Vec<_>>()
+    };
+    assert_eq!(visible(&mut todos, Filter::Active), ["a", "b"]);
+    assert_eq!(visible(&mut todos, Filter::Completed), ["c"]);
+    assert_eq!(todos.completed_count(), 1);
+
+    // Reopening sends a done entry back to Todo, but leaves other statuses alone
+    todos.set_completed(&id_of(&todos, "c"), false).unwrap();
+    todos.set_completed(&id_of(&todos, "b"), false).unwrap();
+    assert_eq!(status_of(&todos, "c"), Status::Todo);
+    assert_eq!(status_of(&todos, "b"), Status::InProgress);
+    let c = todos.get_entry_by_id(&id_of(&todos, "c")).unwrap();
+    assert_eq!(c.completed_at, None);
+}
+
+#[test]
+fn parents_take_the_status_of_their_subtasks() {
+    let mut todos = todos(&["Trip"]);
+    let trip = id_of(&todos, "Trip");
+    todos.add_subtask(&trip, "Book".to_string());
+    todos.add_subtask(&trip, "Pack".to_string());
+    assert_eq!(status_of(&todos, "Trip"), Status::Todo);
+
+    todos.set_status(&id_of(&todos, "Book"), Status::Done).unwrap();
+    assert_eq!(status_of(&todos, "Trip"), Status::InProgress);
+
+    todos.set_status(&id_of(&todos, "Pack"), Status::Done).unwrap();
+    assert_eq!(status_of(&todos, "Trip"), Status::Done);
+
+    // Moving a parent moves its subtasks
+    todos.set_status(&trip, Status::InProgress).unwrap();
+    assert_eq!(status_of(&todos, "Book"), Status::InProgress);
+    assert_eq!(status_of(&todos, "Trip"), Status::InProgress);
+}
+
+#[test]
+fn board_has_a_column_per_status() {
+    let mut todos = todos(&["Write", "Review #work", "Ship #work", "Trip"]);
+    todos.add_subtask(&id_of(&todos, "Trip"), "Pack".to_string());
+    assert!(todos.add_column("Review"));
+    assert!(!todos.add_column(" review "));
+    assert!(!todos.add_column("Done"));
+    assert!(!todos.add_column(""));
+
+    todos.set_status(&id_of(&todos, "Review"), Status::Custom("Review".to_string())).unwrap();
+    todos.set_status(&id_of(&todos, "Ship"), Status::Done).unwrap();
+    todos.set_status(&id_of(&todos, "Pack"), Status::InProgress).unwrap();
+    assert_eq!(
+        board(&todos),
+        [
+            ("To do".to_string(), vec!["Write".to_string()]),
+            ("In progress".to_string(), vec!["Pack".to_string()]),
+            ("Review".to_string(), vec!["Review".to_string()]),
+            ("Done".to_string(), vec!["Ship".to_string()]),
+        ]
+    );
+
+    // The tag applies to the board
+    todos.set_route(Route::parse("#/tag/work/board"));
+    assert_eq!(todos.filter, Filter::Board);
+    let cards: usize = todos.board().iter().map(|c| c.entries.len()).sum();
+    assert_eq!(cards, 2);
+
+    // Removing a column sends its entries back to Todo
+    assert!(todos.remove_column("Review"));
+    assert!(!todos.remove_column("Review"));
+    assert_eq!(status_of(&todos, "Review"), Status::Todo);
+    assert_eq!(todos.statuses().len(), 3);
+}
+
+#[test]
+fn moving_a_blocked_entry_to_done_fails() {
+    let mut todos = todos(&["Deploy", "Test"]);
+    let (deploy, test) = (id_of(&todos, "Deploy"), id_of(&todos, "Test"));
+    todos.add_dependency(&deploy, &test).unwrap();
+
+    assert!(todos.set_status(&deploy, Status::Done).is_err());
+    todos.set_status(&deploy, Status::InProgress).unwrap();
+    todos.set_status(&test, Status::Done).unwrap();
+    todos.set_status(&deploy, Status::Done).unwrap();
+}
@@ -0,0 +1,159 @@
+use todomvc_shared::*;
+
+mod common;
+use common::id_of;
+
+fn is_completed(todos: &Todos, description: &str) -> bool {
+    todos.get_entry_by_id(&id_of(todos, description)).unwrap().is_completed()
+}
+
+// Trip
+// ├── Book flights
+// ├── Pack
+// │   ├── Clothes
+// │   └── Charger
+// └── Visa
+// Groceries
+fn trip() -> Todos {
+    let mut todos = Todos::default();
+    todos.new_entry("Trip".to_string());
+    todos.new_entry("Groceries".to_string());
+    let trip = id_of(&todos, "Trip");
+    for description in ["Book flights", "Pack", "Visa"] {
+        assert!(todos.add_subtask(&trip, description.to_string()));
+    }
+    let pack = id_of(&todos, "Pack");
+    todos.add_subtask(&pack, "Clothes".to_string());
+    todos.add_subtask(&pack, "Charger".to_string());
+    todos
+}
+
+fn descriptions(entries: &[&TodoEntry]) -> Vec<String> {
+    entries.iter().map(|e| e.description.clone()).collect()
+}
+
+#[test]
+fn iterates_depth_first() {
+    let todos = trip();
+    let all: Vec<_> = todos.iter_tree().map(|e| e.description.as_str()).collect();
+    assert_eq!(all, ["Trip", "Book flights", "Pack", "Clothes", "Charger", "Visa", "Groceries"]);
+    let leaves: Vec<_> = todos.iter_leaves().map(|e| e.description.as_str()).collect();
+    assert_eq!(leaves, ["Book flights", "Clothes", "Charger", "Visa", "Groceries"]);
+}
+
+#[test]
+fn completion_rolls_up_and_cascades_down() {
+    let mut todos = trip();
+    for description in ["Clothes", "Charger"] {
+        todos.toggle_completion(&id_of(&todos, description)).unwrap();
+    }
+    assert!(is_completed(&todos, "Pack"));
+    assert!(!is_completed(&todos, "Trip"));
+
+    todos.toggle_completion(&id_of(&todos, "Book flights")).unwrap();
+    todos.toggle_completion(&id_of(&todos, "Visa")).unwrap();
+    assert!(is_completed(&todos, "Trip"));
+
+    // Reopening a subtask reopens its ancestors
+    todos.toggle_completion(&id_of(&todos, "Charger")).unwrap();
+    assert!(!is_completed(&todos, "Pack"));
+    assert!(!is_completed(&todos, "Trip"));
+    assert!(is_completed(&todos, "Book flights"));
+
+    // Completing a parent completes every subtask
+    todos.toggle_completion(&id_of(&todos, "Trip")).unwrap();
+    assert!(todos.get_entry_by_id(&id_of(&todos, "Trip")).unwrap().iter_tree().all(|e| e.is_completed()));
+
+    // A new subtask reopens its completed parent
+    todos.add_subtask(&id_of(&todos, "Pack"), "Adapter".to_string());
+    assert!(!is_completed(&todos, "Pack"));
+    assert!(!is_completed(&todos, "Trip"));
+}
+
+#[test]
+fn counts_only_entries_without_subtasks() {
+    let mut todos = trip();
+    assert_eq!(todos.entry_count(), 5);
+    assert_eq!(todos.completed_count(), 0);
+
+    todos.toggle_completion(&id_of(&todos, "Pack")).unwrap();
+    assert_eq!(todos.completed_count(), 2);
+    let trip = todos.get_entry_by_id(&id_of(&todos, "Trip")).unwrap();
+    assert_eq!(trip.subtask_progress(), Some((2, 4)));
+    assert_eq!(todos.get_entry_by_id(&id_of(&todos, "Visa")).unwrap().subtask_progress(), None);
+
+    todos.set_completed_for_all(true);
+    assert!(todos.is_all_completed());
+    assert_eq!(todos.completed_count(), todos.entry_count());
+}
+
+#[test]
+fn removing_and_clearing_work_at_every_depth() {
+    let mut todos = trip();
+    todos.toggle_completion(&id_of(&todos, "Clothes")).unwrap();
+    todos.toggle_completion(&id_of(&todos, "Visa")).unwrap();
+
+    // Removing the last active subtask completes the parent
+    todos.remove_by_id(&id_of(&todos, "Charger"));
+    assert!(is_completed(&todos, "Pack"));
+
+    todos.clear_completed();
+    let all: Vec<_> = todos.iter_tree().map(|e| e.description.as_str()).collect();
+    assert_eq!(all, ["Trip", "Book flights", "Groceries"]);
+
+    todos.remove_by_id(&id_of(&todos, "Trip"));
+    assert_eq!(todos.entry_count(), 1);
+}
+
+#[test]
+fn filters_keep_the_path_to_matching_subtasks() {
+    let mut todos = trip();
+    todos.toggle_completion(&id_of(&todos, "Clothes")).unwrap();
+
+    todos.set_route(Route::parse("#/completed"));
+    let visible = todos.get_visible_entries();
+    assert_eq!(descriptions(&visible), ["Trip"]);
+    let children = todos.get_visible_children(visible[0]);
+    assert_eq!(descriptions(&children), ["Pack"]);
+    assert_eq!(descriptions(&todos.get_visible_children(children[0])), ["Clothes"]);
+
+    todos.set_route(Route::default().with_query("charger"));
+    assert_eq!(descriptions(&todos.get_visible_entries()), ["Trip"]);
+
+    todos.set_route(Route::default().with_sort(SortOrder::Alphabetical));
+    let trip = todos.get_entry_by_id(&id_of(&todos, "Trip")).unwrap();
+    assert_eq!(descriptions(&todos.get_visible_children(trip)), ["Book flights", "Pack", "Visa"]);
+}
+
+#[test]
+fn collapsed_entries_hide_their_subtasks() {
+    let mut todos = trip();
+    let pack = id_of(&todos, "Pack");
+    todos.toggle_collapsed(&pack);
+    assert!(todos.get_visible_children(todos.get_entry_by_id(&pack).unwrap()).is_empty());
+    // Counts do not depend on what is rendered
+    assert_eq!(todos.entry_count(), 5);
+
+    // Adding a subtask expands the parent
+    todos.add_subtask(&pack, "Adapter".to_string());
+    assert_eq!(todos.get_visible_children(todos.get_entry_by_id(&pack).unwrap()).len(), 3);
+}
+
+#[test]
+fn nested_entries_survive_serialization() {
+    let mut todos = trip();
+    todos.toggle_collapsed(&id_of(&todos, "Pack"));
+    let json = serde_json::to_string(&todos).unwrap();
+    let loaded: Todos = serde_json::from_str(&json).unwrap();
+    let pack = loaded.get_entry_by_id(&id_of(&todos, "Pack")).unwrap();
+    assert!(pack.collapsed);
+    assert_eq!(pack.children.len(), 2);
+
+    // Entries saved before subtasks existed have none
+    let old = r#"{"entries":[{"id":"67e55044-10b1-426f-9247-bb680e5fe0c8","description":"Old","completed":false}],"filter":"All","schema_version":3}"#;
+    let mut old: Todos = serde_json::from_str(old).unwrap();
+    old.migrate();
+    assert!(!old.entries[0].has_subtasks());
+    assert!(!old.entries[0].collapsed);
+    assert_eq!(old.schema_version, SCHEMA_VERSION);
+}
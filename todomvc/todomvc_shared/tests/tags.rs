@@ -0,0 +1,68 @@
+use std::collections::BTreeSet;
+
+use todomvc_shared::*;
+
+fn set(tags: &[&str]) -> BTreeSet<String> {
+    tags.iter().map(|tag| tag.to_string()).collect()
+}
+
+#[test]
+fn extracts_tags_from_descriptions() {
+    let cases = [
+        ("Call Bob #work #Phone", "Call Bob", &["phone", "work"][..]),
+        ("Fix issue #12", "Fix issue #12", &[]),
+        ("#work   review  #dev-ops", "review", &["dev-ops", "work"]),
+        ("#just #tags", "#just #tags", &["just", "tags"]),
+        ("C# is not a tag, nor is #", "C# is not a tag, nor is #", &[]),
+    ];
+    for (text, description, tags) in cases {
+        assert_eq!(extract_tags(text), (description.to_string(), set(tags)), "{text}");
+    }
+}
+
+#[test]
+fn counts_and_filters_by_tag() {
+    let mut todos = Todos::default();
+    todos.new_entry_parsed("Write report #work".to_string());
+    todos.new_entry_parsed("Review PR #work #dev".to_string());
+    todos.new_entry_parsed("Buy milk #errands".to_string());
+    todos.new_entry("Nap".to_string());
+
+    let index = todos.tag_index();
+    assert_eq!(index.iter().collect::<Vec<_>>(), [("dev", 1), ("errands", 1), ("work", 2)]);
+    assert_eq!(index.count("nope"), 0);
+
+    todos.set_route(Route::parse("#/tag/work"));
+    let visible: Vec<_> = todos.get_visible_entries().iter().map(|e| e.description.clone()).collect();
+    assert_eq!(visible, ["Write report", "Review PR"]);
+
+    let id = todos.entries[0].id;
+    todos.toggle_completion(&id).unwrap();
+    todos.set_route(Route::parse("#/tag/work/active"));
+    assert_eq!(todos.get_visible_entries().len(), 1);
+}
+
+#[test]
+fn tag_routes_round_trip() {
+    let route = Route::parse("#/tag/work/completed?q=milk&sort=alpha");
+    assert_eq!(route.tag.as_deref(), Some("work"));
+    assert_eq!(route.filter, Filter::Completed);
+    assert_eq!(route.to_hash(), "#/tag/work/completed?q=milk&sort=alpha");
+
+    assert_eq!(Route::parse("#/tag/work").to_hash(), "#/tag/work");
+    assert_eq!(Route::parse("#/tag/").tag, None);
+    let tagged = Route::default().with_tag(Some("écrire"));
+    assert_eq!(Route::parse(&tagged.to_hash()), tagged);
+    assert_eq!(tagged.with_tag(None).to_hash(), "#/");
+}
+
+#[test]
+fn migration_extracts_tags() {
+    let mut todos = Todos::default();
+    todos.new_entry("Call Bob".to_string());
+    todos.entries[0].description = "Call #Bob".to_string();
+    todos.schema_version = 1;
+    todos.migrate();
+    assert_eq!(todos.entries[0].tags, set(&["bob"]));
+    assert_eq!(todos.schema_version, SCHEMA_VERSION);
+}
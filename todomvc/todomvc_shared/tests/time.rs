@@ -0,0 +1,97 @@
+use std::collections::BTreeSet;
+use std::rc::Rc;
+
+use todomvc_shared::*;
+
+const NOW: Timestamp = Timestamp(1_700_000_000_000);
+
+fn clock() -> Rc<ManualClock> {
+    let clock = Rc::new(ManualClock::new(NOW));
+    set_clock(clock.clone());
+    clock
+}
+
+#[test]
+fn migration_from_version_0_sets_timestamps() {
+    clock();
+    let saved = r#"{
+        "entries": [
+            {"id": "67e55044-10b1-426f-9247-bb680e5fe0c8", "description": "Done", "completed": true},
+            {"id": "67e55044-10b1-426f-9247-bb680e5fe0c9", "description": "Not done", "completed": false}
+        ],
+        "filter": "All"
+    }"#;
+    let mut todos: Todos = serde_json::from_str(saved).unwrap();
+    assert_eq!(todos.schema_version, 0);
+    todos.migrate();
+    let done = &todos.entries[0];
+    assert_eq!((done.created_at, done.updated_at, done.completed_at), (NOW, NOW, Some(NOW)));
+    let active = &todos.entries[1];
+    assert_eq!((active.created_at, active.updated_at, active.completed_at), (NOW, NOW, None));
+    assert_eq!(todos.schema_version, SCHEMA_VERSION);
+}
+
+#[test]
+fn migration_from_version_1_extracts_tags() {
+    clock();
+    let saved = r#"{
+        "entries": [
+            {"id": "67e55044-10b1-426f-9247-bb680e5fe0c8", "description": "Call #Bob about #work",
+             "completed": false, "created_at": 1600000000000, "updated_at": 1600000000000}
+        ],
+        "filter": "All",
+        "schema_version": 1
+    }"#;
+    let mut todos: Todos = serde_json::from_str(saved).unwrap();
+    todos.migrate();
+    let expected: BTreeSet<String> = ["bob", "work"].iter().map(|tag| tag.to_string()).collect();
+    assert_eq!(todos.entries[0].tags, expected);
+    // The timestamps that were recorded are kept
+    assert_eq!(todos.entries[0].created_at, Timestamp(1_600_000_000_000));
+
+    // From version 2 on, the tags are what was saved
+    todos.entries[0].tags.clear();
+    todos.migrate();
+    assert!(todos.entries[0].tags.is_empty());
+}
+
+#[test]
+fn relative_times() {
+    let cases = [
+        (0, "just now"),
+        (59 * SECOND, "just now"),
+        (MINUTE, "1 minute ago"),
+        (3 * HOUR + 20 * MINUTE, "3 hours ago"),
+        (DAY, "1 day ago"),
+        (45 * DAY, "1 month ago"),
+        (800 * DAY, "2 years ago"),
+        (-2 * DAY, "in 2 days"),
+        (-MINUTE, "in 1 minute"),
+    ];
+    for (ago, expected) in cases {
+        assert_eq!(relative_time(Timestamp(NOW.0 - ago), NOW), expected, "{ago} ms ago");
+    }
+}
+
+#[test]
+fn labels_describe_the_latest_change() {
+    let clock = clock();
+    let mut todos = Todos::default();
+    todos.new_entry("Buy milk".to_string());
+    let id = todos.entries[0].id;
+    clock.advance(5 * MINUTE);
+    assert_eq!(todos.entries[0].relative_time_label(now()), "created 5 minutes ago");
+
+    todos.entries[0].set_description("Buy oat milk".to_string());
+    clock.advance(2 * HOUR);
+    assert_eq!(todos.entries[0].relative_time_label(now()), "updated 2 hours ago");
+
+    todos.toggle_completion(&id).unwrap();
+    clock.advance(DAY);
+    assert_eq!(todos.entries[0].relative_time_label(now()), "completed 1 day ago");
+
+    // Reopening forgets when it was completed
+    todos.toggle_completion(&id).unwrap();
+    assert_eq!(todos.entries[0].completed_at, None);
+    assert_eq!(todos.entries[0].relative_time_label(now()), "updated just now");
+}
@@ -1,31 +1,54 @@
 use wasm_bindgen::UnwrapThrowExt;
-use web_sys::HtmlInputElement as InputElement;
+use web_sys::{HtmlInputElement as InputElement, HtmlSelectElement};
 use strum::IntoEnumIterator;
 
-use yew::events::{FocusEvent, KeyboardEvent};
+use yew::events::{DragEvent, Event, FocusEvent, InputEvent, KeyboardEvent, MouseEvent};
 use yew::html::Scope;
-use yew::{classes, html, Classes, Component, Context, Html, NodeRef, TargetCast};
+use yew::{classes, html, Callback, Classes, Component, Context, Html, NodeRef, TargetCast};
 
-use todomvc_shared::{TodoEntry, Filter, Todos};
+use todomvc_shared::{ArchivedEntry, Column, Date, TodoEntry, Filter, SortOrder, Status, TodoLists};
 
 const KEY: &str = "yew.todomvc.self";
 
 pub enum Msg {
     Add(String),
     Edit((uuid::Uuid, String)),
+    SetDue(uuid::Uuid, Option<Date>),
+    CyclePriority(uuid::Uuid),
     Remove(uuid::Uuid),
     SetFilter(Filter),
+    SetSort(SortOrder),
+    SetTag(Option<String>),
+    SetQuery(String),
     ToggleAll,
     ToggleEdit(uuid::Uuid),
     Toggle(uuid::Uuid),
+    ToggleCollapsed(uuid::Uuid),
+    StartSubtask(uuid::Uuid),
+    AddSubtask(Option<String>),
+    AddBlocker(uuid::Uuid, uuid::Uuid),
+    RemoveBlocker(uuid::Uuid, uuid::Uuid),
+    DismissDependencyError,
+    DragCard(uuid::Uuid),
+    DropCard(Status),
+    AddColumn(String),
+    RemoveColumn(String),
+    Restore(uuid::Uuid),
+    DeleteArchived(uuid::Uuid),
+    SetRetention(Option<u32>),
     ClearCompleted,
     Focus,
+    ToggleShowTimes,
 }
 
 pub struct App {
-    data: Todos,
+    data: TodoLists,
     edit_value: String,
     editing_id: Option<uuid::Uuid>,
+    adding_subtask_to: Option<uuid::Uuid>,
+    dependency_error: Option<String>,
+    dragging_id: Option<uuid::Uuid>,
+    show_times: bool,
 
     focus_ref: NodeRef,
 }
@@ -37,9 +60,13 @@ impl Component for App {
     fn create(_ctx: &Context<Self>) -> Self {
         let focus_ref = NodeRef::default();
         Self {
-            data: Todos::load_todos(KEY),
+            data: TodoLists::load_todos(KEY),
             edit_value: "".into(),
             editing_id: None,
+            adding_subtask_to: None,
+            dependency_error: None,
+            dragging_id: None,
+            show_times: false,
             focus_ref
         }
     }
@@ -47,22 +74,42 @@ impl Component for App {
     fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
         match msg {
             Msg::Add(description) => {
-                self.data.new_entry(description);
+                self.data.new_entry_parsed(description);
             }
             Msg::Edit((id, edit_value)) => {
                 if edit_value.is_empty() {
                     self.data.remove_by_id(&id);
                 } else if let Some(e) = self.data.get_entry_by_id_mut(&id) {
-                    e.description = edit_value;
+                    e.set_description(edit_value);
                 }
                 self.edit_value = "".to_string();
             }
+            Msg::SetDue(id, due) => {
+                if let Some(e) = self.data.get_entry_by_id_mut(&id) {
+                    e.set_due(due);
+                }
+            }
+            Msg::CyclePriority(id) => {
+                if let Some(e) = self.data.get_entry_by_id_mut(&id) {
+                    e.cycle_priority();
+                }
+            }
             Msg::Remove(id) => {
                 self.data.remove_by_id(&id);
             }
             Msg::SetFilter(filter) => {
                 self.data.filter = filter;
             }
+            Msg::SetSort(sort) => {
+                self.data.sort = sort;
+            }
+            Msg::SetTag(tag) => {
+                self.data.tag = tag;
+            }
+            Msg::SetQuery(query) => {
+                self.data.query = query;
+                self.data.route().replace_location_hash();
+            }
             Msg::ToggleEdit(id) => {
                 self.edit_value = self.data.get_entry_by_id_mut(&id).unwrap_throw().description.clone();
                 self.editing_id = Some(id);
@@ -72,7 +119,51 @@ impl Component for App {
                 self.data.set_completed_for_all(status);
             }
             Msg::Toggle(id) => {
-                self.data.get_entry_by_id_mut(&id).unwrap_throw().toggle_completion();
+                self.dependency_error = self.data.toggle_completion(&id).err().map(|e| e.to_string());
+            }
+            Msg::AddBlocker(id, blocker_id) => {
+                self.dependency_error = self.data.add_dependency(&id, &blocker_id).err().map(|e| e.to_string());
+            }
+            Msg::RemoveBlocker(id, blocker_id) => {
+                self.data.remove_dependency(&id, &blocker_id);
+            }
+            Msg::DismissDependencyError => {
+                self.dependency_error = None;
+            }
+            Msg::DragCard(id) => {
+                self.dragging_id = Some(id);
+            }
+            Msg::DropCard(status) => {
+                if let Some(id) = self.dragging_id.take() {
+                    self.dependency_error = self.data.set_status(&id, status).err().map(|e| e.to_string());
+                }
+            }
+            Msg::AddColumn(name) => {
+                self.data.add_column(&name);
+            }
+            Msg::RemoveColumn(name) => {
+                self.data.remove_column(&name);
+            }
+            Msg::Restore(id) => {
+                self.data.restore(&id);
+            }
+            Msg::DeleteArchived(id) => {
+                self.data.delete_archived(&id);
+            }
+            Msg::SetRetention(days) => {
+                self.data.set_retention_days(days);
+            }
+            Msg::ToggleCollapsed(id) => {
+                self.data.toggle_collapsed(&id);
+            }
+            Msg::StartSubtask(id) => {
+                self.adding_subtask_to = Some(id);
+            }
+            Msg::AddSubtask(description) => {
+                // Both Enter and the blur that follows end up here
+                if let (Some(id), Some(description)) = (self.adding_subtask_to.take(), description) {
+                    self.data.add_subtask_parsed(&id, description);
+                }
             }
             Msg::ClearCompleted => {
                 self.data.clear_completed();
@@ -82,13 +173,16 @@ impl Component for App {
                     input.focus().unwrap();
                 }
             }
+            Msg::ToggleShowTimes => {
+                self.show_times = !self.show_times;
+            }
         }
-        self.data.save_todos(KEY).unwrap_throw();
+        self.data.save().unwrap_throw();
         true
     }
 
     fn view(&self, ctx: &Context<Self>) -> Html {
-        let hidden_class = if self.data.entries.is_empty() {
+        let hidden_class = if self.data.entries.is_empty() && self.data.archive.is_empty() {
             "hidden"
         } else {
             ""
@@ -109,9 +203,24 @@ impl Component for App {
                             onclick={ctx.link().callback(|_| Msg::ToggleAll)}
                         />
                         <label for="toggle-all" />
-                        <ul class="todo-list">
-                            { for self.data.get_filtered_entries().map(|e| self.view_entry(e, ctx.link())) }
-                        </ul>
+                        if let Some(error) = &self.dependency_error {
+                            <p
+                                class="dependency-error"
+                                title="Click to dismiss"
+                                onclick={ctx.link().callback(|_| Msg::DismissDependencyError)}
+                            >
+                                { error }
+                            </p>
+                        }
+                        if self.data.filter == Filter::Board {
+                            { self.view_board(ctx.link()) }
+                        } else if self.data.filter == Filter::Archive {
+                            { self.view_archive(ctx.link()) }
+                        } else {
+                            <ul class="todo-list">
+                                { for self.data.get_visible_entries().into_iter().map(|e| self.view_entry(e, ctx.link())) }
+                            </ul>
+                        }
                     </section>
                     <footer class={classes!("footer", hidden_class)}>
                         <span class="todo-count">
@@ -124,9 +233,25 @@ impl Component for App {
                         <button class="clear-completed" onclick={ctx.link().callback(|_| Msg::ClearCompleted)}>
                             { format!("Clear completed ({})", self.data.completed_count()) }
                         </button>
+                        <ul class="sort-orders">
+                            { for SortOrder::iter().map(|sort| self.view_sort(sort, ctx.link())) }
+                        </ul>
+                        <ul class="tags">
+                            { for self.data.tag_index().iter().map(|(tag, count)| self.view_tag(tag, count, ctx.link())) }
+                        </ul>
                     </footer>
                 </section>
                 <footer class="info">
+                    <p>
+                        <label>
+                            <input
+                                type="checkbox"
+                                checked={self.show_times}
+                                onclick={ctx.link().callback(|_| Msg::ToggleShowTimes)}
+                            />
+                            { " Show relative times" }
+                        </label>
+                    </p>
                     <p>{ "Double-click to edit a todo" }</p>
                     <p>{ "Written by " }<a href="https://github.com/DenisKolodin/" target="_blank">{ "Denis Kolodin" }</a></p>
                     <p>{ "Part of " }<a href="http://todomvc.com/" target="_blank">{ "TodoMVC" }</a></p>
@@ -146,7 +271,7 @@ impl App {
         html! {
             <li>
                 <a class={cls}
-                   href={filter.as_href()}
+                   href={self.data.route().with_filter(filter).to_hash()}
                    onclick={link.callback(move |_| Msg::SetFilter(filter))}
                 >
                     { filter }
@@ -155,6 +280,41 @@ impl App {
         }
     }
 
+    fn view_sort(&self, sort: SortOrder, link: &Scope<Self>) -> Html {
+        let cls = if self.data.sort == sort {
+            "selected"
+        } else {
+            "not-selected"
+        };
+        html! {
+            <li>
+                <a class={cls}
+                   href={self.data.route().with_sort(sort).to_hash()}
+                   onclick={link.callback(move |_| Msg::SetSort(sort))}
+                >
+                    { sort.label() }
+                </a>
+            </li>
+        }
+    }
+
+    fn view_tag(&self, tag: &str, count: usize, link: &Scope<Self>) -> Html {
+        let selected = self.data.tag.as_deref() == Some(tag);
+        // Clicking the selected tag again shows all tags
+        let target = (!selected).then(|| tag.to_string());
+        let href = self.data.route().with_tag(target.as_deref()).to_hash();
+        html! {
+            <li>
+                <a class={if selected { "selected" } else { "not-selected" }}
+                   {href}
+                   onclick={link.callback(move |_| Msg::SetTag(target.clone()))}
+                >
+                    { "#" }{ tag }<span class="count">{ count }</span>
+                </a>
+            </li>
+        }
+    }
+
     fn view_input(&self, link: &Scope<Self>) -> Html {
         let onkeypress = link.batch_callback(|e: KeyboardEvent| {
             if e.key() == "Enter" {
@@ -166,19 +326,160 @@ impl App {
                 None
             }
         });
+        let oninput = link.callback(|e: InputEvent| {
+            let input: InputElement = e.target_unchecked_into();
+            Msg::SetQuery(input.value())
+        });
+        let query_error = self.data.parse_query().err();
         html! {
-            // You can use standard Rust comments. One line:
-            // <li></li>
-            <input
-                class="new-todo"
-                placeholder="What needs to be done?"
-                {onkeypress}
-            />
-            /* Or multiline:
-            <ul>
-                <li></li>
-            </ul>
-            */
+            <>
+                // You can use standard Rust comments. One line:
+                // <li></li>
+                <input
+                    class="new-todo"
+                    placeholder="What needs to be done?"
+                    {onkeypress}
+                />
+                /* Or multiline:
+                <ul>
+                    <li></li>
+                </ul>
+                */
+                <input
+                    class={classes!("search", query_error.is_some().then_some("invalid"))}
+                    placeholder="Search, e.g. is:active tag:work \"buy milk\""
+                    value={self.data.query.clone()}
+                    {oninput}
+                />
+                if let Some(error) = query_error {
+                    <p class="query-error">{ error.to_string() }</p>
+                }
+            </>
+        }
+    }
+
+    // The board uses the HTML5 drag and drop events, a card is dropped on a column
+    fn view_board(&self, link: &Scope<Self>) -> Html {
+        let onkeypress = link.batch_callback(|e: KeyboardEvent| {
+            (e.key() == "Enter").then(|| {
+                let input: InputElement = e.target_unchecked_into();
+                let name = input.value();
+                input.set_value("");
+                Msg::AddColumn(name)
+            })
+        });
+        html! {
+            <div class="board">
+                <div class="columns">
+                    { for self.data.board().into_iter().map(|column| self.view_column(column, link)) }
+                </div>
+                <input class="new-column" placeholder="Add a column" {onkeypress} />
+            </div>
+        }
+    }
+
+    fn view_archive(&self, link: &Scope<Self>) -> Html {
+        // Retention periods to choose from, in days, `None` is never
+        const RETENTION_CHOICES: [Option<u32>; 4] = [Some(7), Some(30), Some(90), None];
+        let retention_days = self.data.archive.retention_days;
+        let onchange = link.callback(|e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            Msg::SetRetention(select.value().parse().ok())
+        });
+        html! {
+            <div class="archive">
+                <label class="retention">
+                    { "Delete archived todos after " }
+                    <select {onchange}>
+                        { for RETENTION_CHOICES.iter().map(|&days| html! {
+                            <option
+                                value={days.map(|days| days.to_string()).unwrap_or_default()}
+                                selected={days == retention_days}
+                            >
+                                { days.map(|days| format!("{days} days")).unwrap_or_else(|| "never".to_string()) }
+                            </option>
+                        }) }
+                    </select>
+                </label>
+                <ul class="archive-list">
+                    { for self.data.archive.entries.iter().map(|e| self.view_archived(e, link)) }
+                </ul>
+            </div>
+        }
+    }
+
+    fn view_archived(&self, archived: &ArchivedEntry, link: &Scope<Self>) -> Html {
+        let entry = &archived.entry;
+        let id = entry.id;
+        html! {
+            <li class={classes!(entry.is_completed().then_some("completed"))}>
+                <span class="description">{ &entry.description }</span>
+                <span class="archived-at">
+                    { format!("archived {}", todomvc_shared::relative_time(archived.archived_at, todomvc_shared::now())) }
+                </span>
+                <button class="restore" title="Put it back in the list" onclick={link.callback(move |_| Msg::Restore(id))}>
+                    { "Restore" }
+                </button>
+                <button class="delete" title="Delete for good" onclick={link.callback(move |_| Msg::DeleteArchived(id))}>
+                    { "Delete" }
+                </button>
+            </li>
+        }
+    }
+
+    fn view_column(&self, column: Column, link: &Scope<Self>) -> Html {
+        let Column { status, entries } = column;
+        let label = status.label().to_string();
+        let custom_name = match &status {
+            Status::Custom(name) => Some(name.clone()),
+            _ => None,
+        };
+        html! {
+            <section
+                class="column"
+                ondragover={Callback::from(|e: DragEvent| e.prevent_default())}
+                ondrop={link.callback(move |e: DragEvent| {
+                    e.prevent_default();
+                    Msg::DropCard(status.clone())
+                })}
+            >
+                <h2>
+                    { label }
+                    <span class="count">{ entries.len() }</span>
+                    if let Some(name) = custom_name {
+                        <button
+                            class="remove-column"
+                            title="Remove the column, its todos go back to To do"
+                            onclick={link.callback(move |_| Msg::RemoveColumn(name.clone()))}
+                        >
+                            { "×" }
+                        </button>
+                    }
+                </h2>
+                <ul class="cards">
+                    { for entries.into_iter().map(|e| self.view_card(e, link)) }
+                </ul>
+            </section>
+        }
+    }
+
+    fn view_card(&self, entry: &TodoEntry, link: &Scope<Self>) -> Html {
+        let mut class = Classes::from("card");
+        if entry.is_completed() {
+            class.push("completed");
+        }
+        class.push(entry.priority.class_name());
+        if self.data.is_blocked(&entry.id) {
+            class.push("blocked");
+        }
+        let id = entry.id;
+        html! {
+            <li {class} draggable="true" ondragstart={link.callback(move |_: DragEvent| Msg::DragCard(id))}>
+                <span class="description">{ &entry.description }</span>
+                if let Some(label) = entry.due_label(Date::today()) {
+                    <span class="due">{ label }</span>
+                }
+            </li>
         }
     }
 
@@ -187,9 +488,29 @@ impl App {
         if self.editing_id == Some(entry.id) {
             class.push(" editing");
         }
-        if entry.completed {
+        if entry.is_completed() {
             class.push(" completed");
         }
+        let today = Date::today();
+        if entry.is_overdue(today) {
+            class.push(" overdue");
+        }
+        class.push(entry.priority.class_name());
+        class.push(entry.status.class_name());
+        if entry.has_subtasks() {
+            class.push("has-subtasks");
+        }
+        if entry.collapsed {
+            class.push("collapsed");
+        }
+        let blockers = match entry.is_completed() {
+            true => Vec::new(),
+            false => self.data.active_blockers(&entry.id),
+        };
+        let blocked = !blockers.is_empty();
+        if blocked {
+            class.push("blocked");
+        }
         let id = entry.id;
         html! {
             <li {class}>
@@ -197,17 +518,168 @@ impl App {
                     <input
                         type="checkbox"
                         class="toggle"
-                        checked={entry.completed}
-                        onclick={link.callback(move |_| Msg::Toggle(id))}
+                        checked={entry.is_completed()}
+                        onclick={link.callback(move |e: MouseEvent| {
+                            // Keep the box unchecked, the update only reports why
+                            if blocked {
+                                e.prevent_default();
+                            }
+                            Msg::Toggle(id)
+                        })}
                     />
-                    <label ondblclick={link.callback(move |_| Msg::ToggleEdit(id))}>{ &entry.description }</label>
+                    <label ondblclick={link.callback(move |_| Msg::ToggleEdit(id))}>{ self.view_description(entry) }</label>
+                    <button
+                        class="priority"
+                        title={format!("Priority: {}", entry.priority.label())}
+                        onclick={link.callback(move |_| Msg::CyclePriority(id))}
+                    >
+                        { entry.priority.marker() }
+                    </button>
                     <button class="destroy" onclick={link.callback(move |_| Msg::Remove(id))} />
+                    <button
+                        class="add-subtask"
+                        title="Add a subtask"
+                        onclick={link.callback(move |_| Msg::StartSubtask(id))}
+                    >
+                        { "+" }
+                    </button>
+                    if entry.has_subtasks() {
+                        <button
+                            class="collapse"
+                            title={if entry.collapsed { "Expand" } else { "Collapse" }}
+                            onclick={link.callback(move |_| Msg::ToggleCollapsed(id))}
+                        >
+                            { if entry.collapsed { "▸" } else { "▾" } }
+                        </button>
+                    }
+                    if blocked {
+                        <span class="blocked-by">
+                            { format!("blocked by {}", blockers.iter().map(|e| e.description.as_str()).collect::<Vec<_>>().join(", ")) }
+                        </span>
+                    }
+                    if let Some((completed, total)) = entry.subtask_progress() {
+                        <span class="subtask-progress">{ format!("{completed}/{total} subtasks") }</span>
+                    }
+                    if let Some(label) = entry.due_label(today) {
+                        <span class="due">{ label }</span>
+                    }
+                    if let Some(recurrence) = entry.recurrence {
+                        <span class="recurrence" title="Completing it adds the next one">{ recurrence.to_string() }</span>
+                    }
+                    { self.view_entry_time(entry) }
                 </div>
                 { self.view_entry_edit_input(entry, link) }
+                { self.view_subtask_input(entry, link) }
+                // Recursive: each subtask is rendered by `view_entry` too
+                <ul class="subtasks">
+                    { for self.data.get_visible_children(entry).into_iter().map(|e| self.view_entry(e, link)) }
+                </ul>
             </li>
         }
     }
 
+    // The declared blockers of the entry being edited, and a select to add one
+    fn view_entry_blockers(&self, entry: &TodoEntry, link: &Scope<Self>) -> Html {
+        let id = entry.id;
+        let blockers = self
+            .data
+            .dependencies
+            .blockers(&id)
+            .filter_map(|blocker_id| self.data.get_entry_by_id(blocker_id));
+        // A todo cannot be blocked by itself, its subtasks or its ancestors
+        let path = self.data.path_to(&id).unwrap_or_default();
+        let candidates = self.data.iter_tree().filter(|e| {
+            !path.iter().any(|a| a.id == e.id)
+                && !entry.iter_tree().any(|d| d.id == e.id)
+                && !self.data.dependencies.contains(&id, &e.id)
+        });
+        let onchange = link.batch_callback(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            let blocker_id = uuid::Uuid::parse_str(&select.value()).ok();
+            select.set_value("");
+            blocker_id.map(|blocker_id| Msg::AddBlocker(id, blocker_id))
+        });
+        html! {
+            <>
+                <ul class="edit-blockers">
+                    { for blockers.map(|blocker| {
+                        let blocker_id = blocker.id;
+                        html! {
+                            <li class={classes!(blocker.is_completed().then_some("completed"))}>
+                                { &blocker.description }
+                                <button
+                                    class="remove-blocker"
+                                    title="Not blocked by this anymore"
+                                    onclick={link.callback(move |_| Msg::RemoveBlocker(id, blocker_id))}
+                                >
+                                    { "×" }
+                                </button>
+                            </li>
+                        }
+                    }) }
+                </ul>
+                <select class="edit-blocker" {onchange}>
+                    <option value="">{ "Blocked by…" }</option>
+                    { for candidates.map(|e| html! {
+                        <option value={e.id.to_string()}>{ &e.description }</option>
+                    }) }
+                </select>
+            </>
+        }
+    }
+
+    fn view_subtask_input(&self, entry: &TodoEntry, link: &Scope<Self>) -> Html {
+        if self.adding_subtask_to != Some(entry.id) {
+            return html! {};
+        }
+        let add = |input: InputElement| {
+            let value = input.value();
+            let value = value.trim();
+            Msg::AddSubtask((!value.is_empty()).then(|| value.to_string()))
+        };
+        let onblur = link.callback(move |e: FocusEvent| add(e.target_unchecked_into()));
+        let onkeydown = link.batch_callback(move |e: KeyboardEvent| match e.key().as_str() {
+            "Enter" => Some(add(e.target_unchecked_into())),
+            "Escape" => Some(Msg::AddSubtask(None)),
+            _ => None,
+        });
+        html! {
+            <input
+                class="new-subtask"
+                placeholder="Add a subtask"
+                autofocus=true
+                {onblur}
+                {onkeydown}
+            />
+        }
+    }
+
+    fn view_description(&self, entry: &TodoEntry) -> Html {
+        let search = self.data.search();
+        if search.is_empty() {
+            return html! { { &entry.description } };
+        }
+        html! {
+            { for search.split_matches(&entry.description).into_iter().map(|segment| {
+                if segment.matched {
+                    html! { <span class="highlight">{ segment.text }</span> }
+                } else {
+                    html! { { segment.text } }
+                }
+            }) }
+        }
+    }
+
+    fn view_entry_time(&self, entry: &TodoEntry) -> Html {
+        if self.show_times {
+            html! {
+                <span class="time">{ entry.relative_time_label(todomvc_shared::now()) }</span>
+            }
+        } else {
+            html! {}
+        }
+    }
+
     fn view_entry_edit_input(&self, entry: &TodoEntry, link: &Scope<Self>) -> Html {
         let id = entry.id;
         let edit = move |input: InputElement| {
@@ -222,17 +694,32 @@ impl App {
             (e.key() == "Enter").then(|| edit(e.target_unchecked_into()))
         });
 
+        // An empty value clears the due date
+        let onchange = link.callback(move |e: Event| {
+            let input: InputElement = e.target_unchecked_into();
+            Msg::SetDue(id, Date::parse_iso(&input.value()))
+        });
+
         if self.editing_id == Some(entry.id) {
             html! {
-                <input
-                    class="edit"
-                    type="text"
-                    ref={self.focus_ref.clone()}
-                    value={self.edit_value.clone()}
-                    onmouseover={link.callback(|_| Msg::Focus)}
-                    {onblur}
-                    {onkeypress}
-                />
+                <>
+                    <input
+                        class="edit"
+                        type="text"
+                        ref={self.focus_ref.clone()}
+                        value={self.edit_value.clone()}
+                        onmouseover={link.callback(|_| Msg::Focus)}
+                        {onblur}
+                        {onkeypress}
+                    />
+                    <input
+                        class="edit-due"
+                        type="date"
+                        value={entry.due.map(|due| due.to_string()).unwrap_or_default()}
+                        {onchange}
+                    />
+                    { self.view_entry_blockers(entry, link) }
+                </>
             }
         } else {
             html! { <input type="hidden" /> }